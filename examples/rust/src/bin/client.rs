@@ -29,13 +29,15 @@ use {
             subscribe_request_filter_accounts_filter::Filter as AccountsFilterOneof,
             subscribe_request_filter_accounts_filter_lamports::Cmp as AccountsFilterLamports,
             subscribe_request_filter_accounts_filter_memcmp::Data as AccountsFilterMemcmpOneof,
-            subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
-            SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
-            SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterLamports,
+            subscribe_update::UpdateOneof, subscribe_update_notice::Notice as NoticeOneof,
+            CommitmentLevel, SubscribeRequest, SubscribeRequestAccountsDataSlice,
+            SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+            SubscribeRequestFilterAccountsFilterLamports,
             SubscribeRequestFilterAccountsFilterMemcmp, SubscribeRequestFilterBlocks,
             SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterEntry,
-            SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions, SubscribeRequestPing,
-            SubscribeUpdateAccountInfo, SubscribeUpdateEntry, SubscribeUpdateTransactionInfo,
+            SubscribeRequestFilterNotices, SubscribeRequestFilterSlots,
+            SubscribeRequestFilterTransactions, SubscribeRequestPing, SubscribeUpdateAccountInfo,
+            SubscribeUpdateEntry, SubscribeUpdateTimings, SubscribeUpdateTransactionInfo,
         },
         prost::Message,
     },
@@ -48,6 +50,7 @@ type TransactionsStatusFilterMap = HashMap<String, SubscribeRequestFilterTransac
 type EntryFilterMap = HashMap<String, SubscribeRequestFilterEntry>;
 type BlocksFilterMap = HashMap<String, SubscribeRequestFilterBlocks>;
 type BlocksMetaFilterMap = HashMap<String, SubscribeRequestFilterBlocksMeta>;
+type NoticesFilterMap = HashMap<String, SubscribeRequestFilterNotices>;
 
 #[derive(Debug, Clone, Parser)]
 #[clap(author, version, about)]
@@ -252,6 +255,10 @@ struct ActionSubscribe {
     #[clap(long)]
     accounts_data_slice: Vec<String>,
 
+    /// Receive only part of updated data account for accounts filter, overrides `--accounts-data-slice`, format: `offset,size`
+    #[clap(long)]
+    accounts_filter_data_slice: Vec<String>,
+
     /// Subscribe on slots updates
     #[clap(long)]
     slots: bool,
@@ -264,6 +271,10 @@ struct ActionSubscribe {
     #[clap(long)]
     slots_interslot_updates: bool,
 
+    /// Include receive time of slot statuses to slots updates
+    #[clap(long)]
+    slots_include_timings: bool,
+
     /// Subscribe on transactions updates
     #[clap(long)]
     transactions: bool,
@@ -323,6 +334,10 @@ struct ActionSubscribe {
     #[clap(long)]
     entries: bool,
 
+    /// Include signatures of executed transactions to entries
+    #[clap(long)]
+    entries_include_transaction_signatures: Option<bool>,
+
     /// Subscribe on block updates
     #[clap(long)]
     blocks: bool,
@@ -343,14 +358,34 @@ struct ActionSubscribe {
     #[clap(long)]
     blocks_include_entries: Option<bool>,
 
+    /// Include content of blocks which failed to reconstruct
+    #[clap(long)]
+    blocks_include_incomplete: Option<bool>,
+
+    /// Filter included accounts in block message by Owner Pubkey
+    #[clap(long)]
+    blocks_account_owner: Vec<String>,
+
+    /// Receive only part of included accounts data in block message, format: `offset,size`
+    #[clap(long)]
+    blocks_accounts_data_slice: Vec<String>,
+
     /// Subscribe on block meta updates (without transactions)
     #[clap(long)]
     blocks_meta: bool,
 
+    /// Subscribe on server notices (slot updates stall)
+    #[clap(long)]
+    notices: bool,
+
     /// Re-send message from slot
     #[clap(long)]
     from_slot: Option<u64>,
 
+    /// Receive time of server stages with every update
+    #[clap(long)]
+    timings: bool,
+
     /// Send ping in subscribe request
     #[clap(long)]
     ping: Option<i32>,
@@ -449,6 +484,9 @@ impl Action {
                             account: accounts_account,
                             owner: args.accounts_owner.clone(),
                             filters,
+                            data_slice: parse_accounts_data_slice(
+                                &args.accounts_filter_data_slice,
+                            )?,
                         },
                     );
                 }
@@ -460,6 +498,7 @@ impl Action {
                         SubscribeRequestFilterSlots {
                             filter_by_commitment: Some(args.slots_filter_by_commitment),
                             interslot_updates: Some(args.slots_interslot_updates),
+                            include_timings: Some(args.slots_include_timings),
                         },
                     );
                 }
@@ -496,7 +535,13 @@ impl Action {
 
                 let mut entries: EntryFilterMap = HashMap::new();
                 if args.entries {
-                    entries.insert("client".to_owned(), SubscribeRequestFilterEntry {});
+                    entries.insert(
+                        "client".to_owned(),
+                        SubscribeRequestFilterEntry {
+                            include_transaction_signatures: args
+                                .entries_include_transaction_signatures,
+                        },
+                    );
                 }
 
                 let mut blocks: BlocksFilterMap = HashMap::new();
@@ -508,6 +553,12 @@ impl Action {
                             include_transactions: args.blocks_include_transactions,
                             include_accounts: args.blocks_include_accounts,
                             include_entries: args.blocks_include_entries,
+                            include_incomplete: args.blocks_include_incomplete,
+                            account_owner: args.blocks_account_owner.clone(),
+                            account_filters: vec![],
+                            accounts_data_slice: parse_accounts_data_slice(
+                                &args.blocks_accounts_data_slice,
+                            )?,
                         },
                     );
                 }
@@ -517,20 +568,13 @@ impl Action {
                     blocks_meta.insert("client".to_owned(), SubscribeRequestFilterBlocksMeta {});
                 }
 
-                let mut accounts_data_slice = Vec::new();
-                for data_slice in args.accounts_data_slice.iter() {
-                    match data_slice.split_once(',') {
-                        Some((offset, length)) => match (offset.parse(), length.parse()) {
-                            (Ok(offset), Ok(length)) => {
-                                accounts_data_slice
-                                    .push(SubscribeRequestAccountsDataSlice { offset, length });
-                            }
-                            _ => anyhow::bail!("invalid data_slice"),
-                        },
-                        _ => anyhow::bail!("invalid data_slice"),
-                    }
+                let mut notices: NoticesFilterMap = HashMap::new();
+                if args.notices {
+                    notices.insert("client".to_owned(), SubscribeRequestFilterNotices {});
                 }
 
+                let accounts_data_slice = parse_accounts_data_slice(&args.accounts_data_slice)?;
+
                 let ping = args.ping.map(|id| SubscribeRequestPing { id });
 
                 Some((
@@ -546,6 +590,8 @@ impl Action {
                         accounts_data_slice,
                         ping,
                         from_slot: args.from_slot,
+                        notices,
+                        timings: args.timings.then_some(true),
                     },
                     args.resub.unwrap_or(0),
                     args.stats,
@@ -687,6 +733,8 @@ async fn geyser_subscribe(
     let pb_blocks = crate_progress_bar(&pb_multi, ProgressBarTpl::Msg("blocks"))?;
     let mut pb_pp_c = 0;
     let pb_pp = crate_progress_bar(&pb_multi, ProgressBarTpl::Msg("ping/pong"))?;
+    let mut pb_notices_c = 0;
+    let pb_notices = crate_progress_bar(&pb_multi, ProgressBarTpl::Msg("notices"))?;
     let mut pb_total_c = 0;
     let pb_total = crate_progress_bar(&pb_multi, ProgressBarTpl::Total)?;
     let mut pb_verify_c = verify_encoding.then_some((0, 0));
@@ -708,9 +756,12 @@ async fn geyser_subscribe(
                         Some(UpdateOneof::TransactionStatus(_)) => (&mut pb_txs_st_c, &pb_txs_st),
                         Some(UpdateOneof::Entry(_)) => (&mut pb_entries_c, &pb_entries),
                         Some(UpdateOneof::BlockMeta(_)) => (&mut pb_blocks_mt_c, &pb_blocks_mt),
-                        Some(UpdateOneof::Block(_)) => (&mut pb_blocks_c, &pb_blocks),
+                        Some(UpdateOneof::Block(_) | UpdateOneof::BlockFailure(_)) => {
+                            (&mut pb_blocks_c, &pb_blocks)
+                        }
                         Some(UpdateOneof::Ping(_)) => (&mut pb_pp_c, &pb_pp),
                         Some(UpdateOneof::Pong(_)) => (&mut pb_pp_c, &pb_pp),
+                        Some(UpdateOneof::Notice(_)) => (&mut pb_notices_c, &pb_notices),
                         None => {
                             pb_multi.println("update not found in the message")?;
                             break;
@@ -775,6 +826,9 @@ async fn geyser_subscribe(
                     .ok_or(anyhow::anyhow!("no created_at in the message"))?
                     .try_into()
                     .context("failed to parse created_at")?;
+                if let Some(timings) = msg.timings {
+                    print_timings(created_at, timings)?;
+                }
                 match msg.update_oneof {
                     Some(UpdateOneof::Account(msg)) => {
                         let account = msg
@@ -797,6 +851,15 @@ async fn geyser_subscribe(
                                 "parent": msg.parent,
                                 "status": status.as_str_name(),
                                 "deadError": msg.dead_error,
+                                "timings": msg.timings.map(|timings| json!({
+                                    "firstShredReceived": timings.first_shred_received.map(|ts| ts.to_string()),
+                                    "completed": timings.completed.map(|ts| ts.to_string()),
+                                    "createdBank": timings.created_bank.map(|ts| ts.to_string()),
+                                    "processed": timings.processed.map(|ts| ts.to_string()),
+                                    "confirmed": timings.confirmed.map(|ts| ts.to_string()),
+                                    "finalized": timings.finalized.map(|ts| ts.to_string()),
+                                    "dead": timings.dead.map(|ts| ts.to_string()),
+                                })),
                             }),
                         );
                     }
@@ -872,6 +935,28 @@ async fn geyser_subscribe(
                                 "accounts": msg.accounts.into_iter().map(create_pretty_account).collect::<Result<Value, _>>()?,
                                 "entriesCount": msg.entries_count,
                                 "entries": msg.entries.into_iter().map(create_pretty_entry).collect::<Result<Value, _>>()?,
+                                "incomplete": msg.incomplete.map(|incomplete| json!({
+                                    "expectedTransactionCount": incomplete.expected_transaction_count,
+                                    "observedTransactionCount": incomplete.observed_transaction_count,
+                                    "expectedEntriesCount": incomplete.expected_entries_count,
+                                    "observedEntriesCount": incomplete.observed_entries_count,
+                                })),
+                            }),
+                        );
+                    }
+                    Some(UpdateOneof::BlockFailure(msg)) => {
+                        print_update(
+                            "block_failure",
+                            created_at,
+                            &filters,
+                            json!({
+                                "slot": msg.slot,
+                                "incomplete": msg.incomplete.map(|incomplete| json!({
+                                    "expectedTransactionCount": incomplete.expected_transaction_count,
+                                    "observedTransactionCount": incomplete.observed_transaction_count,
+                                    "expectedEntriesCount": incomplete.expected_entries_count,
+                                    "observedEntriesCount": incomplete.observed_entries_count,
+                                })),
                             }),
                         );
                     }
@@ -886,6 +971,43 @@ async fn geyser_subscribe(
                             .await?;
                     }
                     Some(UpdateOneof::Pong(_)) => {}
+                    Some(UpdateOneof::Notice(msg)) => match msg.notice {
+                        Some(NoticeOneof::SlotStall(msg)) => {
+                            let status = SlotStatus::try_from(msg.status)
+                                .context("failed to decode commitment")?;
+                            print_update(
+                                "notice",
+                                created_at,
+                                &filters,
+                                json!({
+                                    "slotStall": {
+                                        "status": status.as_str_name(),
+                                        "stalled": msg.stalled,
+                                        "lastSlot": msg.last_slot,
+                                        "sinceLastSlotMs": msg.since_last_slot_ms,
+                                    }
+                                }),
+                            );
+                        }
+                        Some(NoticeOneof::MessagesDropped(msg)) => {
+                            print_update(
+                                "notice",
+                                created_at,
+                                &filters,
+                                json!({
+                                    "messagesDropped": {
+                                        "accounts": msg.accounts,
+                                        "transactions": msg.transactions,
+                                        "entries": msg.entries,
+                                    }
+                                }),
+                            );
+                        }
+                        None => {
+                            error!("notice not found in the message");
+                            break;
+                        }
+                    },
                     None => {
                         error!("update not found in the message");
                         break;
@@ -917,6 +1039,8 @@ async fn geyser_subscribe(
                     accounts_data_slice: Vec::default(),
                     ping: None,
                     from_slot: None,
+                    notices: HashMap::default(),
+                    timings: None,
                 })
                 .await
                 .map_err(GeyserGrpcClientError::SubscribeSendError)?;
@@ -926,6 +1050,24 @@ async fn geyser_subscribe(
     Ok(())
 }
 
+fn parse_accounts_data_slice(
+    data_slices: &[String],
+) -> anyhow::Result<Vec<SubscribeRequestAccountsDataSlice>> {
+    let mut accounts_data_slice = Vec::new();
+    for data_slice in data_slices.iter() {
+        match data_slice.split_once(',') {
+            Some((offset, length)) => match (offset.parse(), length.parse()) {
+                (Ok(offset), Ok(length)) => {
+                    accounts_data_slice.push(SubscribeRequestAccountsDataSlice { offset, length });
+                }
+                _ => anyhow::bail!("invalid data_slice"),
+            },
+            _ => anyhow::bail!("invalid data_slice"),
+        }
+    }
+    Ok(accounts_data_slice)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProgressBarTpl {
     Msg(&'static str),
@@ -998,9 +1140,45 @@ fn create_pretty_entry(msg: SubscribeUpdateEntry) -> anyhow::Result<Value> {
         "hash": Hash::new_from_array(<[u8; 32]>::try_from(msg.hash.as_slice()).context("invalid entry hash")?).to_string(),
         "executedTransactionCount": msg.executed_transaction_count,
         "startingTransactionIndex": msg.starting_transaction_index,
+        "transactionSignatures": msg
+            .transaction_signatures
+            .iter()
+            .map(|sig| Signature::try_from(sig.as_slice()).map(|sig| sig.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid transaction signature")?,
     }))
 }
 
+fn print_timings(created_at: SystemTime, timings: SubscribeUpdateTimings) -> anyhow::Result<()> {
+    let received_at = SystemTime::now();
+    let broadcasted_at = timings
+        .broadcasted
+        .map(SystemTime::try_from)
+        .transpose()
+        .context("failed to parse broadcasted")?;
+    let sent_at: SystemTime = timings
+        .sent
+        .ok_or(anyhow::anyhow!("no sent in timings"))?
+        .try_into()
+        .context("failed to parse sent")?;
+    let micros =
+        |from: SystemTime, to: SystemTime| to.duration_since(from).unwrap_or_default().as_micros();
+    match broadcasted_at {
+        Some(broadcasted_at) => info!(
+            "timings: plugin to broadcast {}us, broadcast to send {}us, send to receive {}us",
+            micros(created_at, broadcasted_at),
+            micros(broadcasted_at, sent_at),
+            micros(sent_at, received_at),
+        ),
+        None => info!(
+            "timings: plugin to send {}us, send to receive {}us",
+            micros(created_at, sent_at),
+            micros(sent_at, received_at),
+        ),
+    }
+    Ok(())
+}
+
 fn print_update(kind: &str, created_at: SystemTime, filters: &[String], value: Value) {
     let unix_since = created_at
         .duration_since(UNIX_EPOCH)
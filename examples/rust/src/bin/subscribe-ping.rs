@@ -48,7 +48,8 @@ async fn main() -> anyhow::Result<()> {
                     slots: maplit::hashmap! {
                         "".to_owned() => SubscribeRequestFilterSlots {
                             filter_by_commitment: Some(true),
-                            interslot_updates: Some(false)
+                            interslot_updates: Some(false),
+                            include_timings: None,
                         }
                     },
                     commitment: Some(CommitmentLevel::Processed as i32),
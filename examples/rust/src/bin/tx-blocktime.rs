@@ -112,6 +112,8 @@ async fn main() -> anyhow::Result<()> {
             accounts_data_slice: vec![],
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         })
         .await?;
 
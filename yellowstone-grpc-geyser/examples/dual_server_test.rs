@@ -4,14 +4,15 @@ use {
     log::info,
     solana_pubkey::Pubkey,
     std::{env, path::PathBuf, sync::Arc, time::Duration},
-    tokio::{fs, sync::mpsc, time::interval},
+    tokio::{fs, time::interval},
     yellowstone_grpc_geyser::{
         config::ConfigGrpc,
         grpc::GrpcService,
+        ingress::IngressSender,
     },
     yellowstone_grpc_proto::{
         prelude::SlotStatus,
-        plugin::message::{Message, MessageAccount, MessageAccountInfo, MessageSlot},
+        plugin::message::MessageAccountInfo,
     },
 };
 
@@ -43,6 +44,8 @@ async fn main() -> Result<()> {
     let config_grpc = ConfigGrpc {
         address: Some(tcp_address),
         unix_socket_path: Some(unix_socket_path.clone()),
+        unix_socket: Default::default(),
+        listeners: vec![],
         tls_config: None,
         compression: Default::default(),
         server_http2_adaptive_window: None,
@@ -57,24 +60,36 @@ async fn main() -> Result<()> {
         unary_concurrency_limit: 100,
         unary_disabled: false,
         x_token: None,
+        peers: Default::default(),
+        jwt: None,
         replay_stored_slots: 0,
         filter_name_size_limit: 128,
         filter_names_size_limit: 4096,
         filter_names_cleanup_interval: std::time::Duration::from_secs(1),
         filter_limits: Default::default(),
+        filter_limits_identity: Default::default(),
+        reflection: true,
+        slot_watchdog: Default::default(),
+        client_metrics: Default::default(),
+        ingress: Default::default(),
+        pipeline: Default::default(),
+        encoded_cache: Default::default(),
+        filter_index: false,
     };
 
     // Validate configuration
     config_grpc.validate()?;
 
     // Create gRPC service
-    let (_snapshot_tx, messages_tx, shutdown) = GrpcService::create(
+    let (_snapshot_tx, messages_tx, shutdown, health) = GrpcService::create(
         Default::default(), // tokio config
         config_grpc,
         None, // debug clients
         false, // is_reload
     )
     .await?;
+    // No startup snapshot in the test, report service as serving
+    health.set_startup_done();
 
     // Start fake data generator
     let fake_data_shutdown = Arc::clone(&shutdown);
@@ -105,7 +120,7 @@ async fn main() -> Result<()> {
 }
 
 async fn generate_fake_data(
-    messages_tx: mpsc::UnboundedSender<yellowstone_grpc_proto::plugin::message::Message>,
+    messages_tx: IngressSender,
     shutdown: Arc<tokio::sync::Notify>,
 ) {
     use yellowstone_grpc_proto::plugin::message::{Message, MessageAccount, MessageSlot};
@@ -133,6 +148,7 @@ async fn generate_fake_data(
                     status: SlotStatus::SlotProcessed.into(),
                     created_at: prost_types::Timestamp::from(std::time::SystemTime::now()),
                     dead_error: None,
+                    timings: None,
                 });
 
                 // Generate fake account message
@@ -247,8 +263,8 @@ fn generate_fake_account_data(counter: u32) -> Vec<u8> {
         }
         
         // Fill rest with pattern
-        for i in 16..data.len() {
-            data[i] = ((counter + i as u32) % 256) as u8;
+        for (i, byte) in data.iter_mut().enumerate().skip(16) {
+            *byte = ((counter + i as u32) % 256) as u8;
         }
     }
     
@@ -87,6 +87,7 @@ async fn test_account_subscription(client: &mut GeyserClient<Channel>) -> Result
         owner: vec![],   // Empty = any owner
         filters: vec![], // No additional filters
         nonempty_txn_signature: None,
+        data_slice: vec![],
     });
 
     let subscribe_request = SubscribeRequest {
@@ -101,6 +102,8 @@ async fn test_account_subscription(client: &mut GeyserClient<Channel>) -> Result
         accounts_data_slice: vec![],
         ping: None,
         from_slot: None,
+        notices: HashMap::new(),
+        timings: None,
     };
 
     let (request_tx, request_rx) = tokio::sync::mpsc::unbounded_channel();
@@ -1,12 +1,10 @@
 use {
     anyhow::{Context, Result},
-    bs58,
     futures::stream::StreamExt,
     log::{error, info, warn},
     solana_pubkey::Pubkey,
     std::{collections::HashMap, env, str::FromStr, time::Duration},
     tokio::{net::UnixStream, time::timeout},
-    tokio_stream,
     tonic::{
         transport::{Channel, Endpoint, Uri},
         Request,
@@ -124,6 +122,8 @@ async fn subscribe_to_transactions(
         accounts_data_slice: vec![],
         ping: None,
         from_slot: None,
+        notices: HashMap::new(),
+        timings: None,
     };
 
     info!("📡 Sending subscription request...");
@@ -205,6 +205,6 @@ async fn handle_transaction_update(
         }
     }
 
-    println!(""); // Add spacing between transactions
+    println!(); // Add spacing between transactions
     Ok(())
 }
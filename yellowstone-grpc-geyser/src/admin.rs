@@ -0,0 +1,287 @@
+use {
+    crate::{
+        jwt::TokenClaims,
+        metrics::DebugClientStatuses,
+        pipeline::{ReplayStats, ReplayStatsRequest},
+    },
+    log::info,
+    std::{
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+        time::UNIX_EPOCH,
+    },
+    tokio::sync::{mpsc, oneshot, Mutex},
+    tonic::{Request, Response, Result as TonicResult, Status},
+    yellowstone_grpc_proto::{
+        geyser::CommitmentLevel as CommitmentLevelProto,
+        geyser_admin::{
+            geyser_admin_server::GeyserAdmin, CleanupFilterNamesRequest,
+            CleanupFilterNamesResponse, ClientInfo, GetReplayStatsRequest, GetReplayStatsResponse,
+            KickClientRequest, KickClientResponse, ListClientsRequest, ListClientsResponse,
+            SetSubscriptionsPausedRequest, SetSubscriptionsPausedResponse,
+        },
+        plugin::filter::name::FilterNames,
+    },
+};
+
+/// Operational control of the plugin, served only by listeners with `admin` flag
+#[derive(Debug)]
+pub struct GrpcAdminService {
+    pub(crate) debug_clients: Arc<DebugClientStatuses>,
+    pub(crate) subscriptions_paused: Arc<AtomicBool>,
+    pub(crate) filter_names: Arc<Mutex<FilterNames>>,
+    pub(crate) replay_stats_tx: mpsc::Sender<ReplayStatsRequest>,
+    pub(crate) replay_first_available_slot: Option<Arc<AtomicU64>>,
+    pub(crate) replay_stored_slots: u64,
+}
+
+#[tonic::async_trait]
+impl GeyserAdmin for GrpcAdminService {
+    async fn list_clients(
+        &self,
+        request: Request<ListClientsRequest>,
+    ) -> TonicResult<Response<ListClientsResponse>> {
+        TokenClaims::check_method(&request, "ListClients")?;
+        let clients = self
+            .debug_clients
+            .get_clients()
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?
+            .into_iter()
+            .map(|info| ClientInfo {
+                id: info.id as u64,
+                endpoint: info.endpoint,
+                identity: info.identity,
+                connected_at: info
+                    .connected_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_millis() as u64)
+                    .unwrap_or_default(),
+                request: info.request.map(|request| *request),
+                commitment: CommitmentLevelProto::from(info.commitment) as i32,
+                processed_slot: info.processed_slot,
+                queue_size: info.queue_size as u64,
+                lag_slots: info.lag_slots,
+            })
+            .collect();
+        Ok(Response::new(ListClientsResponse { clients }))
+    }
+
+    async fn kick_client(
+        &self,
+        request: Request<KickClientRequest>,
+    ) -> TonicResult<Response<KickClientResponse>> {
+        TokenClaims::check_method(&request, "KickClient")?;
+        let found = self
+            .debug_clients
+            .disconnect(request.get_ref().id as usize)
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?;
+        Ok(Response::new(KickClientResponse { found }))
+    }
+
+    async fn set_subscriptions_paused(
+        &self,
+        request: Request<SetSubscriptionsPausedRequest>,
+    ) -> TonicResult<Response<SetSubscriptionsPausedResponse>> {
+        TokenClaims::check_method(&request, "SetSubscriptionsPaused")?;
+        let paused = request.get_ref().paused;
+        let previous = self.subscriptions_paused.swap(paused, Ordering::Relaxed);
+        if paused != previous {
+            info!("new subscriptions paused: {paused}");
+        }
+        Ok(Response::new(SetSubscriptionsPausedResponse {
+            paused,
+            previous,
+        }))
+    }
+
+    async fn get_replay_stats(
+        &self,
+        request: Request<GetReplayStatsRequest>,
+    ) -> TonicResult<Response<GetReplayStatsResponse>> {
+        TokenClaims::check_method(&request, "GetReplayStats")?;
+        let (tx, rx) = oneshot::channel();
+        self.replay_stats_tx
+            .send(tx)
+            .await
+            .map_err(|_error| Status::internal("failed to send stats request"))?;
+        let ReplayStats {
+            slots,
+            first_slot,
+            last_slot,
+            messages,
+        } = rx
+            .await
+            .map_err(|_error| Status::internal("failed to get stats response"))?;
+        Ok(Response::new(GetReplayStatsResponse {
+            stored_slots_limit: self.replay_stored_slots,
+            first_available: self
+                .replay_first_available_slot
+                .as_ref()
+                .map(|stored| stored.load(Ordering::Relaxed)),
+            slots: slots as u64,
+            first_slot,
+            last_slot,
+            messages: messages as u64,
+        }))
+    }
+
+    async fn cleanup_filter_names(
+        &self,
+        request: Request<CleanupFilterNamesRequest>,
+    ) -> TonicResult<Response<CleanupFilterNamesResponse>> {
+        TokenClaims::check_method(&request, "CleanupFilterNames")?;
+        let mut filter_names = self.filter_names.lock().await;
+        let removed = filter_names.clean();
+        info!("filter names cleanup, removed: {removed}");
+        Ok(Response::new(CleanupFilterNamesResponse {
+            removed: removed as u64,
+            names: filter_names.len() as u64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::GrpcAdminService,
+        crate::{
+            jwt::TokenClaims,
+            metrics::{DebugClientMessage, DebugClientStatuses},
+        },
+        std::{
+            sync::{
+                atomic::{AtomicBool, Ordering},
+                Arc,
+            },
+            time::Duration,
+        },
+        tokio::sync::{mpsc, Mutex},
+        tonic::{Code, Request},
+        yellowstone_grpc_proto::{
+            geyser_admin::{
+                geyser_admin_server::GeyserAdmin, CleanupFilterNamesRequest, KickClientRequest,
+                ListClientsRequest, SetSubscriptionsPausedRequest,
+            },
+            plugin::filter::name::FilterNames,
+        },
+    };
+
+    fn service(filter_names: Arc<Mutex<FilterNames>>) -> GrpcAdminService {
+        GrpcAdminService {
+            debug_clients: DebugClientStatuses::new(),
+            subscriptions_paused: Arc::new(AtomicBool::new(false)),
+            filter_names,
+            replay_stats_tx: mpsc::channel(1).0,
+            replay_first_available_slot: None,
+            replay_stored_slots: 0,
+        }
+    }
+
+    fn filter_names() -> Arc<Mutex<FilterNames>> {
+        Arc::new(Mutex::new(FilterNames::new(
+            64,
+            1_024,
+            Duration::from_secs(1),
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_kick_client() {
+        let service = service(filter_names());
+        let (client_tx, mut client_rx) = mpsc::unbounded_channel();
+        let (stream_tx, mut stream_rx) = mpsc::channel(1);
+        service
+            .debug_clients
+            .clients_tx()
+            .send(DebugClientMessage::New {
+                id: 1,
+                endpoint: "127.0.0.1:10000".to_owned(),
+                client_tx,
+                stream_tx,
+            })
+            .unwrap();
+        // clients and requests are received from different channels
+        while service
+            .list_clients(Request::new(ListClientsRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .clients
+            .is_empty()
+        {
+            tokio::task::yield_now().await;
+        }
+
+        let response = service
+            .kick_client(Request::new(KickClientRequest { id: 2 }))
+            .await
+            .unwrap();
+        assert!(!response.get_ref().found);
+
+        let response = service
+            .kick_client(Request::new(KickClientRequest { id: 1 }))
+            .await
+            .unwrap();
+        assert!(response.get_ref().found);
+        let status = stream_rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+        assert!(matches!(client_rx.recv().await, Some(None)));
+
+        // method is not allowed by token
+        let claims: TokenClaims =
+            serde_json::from_str(r#"{"exp": 0, "methods": ["ListClients"]}"#).unwrap();
+        let mut request = Request::new(KickClientRequest { id: 1 });
+        request.extensions_mut().insert(Arc::new(claims));
+        let status = service.kick_client(request).await.unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_set_subscriptions_paused() {
+        let service = service(filter_names());
+        for (paused, previous) in [(true, false), (true, true), (false, true), (false, false)] {
+            let response = service
+                .set_subscriptions_paused(Request::new(SetSubscriptionsPausedRequest { paused }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(response.paused, paused);
+            assert_eq!(response.previous, previous);
+            assert_eq!(service.subscriptions_paused.load(Ordering::Relaxed), paused);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_filter_names() {
+        let filter_names = filter_names();
+        let service = service(Arc::clone(&filter_names));
+        let used = {
+            let mut filter_names = filter_names.lock().await;
+            let used = filter_names.get("used").unwrap();
+            filter_names.get("unused1").unwrap();
+            filter_names.get("unused2").unwrap();
+            used
+        };
+
+        let response = service
+            .cleanup_filter_names(Request::new(CleanupFilterNamesRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.removed, 2);
+        assert_eq!(response.names, 1);
+
+        drop(used);
+        let response = service
+            .cleanup_filter_names(Request::new(CleanupFilterNamesRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.removed, 1);
+        assert_eq!(response.names, 0);
+    }
+}
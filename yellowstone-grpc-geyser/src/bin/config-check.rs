@@ -10,7 +10,8 @@ struct Args {
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let _config = Config::load_from_file(args.config)?;
+    let config = Config::load_from_file(args.config)?;
+    config.validate()?;
     println!("Config is OK!");
     Ok(())
 }
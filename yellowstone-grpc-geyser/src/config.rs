@@ -2,14 +2,21 @@ use {
     agave_geyser_plugin_interface::geyser_plugin_interface::{
         GeyserPluginError, Result as PluginResult,
     },
+    ipnet::IpNet,
     serde::{de, Deserialize, Deserializer},
+    solana_pubkey::Pubkey,
     std::{
-        collections::HashSet, fmt, fs::read_to_string, net::SocketAddr, path::{Path, PathBuf}, str::FromStr,
+        collections::{HashMap, HashSet},
+        fmt,
+        fs::read_to_string,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        str::FromStr,
         time::Duration,
     },
     tokio::sync::Semaphore,
     tonic::codec::CompressionEncoding,
-    yellowstone_grpc_proto::plugin::filter::limits::FilterLimits,
+    yellowstone_grpc_proto::plugin::filter::limits::{deserialize_pubkey_set, FilterLimits},
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -23,9 +30,15 @@ pub struct Config {
     pub grpc: ConfigGrpc,
     #[serde(default)]
     pub prometheus: Option<ConfigPrometheus>,
+    /// Export of tracing spans to OpenTelemetry collector
+    #[serde(default)]
+    pub tracing: Option<ConfigTracing>,
     /// Collect client filters, processed slot and make it available on prometheus port `/debug_clients`
     #[serde(default)]
     pub debug_clients_http: bool,
+    /// Skip messages in the plugin before conversion
+    #[serde(default)]
+    pub ingestion: ConfigIngestion,
 }
 
 impl Config {
@@ -39,6 +52,29 @@ impl Config {
         let config = read_to_string(file).map_err(GeyserPluginError::ConfigFileOpenError)?;
         Self::load_from_str(&config)
     }
+
+    pub fn validate(&self) -> PluginResult<()> {
+        self.grpc.validate()?;
+        if let Some(tracing) = &self.tracing {
+            if !(0.0..=1.0).contains(&tracing.sample_ratio) {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: format!(
+                        "'tracing.sample_ratio' should be in range [0, 1], got {}",
+                        tracing.sample_ratio
+                    ),
+                });
+            }
+        }
+        if self.ingestion.skip_vote_transactions && self.grpc.pipeline.block_reconstruction {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "Blocks can't be reconstructed without vote transactions, \
+                    'ingestion.skip_vote_transactions' requires \
+                    'grpc.pipeline.block_reconstruction' to be disabled"
+                    .to_owned(),
+            });
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -47,12 +83,24 @@ pub struct ConfigLog {
     /// Log level.
     #[serde(default = "ConfigLog::default_level")]
     pub level: String,
+    /// Log format.
+    #[serde(default)]
+    pub format: ConfigLogFormat,
+    /// Min interval between repeated errors of the same kind, number of suppressed errors
+    /// is logged with the next one
+    #[serde(
+        default = "ConfigLog::default_errors_interval",
+        with = "humantime_serde"
+    )]
+    pub errors_interval: Duration,
 }
 
 impl Default for ConfigLog {
     fn default() -> Self {
         Self {
             level: Self::default_level(),
+            format: ConfigLogFormat::default(),
+            errors_interval: Self::default_errors_interval(),
         }
     }
 }
@@ -61,6 +109,34 @@ impl ConfigLog {
     fn default_level() -> String {
         "info".to_owned()
     }
+
+    const fn default_errors_interval() -> Duration {
+        Duration::from_secs(10)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line with key-values of the record, like `client_id` or `slot`
+    Json,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigIngestion {
+    /// Skip vote transactions, blocks can't be reconstructed without them,
+    /// so `grpc.pipeline.block_reconstruction` should be disabled
+    pub skip_vote_transactions: bool,
+    /// Skip accounts owned by Vote program
+    pub skip_vote_accounts: bool,
+    /// Skip accounts owned by listed programs
+    #[serde(deserialize_with = "deserialize_pubkey_set")]
+    pub skip_owners: HashSet<Pubkey>,
+    /// Skip accounts from snapshot on startup
+    pub skip_startup_accounts: bool,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -143,6 +219,12 @@ pub struct ConfigGrpc {
     pub address: Option<SocketAddr>,
     /// Unix socket path for Grpc service.
     pub unix_socket_path: Option<PathBuf>,
+    /// Unix socket file permissions and ownership
+    #[serde(default)]
+    pub unix_socket: ConfigGrpcUnixSocket,
+    /// Additional listeners with own TLS, auth, compression and filter limits
+    #[serde(default)]
+    pub listeners: Vec<ConfigGrpcListener>,
     /// TLS config
     pub tls_config: Option<ConfigGrpcServerTls>,
     /// Possible compression options
@@ -185,8 +267,17 @@ pub struct ConfigGrpc {
     /// Limits for possible filters
     #[serde(default, alias = "filters")]
     pub filter_limits: FilterLimits,
+    /// Limits for filters per client identity, see `tls_config.client_identities`
+    #[serde(default)]
+    pub filter_limits_identity: HashMap<String, FilterLimits>,
     /// x_token to enforce on connections
     pub x_token: Option<String>,
+    /// Source address allow/deny lists and limits per peer
+    #[serde(default)]
+    pub peers: ConfigGrpcPeers,
+    /// Verifier of signed tokens (JWT) passed in `x-token` header
+    #[serde(default)]
+    pub jwt: Option<ConfigGrpcJwt>,
     /// Filter name size limit
     #[serde(default = "ConfigGrpc::default_filter_name_size_limit")]
     pub filter_name_size_limit: usize,
@@ -215,16 +306,90 @@ pub struct ConfigGrpc {
     pub server_initial_connection_window_size: Option<u32>,
     #[serde(default)]
     pub server_initial_stream_window_size: Option<u32>,
+    /// Enable gRPC server reflection
+    #[serde(default = "ConfigGrpc::default_reflection")]
+    pub reflection: bool,
+    /// Thresholds of slot updates stall, used for health status and notices
+    #[serde(default)]
+    pub slot_watchdog: ConfigGrpcSlotWatchdog,
+    /// Prometheus metrics per client
+    #[serde(default)]
+    pub client_metrics: ConfigGrpcClientMetrics,
+    /// Limit of the queue from plugin to geyser loop and overload policy
+    #[serde(default)]
+    pub ingress: ConfigGrpcIngress,
+    /// Parallelism of messages processing in geyser loop
+    #[serde(default)]
+    pub pipeline: ConfigGrpcPipeline,
+    /// Share encoded updates between streams with the same filter names
+    #[serde(default)]
+    pub encoded_cache: ConfigGrpcEncodedCache,
+    /// Route accounts and transactions only to streams with matched pubkeys, owners or keys
+    #[serde(default)]
+    pub filter_index: bool,
 }
 
 impl ConfigGrpc {
-    /// Validate that at least one of TCP address or Unix socket path is provided
+    /// Validate that at least one listener is provided and every listener has one address
     pub fn validate(&self) -> PluginResult<()> {
-        match (&self.address, &self.unix_socket_path) {
-            (None, None) => Err(GeyserPluginError::ConfigFileReadError {
-                msg: "Must specify at least one of 'address' or 'unix_socket_path'.".to_string(),
-            }),
-            _ => Ok(()),
+        if self.address.is_none() && self.unix_socket_path.is_none() && self.listeners.is_empty()
+        {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "Must specify at least one of 'address', 'unix_socket_path' or 'listeners'."
+                    .to_string(),
+            });
+        }
+        let listeners = self.get_listeners();
+        for listener in listeners.iter() {
+            listener.validate()?;
+        }
+        if listeners.iter().all(|listener| listener.admin) {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "At least one listener should serve Geyser service, not only admin"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn has_admin_listener(&self) -> bool {
+        self.listeners.iter().any(|listener| listener.admin)
+    }
+
+    /// Listeners from `listeners` and from top level `address` and `unix_socket_path`
+    pub fn get_listeners(&self) -> Vec<ConfigGrpcListener> {
+        let mut listeners = vec![];
+        if let Some(address) = self.address {
+            listeners.push(ConfigGrpcListener {
+                name: "tcp".to_owned(),
+                address: Some(address),
+                ..self.get_listener_default()
+            });
+        }
+        if let Some(unix_socket_path) = &self.unix_socket_path {
+            listeners.push(ConfigGrpcListener {
+                name: "unix".to_owned(),
+                unix_socket_path: Some(unix_socket_path.clone()),
+                unix_socket: self.unix_socket.clone(),
+                ..self.get_listener_default()
+            });
+        }
+        listeners.extend(self.listeners.iter().cloned());
+        listeners
+    }
+
+    fn get_listener_default(&self) -> ConfigGrpcListener {
+        ConfigGrpcListener {
+            name: String::new(),
+            address: None,
+            unix_socket_path: None,
+            unix_socket: ConfigGrpcUnixSocket::default(),
+            tls_config: self.tls_config.clone(),
+            x_token: self.x_token.clone(),
+            jwt: self.jwt.clone(),
+            compression: self.compression.clone(),
+            filter_limits: None,
+            admin: false,
         }
     }
 
@@ -260,16 +425,292 @@ impl ConfigGrpc {
         Duration::from_secs(1)
     }
 
+    const fn default_reflection() -> bool {
+        true
+    }
+
     const fn default_replay_stored_slots() -> u64 {
         0
     }
 }
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcIngress {
+    /// Max number of messages in the queue, not limited if not set
+    #[serde(deserialize_with = "deserialize_int_str_maybe")]
+    pub capacity: Option<usize>,
+    /// What to do with messages once the queue is full
+    pub overload_policy: ConfigGrpcIngressOverloadPolicy,
+    /// Max time to wait for free space in the queue with `block` policy
+    #[serde(with = "humantime_serde")]
+    pub block_timeout: Duration,
+}
+
+impl Default for ConfigGrpcIngress {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            overload_policy: ConfigGrpcIngressOverloadPolicy::default(),
+            block_timeout: Duration::from_millis(100),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigGrpcIngressOverloadPolicy {
+    /// Drop accounts from half of capacity and entries from 3/4, other messages
+    /// wait for free space
+    #[default]
+    Shed,
+    /// Block validator thread until free space, accounts, transactions and entries
+    /// are dropped after timeout
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcPipeline {
+    /// Number of shards for block reconstruction, slots are distributed by `slot % shards`
+    pub shards: usize,
+    /// Build blocks from slot messages, without it `blocks` filters are rejected and
+    /// messages for `confirmed` / `finalized` commitments are not delayed until block is built
+    pub block_reconstruction: bool,
+}
+
+impl Default for ConfigGrpcPipeline {
+    fn default() -> Self {
+        Self {
+            shards: 1,
+            block_reconstruction: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcEncodedCache {
+    /// Encode identical updates once for all streams
+    pub enabled: bool,
+    /// Max number of encoded updates
+    #[serde(deserialize_with = "deserialize_int_str")]
+    pub capacity: usize,
+    /// Max total size of encoded updates in bytes
+    #[serde(deserialize_with = "deserialize_int_str")]
+    pub max_bytes: usize,
+}
+
+impl Default for ConfigGrpcEncodedCache {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 100_000,
+            max_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcClientMetrics {
+    /// Export metrics of sent messages, queue size and lag per client
+    pub enabled: bool,
+    /// Label clients by `x-endpoint` header or by identity (mTLS or token subject)
+    pub label: ConfigGrpcClientMetricsLabel,
+    /// Max number of different labels, clients over the limit are counted as `other`
+    pub max_labels: usize,
+}
+
+impl Default for ConfigGrpcClientMetrics {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            label: ConfigGrpcClientMetricsLabel::default(),
+            max_labels: 100,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigGrpcClientMetricsLabel {
+    #[default]
+    Endpoint,
+    /// Identity from TLS certificate or token subject, `x-endpoint` if not available
+    Identity,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcSlotWatchdog {
+    /// Max time without processed slot, stall is not detected if not set
+    #[serde(with = "humantime_serde")]
+    pub processed: Option<Duration>,
+    /// Max time without confirmed slot, stall is not detected if not set
+    #[serde(with = "humantime_serde")]
+    pub confirmed: Option<Duration>,
+    /// Max time without finalized slot, stall is not detected if not set
+    #[serde(with = "humantime_serde")]
+    pub finalized: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcListener {
+    /// Name of the listener for logs
+    pub name: String,
+    /// Address of Grpc service (TCP), exclusive with `unix_socket_path`
+    #[serde(default)]
+    pub address: Option<SocketAddr>,
+    /// Unix socket path for Grpc service, exclusive with `address`
+    #[serde(default)]
+    pub unix_socket_path: Option<PathBuf>,
+    #[serde(default)]
+    pub unix_socket: ConfigGrpcUnixSocket,
+    #[serde(default)]
+    pub tls_config: Option<ConfigGrpcServerTls>,
+    /// x_token to enforce on connections, no auth if both `x_token` and `jwt` are not set
+    #[serde(default)]
+    pub x_token: Option<String>,
+    #[serde(default)]
+    pub jwt: Option<ConfigGrpcJwt>,
+    #[serde(default)]
+    pub compression: ConfigGrpcCompression,
+    /// Filter limits for clients of the listener, `grpc.filter_limits` used if not set
+    #[serde(default)]
+    pub filter_limits: Option<FilterLimits>,
+    /// Serve only `GeyserAdmin` service, TCP listener requires `x_token` or `jwt`
+    #[serde(default)]
+    pub admin: bool,
+}
+
+impl ConfigGrpcListener {
+    fn validate(&self) -> PluginResult<()> {
+        if self.address.is_some() == self.unix_socket_path.is_some() {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: format!(
+                    "Listener {:?} should have one of 'address' or 'unix_socket_path'",
+                    self.name
+                ),
+            });
+        }
+        if self.admin && self.address.is_some() && self.x_token.is_none() && self.jwt.is_none() {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: format!(
+                    "Admin listener {:?} with 'address' should have 'x_token' or 'jwt'",
+                    self.name
+                ),
+            });
+        }
+        self.unix_socket.validate()
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcUnixSocket {
+    /// Socket file mode as octal string, e.g. "0660"
+    #[serde(deserialize_with = "ConfigGrpcUnixSocket::deserialize_mode")]
+    pub mode: Option<u32>,
+    /// Socket file owner, user name or UID
+    pub owner: Option<String>,
+    /// Socket file group, group name or GID
+    pub group: Option<String>,
+    /// Bind `unix_socket_path` as name in abstract namespace (Linux only)
+    pub abstract_namespace: bool,
+}
+
+impl ConfigGrpcUnixSocket {
+    fn validate(&self) -> PluginResult<()> {
+        if self.abstract_namespace {
+            if !cfg!(target_os = "linux") {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: "Abstract namespace Unix sockets are supported only on Linux".to_owned(),
+                });
+            }
+            if self.mode.is_some() || self.owner.is_some() || self.group.is_some() {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: "Unix socket mode, owner and group can't be used with abstract namespace"
+                        .to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize_mode<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<&str>::deserialize(deserializer)? {
+            Some(mode) => u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+                .ok()
+                .filter(|mode| *mode <= 0o7777)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("invalid file mode: {mode:?}"))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigGrpcPeers {
+    /// Allowed source networks for TCP connections, all allowed if empty
+    pub allow: Vec<IpNet>,
+    /// Denied source networks for TCP connections
+    pub deny: Vec<IpNet>,
+    /// Max concurrent connections per source IP (per UID for Unix socket)
+    pub max_connections: Option<usize>,
+    /// Max concurrent subscriptions per source IP (per UID for Unix socket)
+    pub max_subscriptions: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGrpcJwt {
+    /// Secret for HMAC signed tokens (HS256, HS384, HS512)
+    #[serde(default)]
+    pub hmac_secret: Option<String>,
+    /// Path to Ed25519 public key in PEM format for EdDSA signed tokens
+    #[serde(default)]
+    pub ed25519_public_key_path: Option<String>,
+    /// Expected `aud` claim, required if set and not checked otherwise
+    #[serde(default)]
+    pub audience: Option<String>,
+    /// Tokens with these `sub` claims are rejected
+    #[serde(default)]
+    pub revoked_subjects: HashSet<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigGrpcServerTls {
     pub cert_path: String,
     pub key_path: String,
+    /// CA certificates for client certificates verification (mTLS)
+    #[serde(default)]
+    pub ca_path: Option<String>,
+    /// Allow clients without certificate if `ca_path` is set
+    #[serde(default)]
+    pub client_auth_optional: bool,
+    /// Map of certificate SAN (DNS, URI) or subject CN to client identity
+    #[serde(default)]
+    pub client_identities: HashMap<String, String>,
+    /// Interval for checking changes of `cert_path`, `key_path` and `ca_path`,
+    /// new handshakes use reloaded certificates without server restart
+    #[serde(
+        default = "ConfigGrpcServerTls::default_reload_interval",
+        with = "humantime_serde"
+    )]
+    pub reload_interval: Duration,
+}
+
+impl ConfigGrpcServerTls {
+    const fn default_reload_interval() -> Duration {
+        Duration::from_secs(10)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -320,11 +761,48 @@ impl ConfigGrpcCompression {
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigPrometheus {
     /// Address of Prometheus service.
     pub address: SocketAddr,
+    /// Token in `x-token` header for admin routes, routes are disabled if not set
+    #[serde(default)]
+    pub admin_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigTracing {
+    /// OTLP gRPC endpoint of the collector
+    #[serde(default = "ConfigTracing::default_endpoint")]
+    pub endpoint: String,
+    /// Filter of spans in `RUST_LOG` format, plugin and pipeline spans are `debug`
+    #[serde(default = "ConfigTracing::default_filter")]
+    pub filter: String,
+    /// Ratio of sampled traces without sampled parent from client
+    #[serde(default = "ConfigTracing::default_sample_ratio")]
+    pub sample_ratio: f64,
+    #[serde(default = "ConfigTracing::default_service_name")]
+    pub service_name: String,
+}
+
+impl ConfigTracing {
+    fn default_endpoint() -> String {
+        "http://127.0.0.1:4317".to_owned()
+    }
+
+    fn default_filter() -> String {
+        "info".to_owned()
+    }
+
+    const fn default_sample_ratio() -> f64 {
+        1.0
+    }
+
+    fn default_service_name() -> String {
+        env!("CARGO_PKG_NAME").to_owned()
+    }
 }
 
 #[derive(Deserialize)]
@@ -365,3 +843,182 @@ where
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Config, ConfigGrpc, ConfigGrpcUnixSocket},
+        std::path::PathBuf,
+    };
+
+    fn parse_unix_socket(config: &str) -> Result<ConfigGrpcUnixSocket, serde_json::Error> {
+        serde_json::from_str(config)
+    }
+
+    fn parse_grpc(config: &str) -> ConfigGrpc {
+        serde_json::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn test_unix_socket_mode() {
+        for (mode, expected) in [
+            (r#""0660""#, 0o660),
+            (r#""660""#, 0o660),
+            (r#""0o600""#, 0o600),
+            (r#""1777""#, 0o1777),
+            (r#""7777""#, 0o7777),
+        ] {
+            let config = parse_unix_socket(&format!(r#"{{"mode": {mode}}}"#)).unwrap();
+            assert_eq!(config.mode, Some(expected), "mode {mode}");
+        }
+
+        let config = parse_unix_socket(r#"{"mode": null}"#).unwrap();
+        assert_eq!(config.mode, None);
+        let config = parse_unix_socket("{}").unwrap();
+        assert_eq!(config.mode, None);
+
+        for mode in [r#""0980""#, r#""17777""#, r#""rw-rw----""#, r#""""#, "660"] {
+            assert!(
+                parse_unix_socket(&format!(r#"{{"mode": {mode}}}"#)).is_err(),
+                "mode {mode}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_listeners() {
+        let config = parse_grpc(
+            r#"{
+                "address": "127.0.0.1:10000",
+                "unix_socket_path": "/run/geyser.sock",
+                "unix_socket": {"mode": "0600"},
+                "x_token": "secret",
+                "filter_limits": {"accounts": {"max": 1}},
+                "listeners": [
+                    {"name": "local", "unix_socket_path": "/run/local.sock", "filter_limits": {}},
+                    {"name": "admin", "address": "127.0.0.1:10001", "x_token": "admin", "admin": true}
+                ]
+            }"#,
+        );
+        let listeners = config.get_listeners();
+        let names = listeners
+            .iter()
+            .map(|listener| listener.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["tcp", "unix", "local", "admin"]);
+
+        // top level listeners inherit top level options, but not filter limits
+        assert_eq!(listeners[0].address, config.address);
+        assert_eq!(listeners[0].unix_socket_path, None);
+        assert_eq!(listeners[0].x_token.as_deref(), Some("secret"));
+        assert!(listeners[0].filter_limits.is_none());
+        assert_eq!(
+            listeners[1].unix_socket_path,
+            Some(PathBuf::from("/run/geyser.sock"))
+        );
+        assert_eq!(listeners[1].unix_socket.mode, Some(0o600));
+        assert_eq!(listeners[1].x_token.as_deref(), Some("secret"));
+
+        // nothing inherited by listeners from `listeners`
+        assert_eq!(listeners[2].x_token, None);
+        assert_eq!(listeners[2].unix_socket.mode, None);
+        assert!(listeners[2].filter_limits.is_some());
+        assert!(!listeners[2].admin);
+        assert_eq!(listeners[3].x_token.as_deref(), Some("admin"));
+        assert!(listeners[3].admin);
+        assert!(config.has_admin_listener());
+        assert!(config.validate().is_ok());
+
+        let config = parse_grpc(
+            r#"{"listeners": [{"name": "local", "unix_socket_path": "/run/local.sock"}]}"#,
+        );
+        assert_eq!(config.get_listeners().len(), 1);
+        assert!(!config.has_admin_listener());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_listeners_validate() {
+        for (config, valid) in [
+            // no listeners at all
+            (r#"{}"#, false),
+            (r#"{"listeners": []}"#, false),
+            // exactly one of `address` and `unix_socket_path`
+            (r#"{"listeners": [{"name": "a"}]}"#, false),
+            (
+                r#"{"listeners": [{"name": "a", "address": "127.0.0.1:10000", "unix_socket_path": "/run/a.sock"}]}"#,
+                false,
+            ),
+            (r#"{"listeners": [{"name": "a", "address": "127.0.0.1:10000"}]}"#, true),
+            // TCP admin listener requires auth, Unix socket does not
+            (
+                r#"{"address": "127.0.0.1:10000", "listeners": [{"name": "a", "address": "127.0.0.1:10001", "admin": true}]}"#,
+                false,
+            ),
+            (
+                r#"{"address": "127.0.0.1:10000", "listeners": [{"name": "a", "address": "127.0.0.1:10001", "x_token": "t", "admin": true}]}"#,
+                true,
+            ),
+            (
+                r#"{"address": "127.0.0.1:10000", "listeners": [{"name": "a", "unix_socket_path": "/run/a.sock", "admin": true}]}"#,
+                true,
+            ),
+            // top level `x_token` is not inherited by admin listener
+            (
+                r#"{"address": "127.0.0.1:10000", "x_token": "t", "listeners": [{"name": "a", "address": "127.0.0.1:10001", "admin": true}]}"#,
+                false,
+            ),
+            // at least one listener should serve Geyser
+            (
+                r#"{"listeners": [{"name": "a", "unix_socket_path": "/run/a.sock", "admin": true}]}"#,
+                false,
+            ),
+        ] {
+            assert_eq!(parse_grpc(config).validate().is_ok(), valid, "{config}");
+        }
+    }
+
+    #[test]
+    fn test_ingestion_validate() {
+        for (ingestion, pipeline, valid) in [
+            (r#"{}"#, r#"{}"#, true),
+            (r#"{"skip_vote_accounts": true}"#, r#"{}"#, true),
+            (r#"{"skip_vote_transactions": true}"#, r#"{}"#, false),
+            (
+                r#"{"skip_vote_transactions": true}"#,
+                r#"{"block_reconstruction": true}"#,
+                false,
+            ),
+            (
+                r#"{"skip_vote_transactions": true}"#,
+                r#"{"block_reconstruction": false}"#,
+                true,
+            ),
+        ] {
+            let config = format!(
+                r#"{{"libpath": "", "grpc": {{"address": "127.0.0.1:10000", "pipeline": {pipeline}}}, "ingestion": {ingestion}}}"#
+            );
+            let config = Config::load_from_str(&config).unwrap();
+            assert_eq!(config.validate().is_ok(), valid, "{ingestion} {pipeline}");
+        }
+    }
+
+    #[test]
+    fn test_tracing_validate() {
+        for (tracing, valid) in [
+            (r#"null"#, true),
+            (r#"{}"#, true),
+            (r#"{"sample_ratio": 0}"#, true),
+            (r#"{"sample_ratio": 0.25}"#, true),
+            (r#"{"sample_ratio": 1}"#, true),
+            (r#"{"sample_ratio": -0.1}"#, false),
+            (r#"{"sample_ratio": 1.5}"#, false),
+        ] {
+            let config = format!(
+                r#"{{"libpath": "", "grpc": {{"address": "127.0.0.1:10000"}}, "tracing": {tracing}}}"#
+            );
+            let config = Config::load_from_str(&config).unwrap();
+            assert_eq!(config.validate().is_ok(), valid, "{tracing}");
+        }
+    }
+}
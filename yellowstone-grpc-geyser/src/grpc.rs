@@ -1,52 +1,66 @@
 use {
     crate::{
-        config::{ConfigGrpc, ConfigTokio},
-        metrics::{self, DebugClientMessage},
+        admin::GrpcAdminService,
+        config::{ConfigGrpc, ConfigGrpcClientMetrics, ConfigTokio},
+        health::GrpcHealth,
+        ingress::{self, IngressSender},
+        jwt::{TokenClaims, TokenVerifier},
+        logger::FilterSummary,
+        metrics::{
+            self, ClientMetrics, DebugClientMessage, DebugClientStatuses,
+            CLIENT_METRICS_UPDATE_INTERVAL,
+        },
+        otel,
+        peer::{PeerConnectInfo, PeerLimits},
+        pipeline::{
+            self, BroadcastedMessage, EntriesSignatures, ReplayStoredSlotsRequest, ReplayedResponse,
+        },
+        tls::{get_client_identity, TlsServerConfig},
+        unix,
         version::GrpcVersionInfo,
+        watchdog::SlotWatchdog,
     },
-    anyhow::Context,
     log::{error, info},
     prost_types::Timestamp,
-    solana_clock::{Slot, MAX_RECENT_BLOCKHASHES},
-    solana_pubkey::Pubkey,
+    solana_clock::MAX_RECENT_BLOCKHASHES,
     std::{
-        collections::{BTreeMap, HashMap},
+        collections::HashMap,
         sync::{
-            atomic::{AtomicU64, AtomicUsize, Ordering},
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
             Arc,
         },
         time::SystemTime,
     },
     tokio::{
-        fs,
         net::UnixListener,
         runtime::Builder,
         sync::{broadcast, mpsc, oneshot, Mutex, Notify, RwLock, Semaphore},
         task::spawn_blocking,
-        time::{sleep, Duration, Instant},
+        time::{interval, sleep, Duration, MissedTickBehavior},
     },
     tokio_stream::wrappers::ReceiverStream,
     tonic::{
-        service::interceptor::interceptor,
-        transport::{
-            server::{Server, TcpIncoming},
-            Identity, ServerTlsConfig,
+        service::{
+            interceptor::{interceptor, InterceptedService},
+            Routes,
         },
+        transport::server::{Server, TcpIncoming},
         Request, Response, Result as TonicResult, Status, Streaming,
     },
-    tonic_health::server::health_reporter,
+    tonic_reflection::server::Builder as ReflectionBuilder,
+    tracing::{debug_span, info_span, Instrument},
     yellowstone_grpc_proto::{
+        geyser_admin::geyser_admin_server::GeyserAdminServer,
         plugin::{
             filter::{
+                encoded::FilteredUpdateEncodedCache,
+                index::FilterIndex,
                 limits::FilterLimits,
                 message::{FilteredUpdate, FilteredUpdateOneof},
                 name::FilterNames,
                 Filter,
             },
-            message::{
-                CommitmentLevel, Message, MessageBlock, MessageBlockMeta, MessageEntry,
-                MessageSlot, MessageTransactionInfo, SlotStatus,
-            },
+            message::{CommitmentLevel, Message, MessageBlockMeta, SlotStatus},
             proto::geyser_server::{Geyser, GeyserServer},
         },
         prelude::{
@@ -54,13 +68,12 @@ use {
             GetLatestBlockhashRequest, GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse,
             GetVersionRequest, GetVersionResponse, IsBlockhashValidRequest,
             IsBlockhashValidResponse, PingRequest, PongResponse, SubscribeReplayInfoRequest,
-            SubscribeReplayInfoResponse, SubscribeRequest,
+            SubscribeReplayInfoResponse, SubscribeRequest, SubscribeUpdateTimings,
         },
+        ADMIN_FILE_DESCRIPTOR_SET, FILE_DESCRIPTOR_SET,
     },
 };
 
-
-
 #[derive(Debug)]
 struct BlockhashStatus {
     slot: u64,
@@ -249,94 +262,25 @@ impl BlockMetaStorage {
     }
 }
 
-#[derive(Debug, Default)]
-struct MessageId {
-    id: u64,
-}
-
-impl MessageId {
-    fn next(&mut self) -> u64 {
-        self.id = self.id.checked_add(1).expect("message id overflow");
-        self.id
-    }
-}
-
-#[derive(Debug, Default)]
-struct SlotMessages {
-    messages: Vec<Option<(u64, Message)>>, // Option is used for accounts with low write_version
-    messages_slots: Vec<(u64, Message)>,
-    block_meta: Option<Arc<MessageBlockMeta>>,
-    transactions: Vec<Arc<MessageTransactionInfo>>,
-    accounts_dedup: HashMap<Pubkey, (u64, usize)>, // (write_version, message_index)
-    entries: Vec<Arc<MessageEntry>>,
-    sealed: bool,
-    entries_count: usize,
-    confirmed_at: Option<usize>,
-    finalized_at: Option<usize>,
-    parent_slot: Option<Slot>,
-    confirmed: bool,
-    finalized: bool,
-}
-
-impl SlotMessages {
-    pub fn try_seal(&mut self, msgid_gen: &mut MessageId) -> Option<(u64, Message)> {
-        if !self.sealed {
-            if let Some(block_meta) = &self.block_meta {
-                let executed_transaction_count = block_meta.executed_transaction_count as usize;
-                let entries_count = block_meta.entries_count as usize;
-
-                // Additional check `entries_count == 0` due to bug of zero entries on block produced by validator
-                // See GitHub issue: https://github.com/solana-labs/solana/issues/33823
-                if self.transactions.len() == executed_transaction_count
-                    && (entries_count == 0 || self.entries.len() == entries_count)
-                {
-                    let transactions = std::mem::take(&mut self.transactions);
-                    let mut entries = std::mem::take(&mut self.entries);
-                    if entries_count == 0 {
-                        entries.clear();
-                    }
-
-                    let mut accounts = Vec::with_capacity(self.messages.len());
-                    for item in self.messages.iter().flatten() {
-                        if let (_msgid, Message::Account(account)) = item {
-                            accounts.push(Arc::clone(&account.account));
-                        }
-                    }
-
-                    let message_block = Message::Block(Arc::new(MessageBlock::new(
-                        Arc::clone(block_meta),
-                        transactions,
-                        accounts,
-                        entries,
-                    )));
-                    let message = (msgid_gen.next(), message_block);
-                    self.messages.push(Some(message.clone()));
-
-                    self.sealed = true;
-                    self.entries_count = entries_count;
-                    return Some(message);
-                }
-            }
-        }
-
-        None
-    }
-}
-
-type BroadcastedMessage = (CommitmentLevel, Arc<Vec<(u64, Message)>>);
-
-enum ReplayedResponse {
-    Messages(Vec<(u64, Message)>),
-    Lagged(Slot),
+/// Listener settings, added to request extensions by the listener interceptor
+#[derive(Debug)]
+struct GrpcListener {
+    name: String,
+    x_token: Option<String>,
+    token_verifier: Option<TokenVerifier>,
+    client_identities: HashMap<String, String>,
+    filter_limits: Option<Arc<FilterLimits>>,
 }
 
-type ReplayStoredSlotsRequest = (CommitmentLevel, Slot, oneshot::Sender<ReplayedResponse>);
-
 #[derive(Debug)]
 pub struct GrpcService {
     config_snapshot_client_channel_capacity: usize,
     config_channel_capacity: usize,
+    config_client_metrics: ConfigGrpcClientMetrics,
     config_filter_limits: Arc<FilterLimits>,
+    config_filter_limits_identity: HashMap<String, Arc<FilterLimits>>,
+    config_block_reconstruction: bool,
+    peer_limits: Arc<PeerLimits>,
     blocks_meta: Option<BlockMetaStorage>,
     subscribe_id: AtomicUsize,
     snapshot_rx: Mutex<Option<crossbeam_channel::Receiver<Box<Message>>>>,
@@ -345,6 +289,10 @@ pub struct GrpcService {
     replay_first_available_slot: Option<Arc<AtomicU64>>,
     debug_clients_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
     filter_names: Arc<Mutex<FilterNames>>,
+    subscriptions_paused: Arc<AtomicBool>,
+    encoded_cache: Option<Arc<FilteredUpdateEncodedCache>>,
+    filter_index: Option<Arc<FilterIndex>>,
+    entries_signatures: EntriesSignatures,
 }
 
 impl GrpcService {
@@ -352,16 +300,20 @@ impl GrpcService {
     pub async fn create(
         config_tokio: ConfigTokio,
         config: ConfigGrpc,
-        debug_clients_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
+        debug_clients: Option<Arc<DebugClientStatuses>>,
         is_reload: bool,
     ) -> anyhow::Result<(
         Option<crossbeam_channel::Sender<Box<Message>>>,
-        mpsc::UnboundedSender<Message>,
+        IngressSender,
         Arc<Notify>,
+        Arc<GrpcHealth>,
     )> {
         // Validate configuration
-        if config.address.is_none() && config.unix_socket_path.is_none() {
-            return Err(anyhow::anyhow!("Must specify at least one of 'address' or 'unix_socket_path'"));
+        let listeners = config.get_listeners();
+        if listeners.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Must specify at least one of 'address', 'unix_socket_path' or 'listeners'"
+            ));
         }
 
         // Snapshot channel
@@ -392,19 +344,11 @@ impl GrpcService {
                 let (tx, rx) = mpsc::channel(1);
                 (Some(Arc::new(AtomicU64::new(u64::MAX))), Some(tx), Some(rx))
             };
+        let (replay_stats_tx, replay_stats_rx) = mpsc::channel(1);
 
         // Helper function to create a configured server builder
         let create_server_builder = || -> anyhow::Result<Server> {
             let mut server_builder = Server::builder();
-            if let Some(tls_config) = &config.tls_config {
-                let cert = std::fs::read(&tls_config.cert_path)
-                    .context("failed to load cert file")?;
-                let key = std::fs::read(&tls_config.key_path)
-                    .context("failed to load key file")?;
-                server_builder = server_builder
-                    .tls_config(ServerTlsConfig::new().identity(Identity::from_pem(cert, key)))
-                    .context("failed to apply tls_config")?;
-            }
             if let Some(enabled) = config.server_http2_adaptive_window {
                 server_builder = server_builder.http2_adaptive_window(Some(enabled));
             }
@@ -413,7 +357,8 @@ impl GrpcService {
                     server_builder.http2_keepalive_interval(Some(http2_keepalive_interval));
             }
             if let Some(http2_keepalive_timeout) = config.server_http2_keepalive_timeout {
-                server_builder = server_builder.http2_keepalive_timeout(Some(http2_keepalive_timeout));
+                server_builder =
+                    server_builder.http2_keepalive_timeout(Some(http2_keepalive_timeout));
             }
             if let Some(sz) = config.server_initial_connection_window_size {
                 server_builder = server_builder.initial_connection_window_size(sz);
@@ -424,37 +369,116 @@ impl GrpcService {
             Ok(server_builder)
         };
 
+        // Listeners with TLS config (reloaded on certificates change) and auth
+        let listeners = listeners
+            .into_iter()
+            .map(|listener| {
+                let tls_config = listener
+                    .tls_config
+                    .clone()
+                    .map(TlsServerConfig::new)
+                    .transpose()?;
+                let grpc_listener = Arc::new(GrpcListener {
+                    name: listener.name.clone(),
+                    x_token: listener.x_token.clone(),
+                    token_verifier: listener.jwt.clone().map(TokenVerifier::new).transpose()?,
+                    client_identities: listener
+                        .tls_config
+                        .as_ref()
+                        .map(|tls_config| tls_config.client_identities.clone())
+                        .unwrap_or_default(),
+                    filter_limits: listener.filter_limits.clone().map(Arc::new),
+                });
+                Ok((listener, tls_config, grpc_listener))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let peer_limits = PeerLimits::new(config.peers.clone());
+
+        // Health status shared by all listeners, startup is already done on reload
+        let (health, health_service) = GrpcHealth::new(is_reload);
+        let reflection_services = if config.reflection {
+            let builder = || {
+                ReflectionBuilder::configure()
+                    .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+                    .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+            };
+            let admin_builder = || {
+                ReflectionBuilder::configure()
+                    .register_encoded_file_descriptor_set(ADMIN_FILE_DESCRIPTOR_SET)
+                    .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+            };
+            Some((
+                (builder().build_v1()?, builder().build_v1alpha()?),
+                (
+                    admin_builder().build_v1()?,
+                    admin_builder().build_v1alpha()?,
+                ),
+            ))
+        } else {
+            None
+        };
+
         let filter_names = Arc::new(Mutex::new(FilterNames::new(
             config.filter_name_size_limit,
             config.filter_names_size_limit,
             config.filter_names_cleanup_interval,
         )));
+        let filter_index = config
+            .filter_index
+            .then(|| Arc::new(FilterIndex::default()));
+        let subscriptions_paused = Arc::new(AtomicBool::new(false));
+        let entries_signatures = EntriesSignatures::default();
+
+        // Client statuses are required by admin service even without debug clients route
+        let debug_clients =
+            debug_clients.or_else(|| config.has_admin_listener().then(DebugClientStatuses::new));
+        let admin_service = debug_clients.as_ref().map(|debug_clients| {
+            Arc::new(GrpcAdminService {
+                debug_clients: Arc::clone(debug_clients),
+                subscriptions_paused: Arc::clone(&subscriptions_paused),
+                filter_names: Arc::clone(&filter_names),
+                replay_stats_tx,
+                replay_first_available_slot: replay_first_available_slot.clone(),
+                replay_stored_slots: config.replay_stored_slots,
+            })
+        });
 
         // Create Server
         let max_decoding_message_size = config.max_decoding_message_size;
-        let mut service = GeyserServer::new(Self {
+        let service = Arc::new(Self {
             config_snapshot_client_channel_capacity: config.snapshot_client_channel_capacity,
             config_channel_capacity: config.channel_capacity,
+            config_client_metrics: config.client_metrics,
             config_filter_limits: Arc::new(config.filter_limits),
+            config_filter_limits_identity: config
+                .filter_limits_identity
+                .into_iter()
+                .map(|(identity, limits)| (identity, Arc::new(limits)))
+                .collect(),
+            config_block_reconstruction: config.pipeline.block_reconstruction,
+            peer_limits: Arc::clone(&peer_limits),
             blocks_meta,
             subscribe_id: AtomicUsize::new(0),
             snapshot_rx: Mutex::new(snapshot_rx),
             broadcast_tx: broadcast_tx.clone(),
             replay_stored_slots_tx,
             replay_first_available_slot: replay_first_available_slot.clone(),
-            debug_clients_tx,
+            debug_clients_tx: debug_clients.map(|debug_clients| debug_clients.clients_tx()),
             filter_names,
-        })
-        .max_decoding_message_size(max_decoding_message_size);
-        for encoding in config.compression.accept {
-            service = service.accept_compressed(encoding);
-        }
-        for encoding in config.compression.send {
-            service = service.send_compressed(encoding);
-        }
+            subscriptions_paused,
+            encoded_cache: config.encoded_cache.enabled.then(|| {
+                Arc::new(FilteredUpdateEncodedCache::new(
+                    config.encoded_cache.capacity,
+                    config.encoded_cache.max_bytes,
+                ))
+            }),
+            filter_index: filter_index.clone(),
+            entries_signatures: entries_signatures.clone(),
+        });
 
         // Run geyser message loop
-        let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        let (messages_tx, messages_rx) = ingress::channel(config.ingress);
+        let slot_watchdog = SlotWatchdog::new(config.slot_watchdog, Arc::clone(&health));
         spawn_blocking(move || {
             let mut builder = Builder::new_multi_thread();
             if let Some(worker_threads) = config_tokio.worker_threads {
@@ -470,82 +494,112 @@ impl GrpcService {
             if let Some(_tokio_cpus) = config_tokio.affinity.clone() {
                 log::warn!("CPU affinity setting is only supported on Linux. Ignoring affinity configuration on this platform.");
             }
-            builder
+            let result = builder
                 .thread_name_fn(crate::get_thread_name)
                 .enable_all()
                 .build()
                 .expect("Failed to create a new runtime for geyser loop")
-                .block_on(Self::geyser_loop(
+                .block_on(pipeline::geyser_loop(
+                    config.pipeline,
                     messages_rx,
                     blocks_meta_tx,
                     broadcast_tx,
+                    filter_index,
                     replay_stored_slots_rx,
                     replay_first_available_slot,
                     config.replay_stored_slots,
+                    replay_stats_rx,
+                    slot_watchdog,
+                    entries_signatures,
                 ));
+            if let Err(error) = result {
+                error!("geyser loop is stopped: {error}");
+            }
         });
 
         let shutdown = Arc::new(Notify::new());
 
-        // Start servers based on configuration - can start both TCP and Unix simultaneously
+        // Start servers, TCP and Unix listeners can be used simultaneously
         let mut server_tasks = Vec::new();
-
-        // Start TCP server if configured
-        if let Some(address) = config.address {
-            let incoming = TcpIncoming::new(
-                address,
-                true,                          // tcp_nodelay
-                Some(Duration::from_secs(20)), // tcp_keepalive
-            )
-            .map_err(|error| anyhow::anyhow!(error))?;
-
-            info!("gRPC server listening on TCP address: {}", address);
-
-            // Clone service for TCP server
-            let tcp_service = service.clone();
-            let tcp_shutdown = Arc::clone(&shutdown);
-            let tcp_x_token = config.x_token.clone();
-            let tcp_server_builder = create_server_builder()?;
-
-            server_tasks.push(tokio::spawn(async move {
-                Self::start_tcp_server(
-                    tcp_server_builder,
-                    incoming,
-                    tcp_service,
-                    tcp_x_token,
-                    tcp_shutdown,
-                ).await;
-            }));
-        }
-
-        // Start Unix server if configured
-        if let Some(unix_path) = &config.unix_socket_path {
-            // Remove existing socket file if it exists
-            if unix_path.exists() {
-                fs::remove_file(unix_path).await
-                    .with_context(|| format!("Failed to remove existing Unix socket: {:?}", unix_path))?;
+        for (listener, tls_config, grpc_listener) in listeners {
+            // Health is served without auth, so probes work without token
+            let auth_listener = Arc::clone(&grpc_listener);
+            let auth = move |request: Request<()>| Self::check_auth(request, &auth_listener);
+            let mut routes = Routes::new(health_service.clone());
+            if listener.admin {
+                let admin_service = admin_service
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("admin service is not created"))?;
+                routes = routes.add_service(InterceptedService::new(
+                    GeyserAdminServer::from_arc(Arc::clone(admin_service)),
+                    auth.clone(),
+                ));
+            } else {
+                let mut listener_service = GeyserServer::from_arc(Arc::clone(&service))
+                    .max_decoding_message_size(max_decoding_message_size);
+                for encoding in listener.compression.accept {
+                    listener_service = listener_service.accept_compressed(encoding);
+                }
+                for encoding in listener.compression.send {
+                    listener_service = listener_service.send_compressed(encoding);
+                }
+                routes =
+                    routes.add_service(InterceptedService::new(listener_service, auth.clone()));
             }
+            if let Some((reflection, reflection_admin)) = &reflection_services {
+                let (reflection_v1, reflection_v1alpha) = if listener.admin {
+                    reflection_admin
+                } else {
+                    reflection
+                };
+                routes = routes
+                    .add_service(InterceptedService::new(reflection_v1.clone(), auth.clone()))
+                    .add_service(InterceptedService::new(reflection_v1alpha.clone(), auth));
+            }
+            let server_builder = create_server_builder()?;
+            let peer_limits = Arc::clone(&peer_limits);
+            let shutdown = Arc::clone(&shutdown);
+
+            if let Some(address) = listener.address {
+                let incoming = TcpIncoming::new(
+                    address,
+                    true,                          // tcp_nodelay
+                    Some(Duration::from_secs(20)), // tcp_keepalive
+                )
+                .map_err(|error| anyhow::anyhow!(error))?;
 
-            let unix_listener = UnixListener::bind(unix_path)
-                .with_context(|| format!("Failed to bind Unix socket: {:?}", unix_path))?;
-
-            info!("gRPC server listening on Unix socket: {:?}", unix_path);
-
-            // Clone service for Unix server
-            let unix_service = service.clone();
-            let unix_shutdown = Arc::clone(&shutdown);
-            let unix_x_token = config.x_token.clone();
-            let unix_server_builder = create_server_builder()?;
+                info!(
+                    "gRPC server {:?} listening on TCP address: {}",
+                    listener.name, address
+                );
 
-            server_tasks.push(tokio::spawn(async move {
-                Self::start_unix_server(
-                    unix_server_builder,
+                server_tasks.push(tokio::spawn(Self::start_tcp_server(
+                    server_builder,
+                    incoming,
+                    grpc_listener,
+                    peer_limits,
+                    tls_config,
+                    routes,
+                    shutdown,
+                )));
+            } else if let Some(unix_path) = &listener.unix_socket_path {
+                let unix_listener = unix::bind_listener(unix_path, &listener.unix_socket).await?;
+
+                info!(
+                    "gRPC server {:?} listening on Unix socket: {:?}",
+                    listener.name, unix_path
+                );
+
+                server_tasks.push(tokio::spawn(Self::start_unix_server(
+                    server_builder,
                     unix_listener,
-                    unix_service,
-                    unix_x_token,
-                    unix_shutdown,
-                ).await;
-            }));
+                    grpc_listener,
+                    peer_limits,
+                    tls_config,
+                    routes,
+                    shutdown,
+                )));
+            }
         }
 
         // Wait for all servers to complete (they run indefinitely until shutdown)
@@ -555,38 +609,73 @@ impl GrpcService {
             });
         }
 
-        Ok((snapshot_tx, messages_tx, shutdown))
+        Ok((snapshot_tx, messages_tx, shutdown, health))
+    }
+
+    fn check_auth(
+        mut request: Request<()>,
+        listener: &Arc<GrpcListener>,
+    ) -> Result<Request<()>, Status> {
+        request.extensions_mut().insert(Arc::clone(listener));
+        let x_token = listener.x_token.as_deref();
+        let token_verifier = listener.token_verifier.as_ref();
+        if x_token.is_none() && token_verifier.is_none() {
+            return Ok(request);
+        }
+
+        let Some(token) = request.metadata().get("x-token") else {
+            return Err(Status::unauthenticated("No valid auth token"));
+        };
+        if x_token.is_some_and(|x_token| x_token == token) {
+            return Ok(request);
+        }
+        if let Some(token_verifier) = token_verifier {
+            let token = token
+                .to_str()
+                .map_err(|_error| Status::unauthenticated("No valid auth token"))?;
+            let claims = token_verifier.verify(token)?;
+            request.extensions_mut().insert(Arc::new(claims));
+            return Ok(request);
+        }
+        Err(Status::unauthenticated("No valid auth token"))
     }
 
     async fn start_tcp_server(
         server_builder: Server,
         incoming: TcpIncoming,
-        service: GeyserServer<Self>,
-        x_token: Option<String>,
+        listener: Arc<GrpcListener>,
+        peer_limits: Arc<PeerLimits>,
+        tls_config: Option<TlsServerConfig>,
+        routes: Routes,
         shutdown: Arc<Notify>,
     ) {
         tokio::spawn(async move {
-            let (mut health_reporter, health_service) = health_reporter();
-            health_reporter.set_serving::<GeyserServer<Self>>().await;
-
-            let result = server_builder
-                .layer(interceptor(move |request: Request<()>| {
-                    if let Some(x_token) = &x_token {
-                        match request.metadata().get("x-token") {
-                            Some(token) if x_token == token => Ok(request),
-                            _ => Err(Status::unauthenticated("No valid auth token")),
-                        }
-                    } else {
-                        Ok(request)
-                    }
+            let router = server_builder
+                .layer(otel::TraceLayer)
+                .layer(interceptor(|mut request: Request<()>| {
+                    PeerConnectInfo::check_request(&mut request)?;
+                    Ok(request)
                 }))
-                .add_service(health_service)
-                .add_service(service)
-                .serve_with_incoming_shutdown(incoming, shutdown.notified())
-                .await;
+                .add_routes(routes);
+            let incoming = peer_limits.incoming(incoming);
+            let result = match tls_config {
+                Some(tls_config) => {
+                    router
+                        .serve_with_incoming_shutdown(
+                            tls_config.incoming(incoming),
+                            shutdown.notified(),
+                        )
+                        .await
+                }
+                None => {
+                    router
+                        .serve_with_incoming_shutdown(incoming, shutdown.notified())
+                        .await
+                }
+            };
 
             if let Err(e) = result {
-                error!("gRPC TCP server error: {}", e);
+                error!("gRPC TCP server {:?} error: {}", listener.name, e);
             }
         });
     }
@@ -594,389 +683,66 @@ impl GrpcService {
     async fn start_unix_server(
         server_builder: Server,
         unix_listener: UnixListener,
-        service: GeyserServer<Self>,
-        x_token: Option<String>,
+        listener: Arc<GrpcListener>,
+        peer_limits: Arc<PeerLimits>,
+        tls_config: Option<TlsServerConfig>,
+        routes: Routes,
         shutdown: Arc<Notify>,
     ) {
         use tokio_stream::wrappers::UnixListenerStream;
 
         tokio::spawn(async move {
-            let (mut health_reporter, health_service) = health_reporter();
-            health_reporter.set_serving::<GeyserServer<Self>>().await;
-
             let incoming = UnixListenerStream::new(unix_listener);
 
-            let result = server_builder
-                .layer(interceptor(move |request: Request<()>| {
-                    if let Some(x_token) = &x_token {
-                        match request.metadata().get("x-token") {
-                            Some(token) if x_token == token => Ok(request),
-                            _ => Err(Status::unauthenticated("No valid auth token")),
-                        }
-                    } else {
-                        Ok(request)
-                    }
+            let router = server_builder
+                .layer(otel::TraceLayer)
+                .layer(interceptor(|mut request: Request<()>| {
+                    PeerConnectInfo::check_request(&mut request)?;
+                    Ok(request)
                 }))
-                .add_service(health_service)
-                .add_service(service)
-                .serve_with_incoming_shutdown(incoming, shutdown.notified())
-                .await;
-
-            if let Err(e) = result {
-                error!("gRPC Unix server error: {}", e);
-            }
-        });
-    }
-
-    async fn geyser_loop(
-        mut messages_rx: mpsc::UnboundedReceiver<Message>,
-        blocks_meta_tx: Option<mpsc::UnboundedSender<Message>>,
-        broadcast_tx: broadcast::Sender<BroadcastedMessage>,
-        replay_stored_slots_rx: Option<mpsc::Receiver<ReplayStoredSlotsRequest>>,
-        replay_first_available_slot: Option<Arc<AtomicU64>>,
-        replay_stored_slots: u64,
-    ) {
-        const PROCESSED_MESSAGES_MAX: usize = 31;
-        const PROCESSED_MESSAGES_SLEEP: Duration = Duration::from_millis(10);
-
-        let mut msgid_gen = MessageId::default();
-        let mut messages: BTreeMap<u64, SlotMessages> = Default::default();
-        let mut processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
-        let mut processed_first_slot = None;
-        let processed_sleep = sleep(PROCESSED_MESSAGES_SLEEP);
-        tokio::pin!(processed_sleep);
-        let (_tx, rx) = mpsc::channel(1);
-        let mut replay_stored_slots_rx = replay_stored_slots_rx.unwrap_or(rx);
-
-        loop {
-            tokio::select! {
-                Some(message) = messages_rx.recv() => {
-                    metrics::message_queue_size_dec();
-                    let msgid = msgid_gen.next();
-
-                    // Update metrics
-                    if let Message::Slot(slot_message) = &message {
-                        metrics::update_slot_plugin_status(slot_message.status, slot_message.slot);
-                    }
-
-                    // Update blocks info
-                    if let Some(blocks_meta_tx) = &blocks_meta_tx {
-                        if matches!(&message, Message::Slot(_) | Message::BlockMeta(_)) {
-                            let _ = blocks_meta_tx.send(message.clone());
-                        }
-                    }
-
-                    // Remove outdated block reconstruction info
-                    match &message {
-                        // On startup we can receive multiple Confirmed/Finalized slots without BlockMeta message
-                        // With saved first Processed slot we can ignore errors caused by startup process
-                        Message::Slot(msg) if processed_first_slot.is_none() && msg.status == SlotStatus::Processed => {
-                            processed_first_slot = Some(msg.slot);
-                        }
-                        Message::Slot(msg) if msg.status == SlotStatus::Finalized => {
-                            // keep extra 10 slots + slots for replay
-                            if let Some(msg_slot) = msg.slot.checked_sub(10 + replay_stored_slots) {
-                                loop {
-                                    match messages.keys().next().cloned() {
-                                        Some(slot) if slot < msg_slot => {
-                                            if let Some(slot_messages) = messages.remove(&slot) {
-                                                match processed_first_slot {
-                                                    Some(processed_first) if slot <= processed_first => continue,
-                                                    None => continue,
-                                                    _ => {}
-                                                }
-
-                                                if !slot_messages.sealed && slot_messages.finalized_at.is_some() {
-                                                    let mut reasons = vec![];
-                                                    if let Some(block_meta) = slot_messages.block_meta {
-                                                        let block_txn_count = block_meta.executed_transaction_count as usize;
-                                                        let msg_txn_count = slot_messages.transactions.len();
-                                                        if block_txn_count != msg_txn_count {
-                                                            reasons.push("InvalidTxnCount");
-                                                            error!("failed to reconstruct #{slot} -- tx count: {block_txn_count} vs {msg_txn_count}");
-                                                        }
-                                                        let block_entries_count = block_meta.entries_count as usize;
-                                                        let msg_entries_count = slot_messages.entries.len();
-                                                        if block_entries_count != msg_entries_count {
-                                                            reasons.push("InvalidEntriesCount");
-                                                            error!("failed to reconstruct #{slot} -- entries count: {block_entries_count} vs {msg_entries_count}");
-                                                        }
-                                                    } else {
-                                                        reasons.push("NoBlockMeta");
-                                                    }
-                                                    let reason = reasons.join(",");
-
-                                                    metrics::update_invalid_blocks(format!("failed reconstruct {reason}"));
-                                                }
-                                            }
-                                        }
-                                        _ => break,
-                                    }
-                                }
-                                if let Some(stored) = &replay_first_available_slot {
-                                    if let Some(slot) = messages.keys().next().copied() {
-                                        stored.store(slot, Ordering::Relaxed);
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-
-                    // Update block reconstruction info
-                    let slot_messages = messages.entry(message.get_slot()).or_default();
-                    if let Message::Slot(msg) = &message {
-                        match msg.status {
-                            SlotStatus::Processed => {
-                                slot_messages.parent_slot = msg.parent;
-                            },
-                            SlotStatus::Confirmed => {
-                                slot_messages.confirmed = true;
-                            },
-                            SlotStatus::Finalized => {
-                                slot_messages.finalized = true;
-                            },
-                            _ => {}
-                        }
-                    }
-                    if matches!(&message, Message::Slot(_)) {
-                        slot_messages.messages_slots.push((msgid, message.clone()));
-                    } else {
-                        slot_messages.messages.push(Some((msgid, message.clone())));
-
-                        // If we already build Block message, new message will be a problem
-                        if slot_messages.sealed && !(matches!(&message, Message::Entry(_)) && slot_messages.entries_count == 0) {
-                            let kind = match &message {
-                                Message::Slot(_) => "Slot",
-                                Message::Account(_) => "Account",
-                                Message::Transaction(_) => "Transaction",
-                                Message::Entry(_) => "Entry",
-                                Message::BlockMeta(_) => "BlockMeta",
-                                Message::Block(_) => "Block",
-                            };
-                            metrics::update_invalid_blocks(format!("unexpected message {kind}"));
-                        }
-                    }
-                    let mut sealed_block_msg = None;
-                    match &message {
-                        Message::BlockMeta(msg) => {
-                            if slot_messages.block_meta.is_some() {
-                                metrics::update_invalid_blocks("unexpected message: BlockMeta (duplicate)");
-                            }
-                            slot_messages.block_meta = Some(Arc::clone(msg));
-                            sealed_block_msg = slot_messages.try_seal(&mut msgid_gen);
-                        }
-                        Message::Transaction(msg) => {
-                            slot_messages.transactions.push(Arc::clone(&msg.transaction));
-                            sealed_block_msg = slot_messages.try_seal(&mut msgid_gen);
-                        }
-                        // Dedup accounts by max write_version
-                        Message::Account(msg) => {
-                            let write_version = msg.account.write_version;
-                            let msg_index = slot_messages.messages.len() - 1;
-                            if let Some(entry) = slot_messages.accounts_dedup.get_mut(&msg.account.pubkey) {
-                                if entry.0 < write_version {
-                                    // We can replace the message, but in this case we will lose the order
-                                    slot_messages.messages[entry.1] = None;
-                                    *entry = (write_version, msg_index);
-                                }
-                            } else {
-                                slot_messages.accounts_dedup.insert(msg.account.pubkey, (write_version, msg_index));
-                            }
-                        }
-                        Message::Entry(msg) => {
-                            slot_messages.entries.push(Arc::clone(msg));
-                            sealed_block_msg = slot_messages.try_seal(&mut msgid_gen);
-                        }
-                        _ => {}
-                    }
-
-                    // Send messages to filter (and to clients)
-                    let mut messages_vec = Vec::with_capacity(4);
-                    if let Some(sealed_block_msg) = sealed_block_msg {
-                        messages_vec.push(sealed_block_msg);
-                    }
-                    let slot_status = if let Message::Slot(msg) = &message {
-                        Some((msg.slot, msg.status))
-                    } else {
-                        None
-                    };
-                    messages_vec.push((msgid, message));
-
-                    // sometimes we do not receive all statuses
-                    if let Some((slot, status)) = slot_status {
-                        let mut slots = vec![slot];
-                        while let Some((parent, Some(entry))) = slots
-                            .pop()
-                            .and_then(|slot| messages.get(&slot))
-                            .and_then(|entry| entry.parent_slot)
-                            .map(|parent| (parent, messages.get_mut(&parent)))
-                        {
-                            if (status == SlotStatus::Confirmed && !entry.confirmed) ||
-                                (status == SlotStatus::Finalized && !entry.finalized)
-                            {
-                                if status == SlotStatus::Confirmed {
-                                    entry.confirmed = true;
-                                } else if status == SlotStatus::Finalized {
-                                    entry.finalized = true;
-                                }
-
-                                slots.push(parent);
-                                let message_slot = Message::Slot(MessageSlot {
-                                    slot: parent,
-                                    parent: entry.parent_slot,
-                                    status,
-                                    dead_error: None,
-                                    created_at: Timestamp::from(SystemTime::now())
-                                });
-                                messages_vec.push((msgid_gen.next(), message_slot));
-                                metrics::missed_status_message_inc(status);
-                            }
-                        }
-                    }
-
-                    for message in messages_vec.into_iter().rev() {
-                        if let Message::Slot(slot) = &message.1 {
-                            let (mut confirmed_messages, mut finalized_messages) = match slot.status {
-                                SlotStatus::Processed | SlotStatus::FirstShredReceived | SlotStatus::Completed | SlotStatus::CreatedBank | SlotStatus::Dead => {
-                                    (Vec::with_capacity(1), Vec::with_capacity(1))
-                                }
-                                SlotStatus::Confirmed => {
-                                    if let Some(slot_messages) = messages.get_mut(&slot.slot) {
-                                        if !slot_messages.sealed {
-                                            slot_messages.confirmed_at = Some(slot_messages.messages.len());
-                                        }
-                                    }
-
-                                    let vec = messages
-                                        .get(&slot.slot)
-                                        .map(|slot_messages| slot_messages.messages.iter().flatten().cloned().collect())
-                                        .unwrap_or_default();
-                                    (vec, Vec::with_capacity(1))
-                                }
-                                SlotStatus::Finalized => {
-                                    if let Some(slot_messages) = messages.get_mut(&slot.slot) {
-                                        if !slot_messages.sealed {
-                                            slot_messages.finalized_at = Some(slot_messages.messages.len());
-                                        }
-                                    }
-
-                                    let vec = messages
-                                        .get_mut(&slot.slot)
-                                        .map(|slot_messages| slot_messages.messages.iter().flatten().cloned().collect())
-                                        .unwrap_or_default();
-                                    (Vec::with_capacity(1), vec)
-                                }
-                            };
-
-                            // processed
-                            processed_messages.push(message.clone());
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
-                            processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
-                            processed_sleep
-                                .as_mut()
-                                .reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
-
-                            // confirmed
-                            confirmed_messages.push(message.clone());
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
-
-                            // finalized
-                            finalized_messages.push(message);
-                            let _ =
-                                broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
-                        } else {
-                            let mut confirmed_messages = vec![];
-                            let mut finalized_messages = vec![];
-                            if matches!(&message.1, Message::Block(_)) {
-                                if let Some(slot_messages) = messages.get(&message.1.get_slot()) {
-                                    if let Some(confirmed_at) = slot_messages.confirmed_at {
-                                        confirmed_messages.extend(
-                                            slot_messages.messages.as_slice()[confirmed_at..].iter().filter_map(|x| x.clone())
-                                        );
-                                    }
-                                    if let Some(finalized_at) = slot_messages.finalized_at {
-                                        finalized_messages.extend(
-                                            slot_messages.messages.as_slice()[finalized_at..].iter().filter_map(|x| x.clone())
-                                        );
-                                    }
-                                }
-                            }
-
-                            processed_messages.push(message);
-                            if processed_messages.len() >= PROCESSED_MESSAGES_MAX
-                                || !confirmed_messages.is_empty()
-                                || !finalized_messages.is_empty()
-                            {
-                                let _ = broadcast_tx
-                                    .send((CommitmentLevel::Processed, processed_messages.into()));
-                                processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
-                                processed_sleep
-                                    .as_mut()
-                                    .reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
-                            }
-
-                            if !confirmed_messages.is_empty() {
-                                let _ =
-                                    broadcast_tx.send((CommitmentLevel::Confirmed, confirmed_messages.into()));
-                            }
-
-                            if !finalized_messages.is_empty() {
-                                let _ =
-                                    broadcast_tx.send((CommitmentLevel::Finalized, finalized_messages.into()));
-                            }
-                        }
-                    }
+                .add_routes(routes);
+            let incoming = peer_limits.incoming(incoming);
+            let result = match tls_config {
+                Some(tls_config) => {
+                    router
+                        .serve_with_incoming_shutdown(
+                            tls_config.incoming(incoming),
+                            shutdown.notified(),
+                        )
+                        .await
                 }
-                () = &mut processed_sleep => {
-                    if !processed_messages.is_empty() {
-                        let _ = broadcast_tx.send((CommitmentLevel::Processed, processed_messages.into()));
-                        processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
-                    }
-                    processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
+                None => {
+                    router
+                        .serve_with_incoming_shutdown(incoming, shutdown.notified())
+                        .await
                 }
-                Some((commitment, replay_slot, tx)) = replay_stored_slots_rx.recv() => {
-                    if let Some((slot, _)) = messages.first_key_value() {
-                        if replay_slot < *slot {
-                            let _ = tx.send(ReplayedResponse::Lagged(*slot));
-                            continue;
-                        }
-                    }
+            };
 
-                    let mut replayed_messages = Vec::with_capacity(32_768);
-                    for (slot, messages) in messages.iter() {
-                        if *slot >= replay_slot {
-                            replayed_messages.extend_from_slice(&messages.messages_slots);
-                            if commitment == CommitmentLevel::Processed
-                                || (commitment == CommitmentLevel::Finalized && messages.finalized)
-                                || (commitment == CommitmentLevel::Confirmed && messages.confirmed)
-                            {
-                                replayed_messages.extend(messages.messages.iter().filter_map(|v| v.clone()));
-                            }
-                        }
-                    }
-                    let _ = tx.send(ReplayedResponse::Messages(replayed_messages));
-                }
-                else => break,
+            if let Err(e) = result {
+                error!("gRPC Unix server {:?} error: {}", listener.name, e);
             }
-        }
+        });
     }
 
     #[allow(clippy::too_many_arguments)]
     async fn client_loop(
         id: usize,
         endpoint: String,
+        identity: Option<String>,
         stream_tx: mpsc::Sender<TonicResult<FilteredUpdate>>,
         mut client_rx: mpsc::UnboundedReceiver<Option<(Option<u64>, Filter)>>,
         mut snapshot_rx: Option<crossbeam_channel::Receiver<Box<Message>>>,
         mut messages_rx: broadcast::Receiver<BroadcastedMessage>,
         replay_stored_slots_tx: Option<mpsc::Sender<ReplayStoredSlotsRequest>>,
         debug_client_tx: Option<mpsc::UnboundedSender<DebugClientMessage>>,
+        mut client_metrics: ClientMetrics,
+        encoded_cache: Option<Arc<FilteredUpdateEncodedCache>>,
+        filter_index: Option<Arc<FilterIndex>>,
+        entries_signatures: EntriesSignatures,
         drop_client: impl FnOnce(),
     ) {
         let mut filter = Filter::default();
+        let mut filter_index_client = None;
         metrics::update_subscriptions(&endpoint, None, Some(&filter));
 
         metrics::connections_total_inc();
@@ -984,7 +750,18 @@ impl GrpcService {
             id,
             filter: Box::new(filter.clone()),
         });
-        info!("client #{id}: new");
+        if let Some(identity) = &identity {
+            metrics::connections_identity_inc(identity);
+            DebugClientMessage::maybe_send(&debug_client_tx, || {
+                DebugClientMessage::UpdateIdentity {
+                    id,
+                    identity: identity.clone(),
+                }
+            });
+            info!(client_id = id, endpoint:% = endpoint, identity:% = identity; "client #{id}: new, identity: {identity}");
+        } else {
+            info!(client_id = id, endpoint:% = endpoint; "client #{id}: new");
+        }
 
         let mut is_alive = true;
         if let Some(snapshot_rx) = snapshot_rx.take() {
@@ -996,13 +773,23 @@ impl GrpcService {
                 snapshot_rx,
                 &mut is_alive,
                 &mut filter,
+                &mut client_metrics,
+                &entries_signatures,
             )
             .await;
         }
 
         if is_alive {
+            if let Some(filter_index) = &filter_index {
+                filter_index_client = filter_index.update(id, &filter);
+            }
+            let mut client_metrics_interval = interval(CLIENT_METRICS_UPDATE_INTERVAL);
+            client_metrics_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
             'outer: loop {
                 tokio::select! {
+                    _ = client_metrics_interval.tick() => {
+                        client_metrics.refresh(&stream_tx);
+                    }
                     mut message = client_rx.recv() => {
                         // forward to latest filter
                         loop {
@@ -1021,13 +808,17 @@ impl GrpcService {
                         match message {
                             Some(Some((from_slot, filter_new))) => {
                                 metrics::update_subscriptions(&endpoint, Some(&filter), Some(&filter_new));
+                                entries_signatures.update(Some(&filter), Some(&filter_new));
                                 filter = filter_new;
+                                if let Some(filter_index) = &filter_index {
+                                    filter_index_client = filter_index.update(id, &filter);
+                                }
                                 DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::UpdateFilter { id, filter: Box::new(filter.clone()) });
-                                info!("client #{id}: filter updated");
+                                info!(client_id = id, endpoint:% = endpoint, filter:% = FilterSummary(&filter); "client #{id}: filter updated");
 
                                 if let Some(from_slot) = from_slot {
                                     let Some(replay_stored_slots_tx) = &replay_stored_slots_tx else {
-                                        info!("client #{id}: from_slot is not supported");
+                                        info!(client_id = id, endpoint:% = endpoint, error_kind = "replay_unsupported"; "client #{id}: from_slot is not supported");
                                         tokio::spawn(async move {
                                             let _ = stream_tx.send(Err(Status::internal("from_slot is not supported"))).await;
                                         });
@@ -1037,17 +828,17 @@ impl GrpcService {
                                     let (tx, rx) = oneshot::channel();
                                     let commitment = filter.get_commitment_level();
                                     if let Err(_error) = replay_stored_slots_tx.send((commitment, from_slot, tx)).await {
-                                        error!("client #{id}: failed to send from_slot request");
+                                        error!(client_id = id, endpoint:% = endpoint, error_kind = "replay_request"; "client #{id}: failed to send from_slot request");
                                         tokio::spawn(async move {
                                             let _ = stream_tx.send(Err(Status::internal("failed to send from_slot request"))).await;
                                         });
                                         break 'outer;
                                     }
 
-                                    let mut messages = match rx.await {
+                                    let messages = match rx.await {
                                         Ok(ReplayedResponse::Messages(messages)) => messages,
                                         Ok(ReplayedResponse::Lagged(slot)) => {
-                                            info!("client #{id}: broadcast from {from_slot} is not available");
+                                            info!(client_id = id, endpoint:% = endpoint, slot = from_slot, error_kind = "replay_unavailable"; "client #{id}: broadcast from {from_slot} is not available");
                                             tokio::spawn(async move {
                                                 let message = format!(
                                                     "broadcast from {from_slot} is not available, last available: {slot}"
@@ -1057,7 +848,7 @@ impl GrpcService {
                                             break 'outer;
                                         },
                                         Err(_error) => {
-                                            error!("client #{id}: failed to get replay response");
+                                            error!(client_id = id, endpoint:% = endpoint, error_kind = "replay_response"; "client #{id}: failed to get replay response");
                                             tokio::spawn(async move {
                                                 let _ = stream_tx.send(Err(Status::internal("failed to get replay response"))).await;
                                             });
@@ -1065,13 +856,22 @@ impl GrpcService {
                                         }
                                     };
 
-                                    messages.sort_by_key(|msg| msg.0);
-                                    for (_msgid, message) in messages.iter() {
-                                        for message in filter.get_updates(message, Some(commitment)) {
+                                    for (msgid, message) in messages.iter() {
+                                        for mut message in filter.get_updates(message, Some(commitment)) {
+                                            if let Some(encoded_cache) = &encoded_cache {
+                                                metrics::encoded_cache_inc(encoded_cache.encode(*msgid, &mut message));
+                                            }
+                                            if filter.get_timings() {
+                                                message.timings = Some(SubscribeUpdateTimings {
+                                                    broadcasted: None,
+                                                    sent: Some(Timestamp::from(SystemTime::now())),
+                                                });
+                                            }
+                                            let message_size = client_metrics.message_size(&message);
                                             match stream_tx.send(Ok(message)).await {
-                                                Ok(()) => {}
+                                                Ok(()) => client_metrics.message_sent(message_size),
                                                 Err(mpsc::error::SendError(_)) => {
-                                                    error!("client #{id}: stream closed");
+                                                    error!(client_id = id, endpoint:% = endpoint, error_kind = "stream_closed"; "client #{id}: stream closed");
                                                     break 'outer;
                                                 }
                                             }
@@ -1088,13 +888,13 @@ impl GrpcService {
                         }
                     }
                     message = messages_rx.recv() => {
-                        let (commitment, messages) = match message {
-                            Ok((commitment, messages)) => (commitment, messages),
+                        let BroadcastedMessage { commitment, messages, routes, broadcasted_at } = match message {
+                            Ok(message) => message,
                             Err(broadcast::error::RecvError::Closed) => {
                                 break 'outer;
                             },
                             Err(broadcast::error::RecvError::Lagged(_)) => {
-                                info!("client #{id}: lagged to receive geyser messages");
+                                info!(client_id = id, endpoint:% = endpoint, error_kind = "lagged_receive"; "client #{id}: lagged to receive geyser messages");
                                 tokio::spawn(async move {
                                     let _ = stream_tx.send(Err(Status::internal("lagged to receive geyser messages"))).await;
                                 });
@@ -1103,24 +903,61 @@ impl GrpcService {
                         };
 
                         if commitment == filter.get_commitment_level() {
-                            for (_msgid, message) in messages.iter() {
-                                for message in filter.get_updates(message, Some(commitment)) {
+                            // only matched accounts and transactions if stream is in the index
+                            let positions = routes
+                                .as_ref()
+                                .zip(filter_index_client.as_ref())
+                                .and_then(|(routes, client)| routes.get_positions(client));
+                            let messages_iter: Box<dyn Iterator<Item = &(u64, Message)>> = match &positions {
+                                Some(positions) => Box::new(positions.iter().map(|position| &messages[*position])),
+                                None => Box::new(messages.iter()),
+                            };
+                            let span = debug_span!("client_loop.send", commitment = ?commitment, messages = messages.len(), updates = tracing::field::Empty);
+                            let _span_guard = span.enter();
+                            let broadcasted = filter.get_timings().then(|| Timestamp::from(broadcasted_at));
+                            let mut updates_sent = 0;
+                            for (msgid, message) in messages_iter {
+                                for mut message in filter.get_updates(message, Some(commitment)) {
+                                    if let Some(encoded_cache) = &encoded_cache {
+                                        metrics::encoded_cache_inc(encoded_cache.encode(*msgid, &mut message));
+                                    }
+                                    if let Some(broadcasted) = broadcasted {
+                                        message.timings = Some(SubscribeUpdateTimings {
+                                            broadcasted: Some(broadcasted),
+                                            sent: Some(Timestamp::from(SystemTime::now())),
+                                        });
+                                    }
+                                    updates_sent += 1;
+                                    let message_size = client_metrics.message_size(&message);
                                     match stream_tx.try_send(Ok(message)) {
-                                        Ok(()) => {}
+                                        Ok(()) => client_metrics.message_sent(message_size),
                                         Err(mpsc::error::TrySendError::Full(_)) => {
-                                            error!("client #{id}: lagged to send an update");
+                                            error!(client_id = id, endpoint:% = endpoint, error_kind = "lagged_send"; "client #{id}: lagged to send an update");
                                             tokio::spawn(async move {
                                                 let _ = stream_tx.send(Err(Status::internal("lagged to send an update"))).await;
                                             });
                                             break 'outer;
                                         }
                                         Err(mpsc::error::TrySendError::Closed(_)) => {
-                                            error!("client #{id}: stream closed");
+                                            error!(client_id = id, endpoint:% = endpoint, error_kind = "stream_closed"; "client #{id}: stream closed");
                                             break 'outer;
                                         }
                                     }
                                 }
                             }
+                            span.record("updates", updates_sent);
+                            if updates_sent > 0 {
+                                let elapsed = broadcasted_at.elapsed().unwrap_or_default();
+                                metrics::updates_latency_observe("client_loop", elapsed);
+                            }
+                            client_metrics.update_queue_size(&stream_tx);
+                            let slot = match &routes {
+                                Some(routes) => routes.get_slot_max(),
+                                None => messages.iter().map(|(_msgid, message)| message.get_slot()).max(),
+                            };
+                            if let Some(slot) = slot {
+                                client_metrics.update_lag(commitment, slot);
+                            }
                         }
 
                         if commitment == CommitmentLevel::Processed && debug_client_tx.is_some() {
@@ -1136,12 +973,20 @@ impl GrpcService {
         }
 
         metrics::connections_total_dec();
+        if let Some(identity) = &identity {
+            metrics::connections_identity_dec(identity);
+        }
         DebugClientMessage::maybe_send(&debug_client_tx, || DebugClientMessage::Removed { id });
         metrics::update_subscriptions(&endpoint, Some(&filter), None);
-        info!("client #{id}: removed");
+        entries_signatures.update(Some(&filter), None);
+        if let Some(filter_index) = &filter_index {
+            filter_index.remove(id);
+        }
+        info!(client_id = id, endpoint:% = endpoint; "client #{id}: removed");
         drop_client();
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn client_loop_snapshot(
         id: usize,
         endpoint: &str,
@@ -1150,8 +995,10 @@ impl GrpcService {
         snapshot_rx: crossbeam_channel::Receiver<Box<Message>>,
         is_alive: &mut bool,
         filter: &mut Filter,
+        client_metrics: &mut ClientMetrics,
+        entries_signatures: &EntriesSignatures,
     ) {
-        info!("client #{id}: going to receive snapshot data");
+        info!(client_id = id, endpoint:% = endpoint; "client #{id}: going to receive snapshot data");
 
         // we start with default filter, for snapshot we need wait actual filter first
         while *is_alive {
@@ -1159,15 +1006,16 @@ impl GrpcService {
                 Some(Some((_from_slot, filter_new))) => {
                     if let Some(msg) = filter_new.get_pong_msg() {
                         if stream_tx.send(Ok(msg)).await.is_err() {
-                            error!("client #{id}: stream closed");
+                            error!(client_id = id, endpoint:% = endpoint, error_kind = "stream_closed"; "client #{id}: stream closed");
                             *is_alive = false;
                         }
                         continue;
                     }
 
                     metrics::update_subscriptions(endpoint, Some(filter), Some(&filter_new));
+                    entries_signatures.update(Some(filter), Some(&filter_new));
                     *filter = filter_new;
-                    info!("client #{id}: filter updated");
+                    info!(client_id = id, endpoint:% = endpoint, filter:% = FilterSummary(filter); "client #{id}: filter updated");
                     break;
                 }
                 Some(None) => {
@@ -1190,17 +1038,19 @@ impl GrpcService {
                     continue;
                 }
                 Err(crossbeam_channel::TryRecvError::Disconnected) => {
-                    info!("client #{id}: end of startup");
+                    info!(client_id = id, endpoint:% = endpoint; "client #{id}: end of startup");
                     break;
                 }
             };
 
             for message in filter.get_updates(&message, None) {
+                let message_size = client_metrics.message_size(&message);
                 if stream_tx.send(Ok(message)).await.is_err() {
-                    error!("client #{id}: stream closed");
+                    error!(client_id = id, endpoint:% = endpoint, error_kind = "stream_closed"; "client #{id}: stream closed");
                     *is_alive = false;
                     break;
                 }
+                client_metrics.message_sent(message_size);
             }
         }
     }
@@ -1214,6 +1064,11 @@ impl Geyser for GrpcService {
         &self,
         mut request: Request<Streaming<SubscribeRequest>>,
     ) -> TonicResult<Response<Self::SubscribeStream>> {
+        TokenClaims::check_method(&request, "Subscribe")?;
+        if self.subscriptions_paused.load(Ordering::Relaxed) {
+            return Err(Status::unavailable("new subscriptions are paused"));
+        }
+        let peer_guard = self.peer_limits.subscription_open(&request)?;
         let id = self.subscribe_id.fetch_add(1, Ordering::Relaxed);
 
         let x_request_snapshot = request.metadata().contains_key("x-request-snapshot");
@@ -1263,12 +1118,40 @@ impl Geyser for GrpcService {
             .get("x-endpoint")
             .and_then(|h| h.to_str().ok().map(|s| s.to_string()))
             .unwrap_or_else(|| "".to_owned());
+        DebugClientMessage::maybe_send(&self.debug_clients_tx, || DebugClientMessage::New {
+            id,
+            endpoint: endpoint.clone(),
+            client_tx: client_tx.clone(),
+            stream_tx: stream_tx.clone(),
+        });
 
-        let config_filter_limits = Arc::clone(&self.config_filter_limits);
+        let claims = request.extensions().get::<Arc<TokenClaims>>().cloned();
+        let listener = request.extensions().get::<Arc<GrpcListener>>().cloned();
+        let identity = listener
+            .as_ref()
+            .and_then(|listener| get_client_identity(&request, &listener.client_identities))
+            .or_else(|| claims.as_ref().and_then(|claims| claims.sub.clone()));
+        let config_filter_limits = identity
+            .as_ref()
+            .and_then(|identity| self.config_filter_limits_identity.get(identity))
+            .or_else(|| {
+                listener
+                    .as_ref()
+                    .and_then(|listener| listener.filter_limits.as_ref())
+            })
+            .unwrap_or(&self.config_filter_limits);
+        let config_filter_limits = match &claims {
+            Some(claims) => claims.get_filter_limits(config_filter_limits),
+            None => Arc::clone(config_filter_limits),
+        };
+        let client_metrics =
+            ClientMetrics::new(&self.config_client_metrics, &endpoint, identity.as_deref());
         let filter_names = Arc::clone(&self.filter_names);
+        let block_reconstruction = self.config_block_reconstruction;
         let incoming_stream_tx = stream_tx.clone();
         let incoming_client_tx = client_tx;
         let incoming_exit = Arc::clone(&notify_exit2);
+        let incoming_debug_client_tx = self.debug_clients_tx.clone();
         tokio::spawn(async move {
             let exit = incoming_exit.notified();
             tokio::pin!(exit);
@@ -1283,11 +1166,16 @@ impl Geyser for GrpcService {
                             let mut filter_names = filter_names.lock().await;
                             filter_names.try_clean();
 
-                            if let Err(error) = match Filter::new(&request, &config_filter_limits, &mut filter_names) {
+                            let filter = if !block_reconstruction && !request.blocks.is_empty() {
+                                Err("blocks are not available, block reconstruction is disabled".to_owned())
+                            } else {
+                                Filter::new(&request, &config_filter_limits, &mut filter_names).map_err(|error| error.to_string())
+                            };
+                            if let Err(error) = match filter {
                                 Ok(filter) => {
                                     if let Some(msg) = filter.get_pong_msg() {
                                         if incoming_stream_tx.send(Ok(msg)).await.is_err() {
-                                            error!("client #{id}: stream closed");
+                                            error!(client_id = id, error_kind = "stream_closed"; "client #{id}: stream closed");
                                             let _ = incoming_client_tx.send(None);
                                             break;
                                         }
@@ -1295,11 +1183,14 @@ impl Geyser for GrpcService {
                                     }
 
                                     match incoming_client_tx.send(Some((request.from_slot, filter))) {
-                                        Ok(()) => Ok(()),
+                                        Ok(()) => {
+                                            DebugClientMessage::maybe_send(&incoming_debug_client_tx, || DebugClientMessage::UpdateRequest { id, request: Box::new(request) });
+                                            Ok(())
+                                        }
                                         Err(error) => Err(error.to_string()),
                                     }
                                 },
-                                Err(error) => Err(error.to_string()),
+                                Err(error) => Err(error),
                             } {
                                 let err = Err(Status::invalid_argument(format!(
                                     "failed to create filter: {error}"
@@ -1321,28 +1212,39 @@ impl Geyser for GrpcService {
             }
         });
 
-        tokio::spawn(Self::client_loop(
-            id,
-            endpoint,
-            stream_tx,
-            client_rx,
-            snapshot_rx,
-            self.broadcast_tx.subscribe(),
-            self.replay_stored_slots_tx.clone(),
-            self.debug_clients_tx.clone(),
-            move || {
-                notify_exit1.notify_one();
-                notify_exit2.notify_one();
-            },
-        ));
+        let span = info_span!("client_loop", client.id = id, endpoint = %endpoint);
+        tokio::spawn(
+            Self::client_loop(
+                id,
+                endpoint,
+                identity,
+                stream_tx,
+                client_rx,
+                snapshot_rx,
+                self.broadcast_tx.subscribe(),
+                self.replay_stored_slots_tx.clone(),
+                self.debug_clients_tx.clone(),
+                client_metrics,
+                self.encoded_cache.clone(),
+                self.filter_index.clone(),
+                self.entries_signatures.clone(),
+                move || {
+                    drop(peer_guard);
+                    notify_exit1.notify_one();
+                    notify_exit2.notify_one();
+                },
+            )
+            .instrument(span),
+        );
 
         Ok(Response::new(ReceiverStream::new(stream_rx)))
     }
 
     async fn subscribe_first_available_slot(
         &self,
-        _request: Request<SubscribeReplayInfoRequest>,
+        request: Request<SubscribeReplayInfoRequest>,
     ) -> Result<Response<SubscribeReplayInfoResponse>, Status> {
+        TokenClaims::check_method(&request, "SubscribeReplayInfo")?;
         let response = SubscribeReplayInfoResponse {
             first_available: self
                 .replay_first_available_slot
@@ -1353,6 +1255,7 @@ impl Geyser for GrpcService {
     }
 
     async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+        TokenClaims::check_method(&request, "Ping")?;
         let count = request.get_ref().count;
         let response = PongResponse { count };
         Ok(Response::new(response))
@@ -1362,6 +1265,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetLatestBlockhashRequest>,
     ) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+        TokenClaims::check_method(&request, "GetLatestBlockhash")?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1385,6 +1289,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetBlockHeightRequest>,
     ) -> Result<Response<GetBlockHeightResponse>, Status> {
+        TokenClaims::check_method(&request, "GetBlockHeight")?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1405,6 +1310,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<GetSlotRequest>,
     ) -> Result<Response<GetSlotResponse>, Status> {
+        TokenClaims::check_method(&request, "GetSlot")?;
         if let Some(blocks_meta) = &self.blocks_meta {
             blocks_meta
                 .get_block(
@@ -1421,6 +1327,7 @@ impl Geyser for GrpcService {
         &self,
         request: Request<IsBlockhashValidRequest>,
     ) -> Result<Response<IsBlockhashValidResponse>, Status> {
+        TokenClaims::check_method(&request, "IsBlockhashValid")?;
         if let Some(blocks_meta) = &self.blocks_meta {
             let req = request.get_ref();
             blocks_meta
@@ -1433,8 +1340,9 @@ impl Geyser for GrpcService {
 
     async fn get_version(
         &self,
-        _request: Request<GetVersionRequest>,
+        request: Request<GetVersionRequest>,
     ) -> Result<Response<GetVersionResponse>, Status> {
+        TokenClaims::check_method(&request, "GetVersion")?;
         Ok(Response::new(GetVersionResponse {
             version: serde_json::to_string(&GrpcVersionInfo::default()).unwrap(),
         }))
@@ -0,0 +1,83 @@
+use {
+    log::info,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+    tokio::time::sleep,
+    tonic_health::{
+        pb::health_server::{Health, HealthServer},
+        server::health_reporter,
+        ServingStatus,
+    },
+    yellowstone_grpc_proto::plugin::proto::geyser_server::SERVICE_NAME,
+};
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Serving status of `geyser.Geyser` and of the whole server (empty service name),
+/// shared by all listeners. Not serving until end of startup and while slot updates are stalled.
+#[derive(Debug)]
+pub struct GrpcHealth {
+    startup_done: AtomicBool,
+    slot_stalled: AtomicBool,
+}
+
+impl GrpcHealth {
+    pub fn new(startup_done: bool) -> (Arc<Self>, HealthServer<impl Health>) {
+        let (mut reporter, service) = health_reporter();
+        let health = Arc::new(Self {
+            startup_done: AtomicBool::new(startup_done),
+            slot_stalled: AtomicBool::new(false),
+        });
+
+        // status updater stopped once service dropped
+        let health_weak = Arc::downgrade(&health);
+        tokio::spawn(async move {
+            let mut serving = None;
+            loop {
+                let Some(health) = health_weak.upgrade() else {
+                    break;
+                };
+                let new_serving = health.is_serving();
+                drop(health);
+
+                if serving != Some(new_serving) {
+                    serving = Some(new_serving);
+                    let status = if new_serving {
+                        ServingStatus::Serving
+                    } else {
+                        ServingStatus::NotServing
+                    };
+                    for service_name in ["", SERVICE_NAME] {
+                        reporter.set_service_status(service_name, status).await;
+                    }
+                    info!("gRPC health status: {status:?}");
+                }
+
+                sleep(HEALTH_CHECK_INTERVAL).await;
+            }
+        });
+
+        (health, service)
+    }
+
+    fn is_serving(&self) -> bool {
+        self.is_startup_done() && !self.slot_stalled.load(Ordering::Relaxed)
+    }
+
+    pub fn is_startup_done(&self) -> bool {
+        self.startup_done.load(Ordering::Relaxed)
+    }
+
+    pub fn set_startup_done(&self) {
+        self.startup_done.store(true, Ordering::Relaxed);
+    }
+
+    pub fn set_slot_stalled(&self, stalled: bool) {
+        self.slot_stalled.store(stalled, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,398 @@
+use {
+    crate::{
+        config::{ConfigGrpcIngress, ConfigGrpcIngressOverloadPolicy},
+        metrics,
+    },
+    log::warn,
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+    tokio::{
+        runtime::Handle,
+        sync::mpsc::{
+            self,
+            error::{SendTimeoutError, TrySendError},
+        },
+    },
+    yellowstone_grpc_proto::plugin::message::{Message, MessageNotice},
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("geyser loop is stopped")]
+pub struct IngressClosedError;
+
+#[derive(Debug, Default)]
+struct IngressDropped {
+    accounts: AtomicU64,
+    transactions: AtomicU64,
+    entries: AtomicU64,
+}
+
+/// Queue of messages from plugin to geyser loop with optional capacity.
+/// Should be created within Tokio runtime, `block` policy uses its timer.
+pub fn channel(config: ConfigGrpcIngress) -> (IngressSender, IngressReceiver) {
+    let dropped = Arc::new(IngressDropped::default());
+    let (tx, rx) = match config.capacity {
+        Some(capacity) => {
+            let (tx, rx) = mpsc::channel(capacity.max(1));
+            (
+                IngressTx::Bounded {
+                    tx,
+                    config,
+                    handle: Handle::current(),
+                },
+                IngressRx::Bounded(rx),
+            )
+        }
+        None => {
+            let (tx, rx) = mpsc::unbounded_channel();
+            (IngressTx::Unbounded(tx), IngressRx::Unbounded(rx))
+        }
+    };
+    (
+        IngressSender {
+            tx,
+            dropped: Arc::clone(&dropped),
+        },
+        IngressReceiver { rx, dropped },
+    )
+}
+
+/// What to do with the message, depends on the overload policy and the number of queued messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IngressAction {
+    /// Wait for free space as long as needed
+    Send,
+    /// Wait for free space up to timeout, drop after
+    SendTimeout(Duration),
+    Drop,
+}
+
+impl IngressAction {
+    const fn get(
+        config: &ConfigGrpcIngress,
+        message: &Message,
+        len: usize,
+        capacity: usize,
+    ) -> Self {
+        match (config.overload_policy, message) {
+            // transactions, votes included, are never shed, only accounts and entries
+            (ConfigGrpcIngressOverloadPolicy::Shed, Message::Account(_)) if len >= capacity / 2 => {
+                Self::Drop
+            }
+            (ConfigGrpcIngressOverloadPolicy::Shed, Message::Entry(_))
+                if len >= capacity / 4 * 3 =>
+            {
+                Self::Drop
+            }
+            (
+                ConfigGrpcIngressOverloadPolicy::Block,
+                Message::Account(_) | Message::Transaction(_) | Message::Entry(_),
+            ) => Self::SendTimeout(config.block_timeout),
+            _ => Self::Send,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum IngressTx {
+    Unbounded(mpsc::UnboundedSender<Message>),
+    Bounded {
+        tx: mpsc::Sender<Message>,
+        config: ConfigGrpcIngress,
+        handle: Handle,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct IngressSender {
+    tx: IngressTx,
+    dropped: Arc<IngressDropped>,
+}
+
+impl IngressSender {
+    /// Message dropped by overload policy is not an error, error returned only on closed queue.
+    /// Waits for free space in the queue, should not be called from async context.
+    pub fn send(&self, message: Message) -> Result<(), IngressClosedError> {
+        let (tx, config, handle) = match &self.tx {
+            IngressTx::Unbounded(tx) => {
+                tx.send(message).map_err(|_error| IngressClosedError)?;
+                metrics::message_queue_size_inc();
+                return Ok(());
+            }
+            IngressTx::Bounded { tx, config, handle } => (tx, config, handle),
+        };
+
+        let len = tx.max_capacity() - tx.capacity();
+        let message = match IngressAction::get(config, &message, len, tx.max_capacity()) {
+            IngressAction::Drop => {
+                self.drop_message(&message);
+                return Ok(());
+            }
+            action => match tx.try_send(message) {
+                Ok(()) => None,
+                Err(TrySendError::Full(message)) => Some((action, message)),
+                Err(TrySendError::Closed(_message)) => return Err(IngressClosedError),
+            },
+        };
+        match message {
+            None => {}
+            Some((IngressAction::SendTimeout(timeout), message)) => {
+                match handle.block_on(tx.send_timeout(message, timeout)) {
+                    Ok(()) => {}
+                    Err(SendTimeoutError::Timeout(message)) => {
+                        self.drop_message(&message);
+                        return Ok(());
+                    }
+                    Err(SendTimeoutError::Closed(_message)) => return Err(IngressClosedError),
+                }
+            }
+            Some((_action, message)) => {
+                tx.blocking_send(message)
+                    .map_err(|_error| IngressClosedError)?;
+            }
+        }
+        metrics::message_queue_size_inc();
+        Ok(())
+    }
+
+    fn drop_message(&self, message: &Message) {
+        let (counter, kind) = match message {
+            Message::Account(_) => (&self.dropped.accounts, "account"),
+            Message::Transaction(_) => (&self.dropped.transactions, "transaction"),
+            Message::Entry(_) => (&self.dropped.entries, "entry"),
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        metrics::ingress_dropped_inc(kind);
+    }
+}
+
+#[derive(Debug)]
+enum IngressRx {
+    Unbounded(mpsc::UnboundedReceiver<Message>),
+    Bounded(mpsc::Receiver<Message>),
+}
+
+#[derive(Debug)]
+pub struct IngressReceiver {
+    rx: IngressRx,
+    dropped: Arc<IngressDropped>,
+}
+
+impl IngressReceiver {
+    pub async fn recv(&mut self) -> Option<Message> {
+        match &mut self.rx {
+            IngressRx::Unbounded(rx) => rx.recv().await,
+            IngressRx::Bounded(rx) => rx.recv().await,
+        }
+    }
+
+    /// Notice with number of messages dropped since the previous call, if any
+    pub fn take_dropped_notice(&self) -> Option<Message> {
+        let accounts = self.dropped.accounts.swap(0, Ordering::Relaxed);
+        let transactions = self.dropped.transactions.swap(0, Ordering::Relaxed);
+        let entries = self.dropped.entries.swap(0, Ordering::Relaxed);
+        if accounts == 0 && transactions == 0 && entries == 0 {
+            return None;
+        }
+
+        warn!(
+            "queue overload, dropped accounts: {accounts}, transactions: {transactions}, entries: {entries}"
+        );
+        Some(Message::Notice(Arc::new(MessageNotice::messages_dropped(
+            accounts,
+            transactions,
+            entries,
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{channel, IngressAction},
+        crate::config::{ConfigGrpcIngress, ConfigGrpcIngressOverloadPolicy},
+        prost_types::Timestamp,
+        std::{sync::Arc, time::Duration},
+        tokio::runtime::Runtime,
+        yellowstone_grpc_proto::{
+            geyser::subscribe_update_notice::Notice as NoticeOneof,
+            plugin::message::{
+                Message, MessageAccount, MessageAccountInfo, MessageEntry, MessageNotice,
+                MessageSlot, MessageTransaction, MessageTransactionInfo, SlotStatus,
+            },
+        },
+    };
+
+    const fn config(overload_policy: ConfigGrpcIngressOverloadPolicy) -> ConfigGrpcIngress {
+        ConfigGrpcIngress {
+            capacity: Some(8),
+            overload_policy,
+            block_timeout: Duration::from_millis(10),
+        }
+    }
+
+    fn slot() -> Message {
+        Message::Slot(MessageSlot {
+            slot: 0,
+            parent: None,
+            status: SlotStatus::Processed,
+            dead_error: None,
+            timings: None,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    fn account() -> Message {
+        Message::Account(MessageAccount {
+            account: Arc::new(MessageAccountInfo {
+                pubkey: Default::default(),
+                lamports: 0,
+                owner: Default::default(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![],
+                write_version: 0,
+                txn_signature: None,
+            }),
+            slot: 0,
+            is_startup: false,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    fn transaction(is_vote: bool) -> Message {
+        Message::Transaction(MessageTransaction {
+            transaction: Arc::new(MessageTransactionInfo {
+                signature: Default::default(),
+                is_vote,
+                transaction: Default::default(),
+                meta: Default::default(),
+                index: 0,
+                account_keys: Default::default(),
+            }),
+            slot: 0,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    fn entry() -> Message {
+        Message::Entry(Arc::new(MessageEntry {
+            slot: 0,
+            index: 0,
+            num_hashes: 0,
+            hash: Default::default(),
+            executed_transaction_count: 0,
+            starting_transaction_index: 0,
+            transaction_signatures: None,
+            created_at: Timestamp::default(),
+        }))
+    }
+
+    #[test]
+    fn test_action_shed() {
+        let config = config(ConfigGrpcIngressOverloadPolicy::Shed);
+        let get = |message: &Message, len| IngressAction::get(&config, message, len, 8);
+
+        assert_eq!(get(&account(), 3), IngressAction::Send);
+        assert_eq!(get(&account(), 4), IngressAction::Drop);
+        assert_eq!(get(&entry(), 5), IngressAction::Send);
+        assert_eq!(get(&entry(), 6), IngressAction::Drop);
+        // transactions, votes included, are never shed
+        for len in [0, 6, 8] {
+            assert_eq!(get(&transaction(true), len), IngressAction::Send);
+            assert_eq!(get(&transaction(false), len), IngressAction::Send);
+            assert_eq!(get(&slot(), len), IngressAction::Send);
+        }
+    }
+
+    #[test]
+    fn test_action_block() {
+        let config = config(ConfigGrpcIngressOverloadPolicy::Block);
+        let get = |message: &Message, len| IngressAction::get(&config, message, len, 8);
+
+        let timeout = IngressAction::SendTimeout(Duration::from_millis(10));
+        for len in [0, 8] {
+            assert_eq!(get(&account(), len), timeout);
+            assert_eq!(get(&transaction(true), len), timeout);
+            assert_eq!(get(&entry(), len), timeout);
+            assert_eq!(get(&slot(), len), IngressAction::Send);
+            let notice = Message::Notice(Arc::new(MessageNotice::messages_dropped(1, 0, 0)));
+            assert_eq!(get(&notice, len), IngressAction::Send);
+        }
+    }
+
+    fn dropped(notice: Option<Message>) -> Option<(u64, u64, u64)> {
+        match notice? {
+            Message::Notice(notice) => match &notice.notice.notice {
+                Some(NoticeOneof::MessagesDropped(dropped)) => {
+                    Some((dropped.accounts, dropped.transactions, dropped.entries))
+                }
+                notice => panic!("unexpected notice: {notice:?}"),
+            },
+            message => panic!("unexpected message: {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_channel_shed() {
+        let runtime = Runtime::new().unwrap();
+        let (tx, mut rx) = {
+            let _guard = runtime.enter();
+            channel(config(ConfigGrpcIngressOverloadPolicy::Shed))
+        };
+
+        for _ in 0..6 {
+            tx.send(account()).unwrap();
+        }
+        for _ in 0..4 {
+            tx.send(entry()).unwrap();
+        }
+        for _ in 0..2 {
+            tx.send(transaction(true)).unwrap();
+        }
+        // 4 accounts, 2 entries and 2 transactions are queued
+        assert_eq!(dropped(rx.take_dropped_notice()), Some((2, 0, 2)));
+        assert_eq!(dropped(rx.take_dropped_notice()), None);
+
+        // slot waits for free space on full queue
+        let sender = std::thread::spawn(move || tx.send(slot()));
+        let mut received = 0;
+        runtime.block_on(async {
+            while let Some(message) = rx.recv().await {
+                received += 1;
+                if matches!(message, Message::Slot(_)) {
+                    break;
+                }
+            }
+        });
+        assert_eq!(received, 9);
+        sender.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_channel_block() {
+        let runtime = Runtime::new().unwrap();
+        let (tx, rx) = {
+            let _guard = runtime.enter();
+            channel(config(ConfigGrpcIngressOverloadPolicy::Block))
+        };
+
+        for _ in 0..8 {
+            tx.send(transaction(false)).unwrap();
+        }
+        assert_eq!(dropped(rx.take_dropped_notice()), None);
+        tx.send(account()).unwrap();
+        tx.send(transaction(true)).unwrap();
+        tx.send(entry()).unwrap();
+        assert_eq!(dropped(rx.take_dropped_notice()), Some((1, 1, 1)));
+
+        drop(rx);
+        assert!(tx.send(slot()).is_err());
+    }
+}
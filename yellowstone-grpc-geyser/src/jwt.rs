@@ -0,0 +1,318 @@
+use {
+    crate::config::ConfigGrpcJwt,
+    anyhow::Context,
+    jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation},
+    serde::Deserialize,
+    std::{collections::HashSet, fmt, fs, sync::Arc},
+    tonic::{Request, Status},
+    yellowstone_grpc_proto::plugin::filter::limits::{FilterLimits, FilterLimitsKind},
+};
+
+/// Claims of the signed token, `exp` is required
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenClaims {
+    /// Subject, used as client identity if no identity from TLS certificate
+    #[serde(default)]
+    pub sub: Option<String>,
+    pub exp: u64,
+    /// Allowed RPC methods (`Subscribe`, `GetSlot`, etc), all methods allowed if not set
+    #[serde(default)]
+    pub methods: Option<HashSet<String>>,
+    /// Allowed subscribe kinds, all kinds allowed if not set
+    #[serde(default)]
+    pub kinds: Option<HashSet<FilterLimitsKind>>,
+    /// Restriction of configured filter limits
+    #[serde(default)]
+    pub filter_limits: Option<FilterLimits>,
+}
+
+impl TokenClaims {
+    /// Check that RPC method is allowed by token from request extensions (if any)
+    pub fn check_method<T>(request: &Request<T>, method: &str) -> Result<(), Status> {
+        match request
+            .extensions()
+            .get::<Arc<Self>>()
+            .and_then(|claims| claims.methods.as_ref())
+        {
+            Some(methods) if !methods.contains(method) => Err(Status::permission_denied(format!(
+                "method {method} is not allowed"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Filter limits restricted by the token, token can not extend configured limits
+    pub fn get_filter_limits(&self, limits: &Arc<FilterLimits>) -> Arc<FilterLimits> {
+        if self.filter_limits.is_none() && self.kinds.is_none() {
+            return Arc::clone(limits);
+        }
+
+        let mut limits = match &self.filter_limits {
+            Some(filter_limits) => limits.restrict(filter_limits),
+            None => limits.as_ref().clone(),
+        };
+        if self.kinds.is_some() {
+            limits = limits.restrict(&FilterLimits {
+                kinds: self.kinds.clone(),
+                ..FilterLimits::default()
+            });
+        }
+        Arc::new(limits)
+    }
+}
+
+pub struct TokenVerifier {
+    hmac_key: Option<DecodingKey>,
+    ed25519_key: Option<DecodingKey>,
+    audience: Option<String>,
+    revoked_subjects: HashSet<String>,
+}
+
+impl fmt::Debug for TokenVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenVerifier")
+            .field("audience", &self.audience)
+            .field("revoked_subjects", &self.revoked_subjects)
+            .finish()
+    }
+}
+
+impl TokenVerifier {
+    pub fn new(config: ConfigGrpcJwt) -> anyhow::Result<Self> {
+        let hmac_key = config
+            .hmac_secret
+            .map(|secret| DecodingKey::from_secret(secret.as_bytes()));
+        let ed25519_key = config
+            .ed25519_public_key_path
+            .map(|path| {
+                let pem = fs::read(path).context("failed to load Ed25519 public key file")?;
+                DecodingKey::from_ed_pem(&pem).context("failed to parse Ed25519 public key")
+            })
+            .transpose()?;
+        anyhow::ensure!(
+            hmac_key.is_some() || ed25519_key.is_some(),
+            "at least one of `hmac_secret` or `ed25519_public_key_path` is required"
+        );
+
+        Ok(Self {
+            hmac_key,
+            ed25519_key,
+            audience: config.audience,
+            revoked_subjects: config.revoked_subjects,
+        })
+    }
+
+    pub fn verify(&self, token: &str) -> Result<TokenClaims, Status> {
+        let header = decode_header(token)
+            .map_err(|error| Status::unauthenticated(format!("invalid token: {error}")))?;
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => self.hmac_key.as_ref(),
+            Algorithm::EdDSA => self.ed25519_key.as_ref(),
+            _ => None,
+        }
+        .ok_or_else(|| Status::unauthenticated("token algorithm is not allowed"))?;
+
+        let mut validation = Validation::new(header.alg);
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                validation.set_required_spec_claims(&["exp", "aud"]);
+            }
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<TokenClaims>(token, key, &validation)
+            .map_err(|error| Status::unauthenticated(format!("invalid token: {error}")))?
+            .claims;
+        if let Some(sub) = &claims.sub {
+            if self.revoked_subjects.contains(sub) {
+                return Err(Status::unauthenticated("token is revoked"));
+            }
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{TokenClaims, TokenVerifier},
+        crate::config::ConfigGrpcJwt,
+        jsonwebtoken::{encode, Algorithm, EncodingKey, Header},
+        serde_json::{json, Value},
+        std::{
+            collections::HashSet,
+            sync::Arc,
+            time::{SystemTime, UNIX_EPOCH},
+        },
+        tonic::{Code, Request},
+        yellowstone_grpc_proto::plugin::filter::limits::{FilterLimits, FilterLimitsKind},
+    };
+
+    const HMAC_SECRET: &str = "secret";
+    const ED25519_KEY: &[u8] = include_bytes!("../tests/fixtures/jwt/ed25519.pem");
+
+    fn create_verifier(audience: Option<&str>) -> TokenVerifier {
+        TokenVerifier::new(ConfigGrpcJwt {
+            hmac_secret: Some(HMAC_SECRET.to_owned()),
+            ed25519_public_key_path: Some(
+                concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/tests/fixtures/jwt/ed25519-public.pem"
+                )
+                .to_owned(),
+            ),
+            audience: audience.map(ToOwned::to_owned),
+            revoked_subjects: HashSet::from(["revoked".to_owned()]),
+        })
+        .unwrap()
+    }
+
+    fn create_token(alg: Algorithm, mut claims: Value) -> String {
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 60;
+        claims["exp"] = json!(exp);
+        let key = match alg {
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(ED25519_KEY).unwrap(),
+            _ => EncodingKey::from_secret(HMAC_SECRET.as_bytes()),
+        };
+        encode(&Header::new(alg), &claims, &key).unwrap()
+    }
+
+    fn verify_code(verifier: &TokenVerifier, token: &str) -> Option<Code> {
+        verifier.verify(token).err().map(|status| status.code())
+    }
+
+    #[test]
+    fn test_verify_algorithms() {
+        let verifier = create_verifier(None);
+        for alg in [
+            Algorithm::HS256,
+            Algorithm::HS384,
+            Algorithm::HS512,
+            Algorithm::EdDSA,
+        ] {
+            let token = create_token(alg, json!({"sub": "client-1"}));
+            let claims = verifier.verify(&token).unwrap();
+            assert_eq!(claims.sub.as_deref(), Some("client-1"));
+        }
+
+        // signed by other key
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &json!({"exp": u64::MAX}),
+            &EncodingKey::from_secret(b"other"),
+        )
+        .unwrap();
+        assert_eq!(verify_code(&verifier, &token), Some(Code::Unauthenticated));
+
+        // expired
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &json!({"exp": 1}),
+            &EncodingKey::from_secret(HMAC_SECRET.as_bytes()),
+        )
+        .unwrap();
+        assert_eq!(verify_code(&verifier, &token), Some(Code::Unauthenticated));
+
+        // algorithm without configured key
+        let verifier = TokenVerifier::new(ConfigGrpcJwt {
+            hmac_secret: Some(HMAC_SECRET.to_owned()),
+            ed25519_public_key_path: None,
+            audience: None,
+            revoked_subjects: HashSet::new(),
+        })
+        .unwrap();
+        let token = create_token(Algorithm::EdDSA, json!({}));
+        assert_eq!(verify_code(&verifier, &token), Some(Code::Unauthenticated));
+    }
+
+    #[test]
+    fn test_verify_revoked_subject() {
+        let verifier = create_verifier(None);
+        let token = create_token(Algorithm::HS256, json!({"sub": "revoked"}));
+        assert_eq!(verify_code(&verifier, &token), Some(Code::Unauthenticated));
+    }
+
+    #[test]
+    fn test_verify_audience() {
+        let token_aud = create_token(Algorithm::HS256, json!({"aud": "grpc"}));
+        let token_other_aud = create_token(Algorithm::HS256, json!({"aud": "other"}));
+        let token = create_token(Algorithm::HS256, json!({}));
+
+        // `aud` is not checked without configured audience
+        let verifier = create_verifier(None);
+        for token in [&token_aud, &token_other_aud, &token] {
+            assert_eq!(verify_code(&verifier, token), None);
+        }
+
+        let verifier = create_verifier(Some("grpc"));
+        assert_eq!(verify_code(&verifier, &token_aud), None);
+        for token in [&token_other_aud, &token] {
+            assert_eq!(verify_code(&verifier, token), Some(Code::Unauthenticated));
+        }
+    }
+
+    #[test]
+    fn test_check_method() {
+        let verifier = create_verifier(None);
+        let token = create_token(Algorithm::HS256, json!({"methods": ["Subscribe"]}));
+        let claims = Arc::new(verifier.verify(&token).unwrap());
+
+        let mut request = Request::new(());
+        assert!(TokenClaims::check_method(&request, "GetSlot").is_ok());
+        request.extensions_mut().insert(claims);
+        assert!(TokenClaims::check_method(&request, "Subscribe").is_ok());
+        assert_eq!(
+            TokenClaims::check_method(&request, "GetSlot")
+                .unwrap_err()
+                .code(),
+            Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_filter_limits() {
+        let verifier = create_verifier(None);
+        let mut limits = FilterLimits::default();
+        limits.accounts.max = 10;
+        limits.accounts.any = false;
+        limits.slots.max = 1;
+        limits.kinds = Some(HashSet::from([
+            FilterLimitsKind::Accounts,
+            FilterLimitsKind::Slots,
+        ]));
+        let limits = Arc::new(limits);
+
+        let token = create_token(Algorithm::HS256, json!({}));
+        let claims = verifier.verify(&token).unwrap();
+        assert!(Arc::ptr_eq(&claims.get_filter_limits(&limits), &limits));
+
+        // token limits only restrict configured limits
+        let token = create_token(
+            Algorithm::HS256,
+            json!({
+                "kinds": ["slots", "blocks"],
+                "filter_limits": {
+                    "accounts": {"max": 100, "any": true, "owner_max": 5},
+                    "slots": {"max": 10},
+                    "entries": {"max": 2}
+                }
+            }),
+        );
+        let claims = verifier.verify(&token).unwrap();
+        let restricted = claims.get_filter_limits(&limits);
+        assert_eq!(restricted.accounts.max, 10);
+        assert!(!restricted.accounts.any);
+        assert_eq!(restricted.accounts.owner_max, 5);
+        assert_eq!(restricted.slots.max, 1);
+        assert_eq!(restricted.entries.max, 2);
+        assert_eq!(
+            restricted.kinds,
+            Some(HashSet::from([FilterLimitsKind::Slots]))
+        );
+    }
+}
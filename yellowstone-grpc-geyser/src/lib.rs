@@ -1,8 +1,19 @@
+pub mod admin;
 pub mod config;
 pub mod grpc;
+pub mod health;
+pub mod ingress;
+pub mod jwt;
+pub mod logger;
 pub mod metrics;
+pub mod otel;
+pub mod peer;
+pub mod pipeline;
 pub mod plugin;
+pub mod tls;
+pub mod unix;
 pub mod version;
+pub mod watchdog;
 
 pub fn get_thread_name() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
@@ -0,0 +1,461 @@
+use {
+    crate::config::{ConfigLog, ConfigLogFormat},
+    anyhow::Context as _,
+    env_logger::{fmt::Formatter, Builder, Logger},
+    log::{
+        kv::{self, Key, Value, VisitSource},
+        LevelFilter, Log, Metadata, Record,
+    },
+    serde::Serialize,
+    std::{
+        collections::HashMap,
+        env, fmt,
+        io::{self, Write},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        },
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+    yellowstone_grpc_proto::plugin::filter::Filter,
+};
+
+lazy_static::lazy_static! {
+    static ref LOGGER: RwLock<Option<LoggerState>> = RwLock::new(None);
+    static ref ERRORS: Mutex<HashMap<&'static str, (Instant, u64)>> = Mutex::new(HashMap::new());
+}
+
+static ERRORS_INTERVAL_MS: AtomicU64 = AtomicU64::new(10_000);
+
+struct LoggerState {
+    logger: Logger,
+    format: ConfigLogFormat,
+    filter: String,
+    /// Filter from `RUST_LOG` or config, restored once temporary filter is expired
+    filter_default: String,
+    filter_expires_at: Option<SystemTime>,
+    /// Incremented on every change, expired filter is not restored over newer one
+    generation: u64,
+}
+
+/// Logger state is replaced as a whole, so it is still valid after panic of other thread
+fn logger_read() -> RwLockReadGuard<'static, Option<LoggerState>> {
+    LOGGER.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn logger_write() -> RwLockWriteGuard<'static, Option<LoggerState>> {
+    LOGGER.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl LoggerState {
+    fn set_filter(&mut self, filter: &str) {
+        self.logger = build_logger(filter, self.format);
+        self.filter = filter.to_owned();
+        self.generation += 1;
+        log::set_max_level(self.logger.filter());
+    }
+}
+
+/// Installed once, logger behind it can be replaced on plugin reload or filter change
+struct LoggerShim;
+
+impl Log for LoggerShim {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        logger_read()
+            .as_ref()
+            .is_some_and(|state| state.logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(state) = logger_read().as_ref() {
+            state.logger.log(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Configure logger with level as default filter if `RUST_LOG` is not set,
+/// text format is the same as with `solana_logger`
+pub fn setup(config: &ConfigLog) {
+    ERRORS_INTERVAL_MS.store(config.errors_interval.as_millis() as u64, Ordering::Relaxed);
+
+    let filter = env::var("RUST_LOG").unwrap_or_else(|_| config.level.clone());
+    let logger = build_logger(&filter, config.format);
+
+    log::set_max_level(logger.filter());
+    *logger_write() = Some(LoggerState {
+        logger,
+        format: config.format,
+        filter: filter.clone(),
+        filter_default: filter,
+        filter_expires_at: None,
+        generation: 0,
+    });
+    let _ = log::set_boxed_logger(Box::new(LoggerShim));
+}
+
+fn build_logger(filter: &str, format: ConfigLogFormat) -> Logger {
+    let mut builder = Builder::new();
+    builder.parse_filters(filter).format_timestamp_nanos();
+    if let Ok(style) = env::var("RUST_LOG_STYLE") {
+        builder.parse_write_style(&style);
+    }
+    if format == ConfigLogFormat::Json {
+        builder.format(format_json);
+    }
+    builder.build()
+}
+
+/// `env_logger` ignores invalid directives, so levels are checked before filter change
+fn validate_filter(filter: &str) -> anyhow::Result<()> {
+    let directives = filter.split('/').next().unwrap_or_default();
+    anyhow::ensure!(!directives.trim().is_empty(), "empty filter");
+    for directive in directives.split(',').map(str::trim) {
+        match directive.split_once('=') {
+            Some((module, level)) => {
+                anyhow::ensure!(!module.is_empty(), "empty module in directive: {directive}");
+                level
+                    .parse::<LevelFilter>()
+                    .with_context(|| format!("invalid level in directive: {directive}"))?;
+            }
+            None => anyhow::ensure!(!directive.is_empty(), "empty directive"),
+        }
+    }
+    Ok(())
+}
+
+/// Current log filter, returned by admin route
+#[derive(Debug, Serialize)]
+pub struct LogFilterStatus {
+    pub filter: String,
+    pub filter_default: String,
+    /// Unix time in ms when temporary filter is replaced with default
+    pub expires_at: Option<u64>,
+}
+
+pub fn get_filter() -> Option<LogFilterStatus> {
+    logger_read().as_ref().map(|state| LogFilterStatus {
+        filter: state.filter.clone(),
+        filter_default: state.filter_default.clone(),
+        expires_at: state.filter_expires_at.map(|expires_at| {
+            expires_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        }),
+    })
+}
+
+/// Change log filter, with `ttl` default filter is restored after timeout,
+/// without `ttl` filter becomes default. Should be called within tokio runtime.
+pub fn set_filter(filter: &str, ttl: Option<Duration>) -> anyhow::Result<()> {
+    validate_filter(filter)?;
+    let expires_at = ttl
+        .map(|ttl| {
+            SystemTime::now()
+                .checked_add(ttl)
+                .context("ttl is too large")
+        })
+        .transpose()?;
+
+    let mut state = logger_write();
+    let state = state.as_mut().context("logger is not initialized")?;
+    state.set_filter(filter);
+    match ttl {
+        Some(ttl) => {
+            state.filter_expires_at = expires_at;
+            let generation = state.generation;
+            tokio::spawn(async move {
+                tokio::time::sleep(ttl).await;
+                reset_filter_generation(Some(generation));
+            });
+        }
+        None => {
+            state.filter_default = filter.to_owned();
+            state.filter_expires_at = None;
+        }
+    }
+    Ok(())
+}
+
+/// Restore default filter before expiration of temporary one
+pub fn reset_filter() {
+    reset_filter_generation(None);
+}
+
+fn reset_filter_generation(generation: Option<u64>) {
+    let mut logger = logger_write();
+    let Some(state) = logger.as_mut() else {
+        return;
+    };
+    if state.filter_expires_at.is_none()
+        || generation.is_some_and(|generation| generation != state.generation)
+    {
+        return;
+    }
+
+    let filter = state.filter_default.clone();
+    state.set_filter(&filter);
+    state.filter_expires_at = None;
+    drop(logger);
+    log::info!("log filter restored: {filter}");
+}
+
+fn format_json(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let mut object = serde_json::Map::new();
+    object.insert("ts".to_owned(), buf.timestamp_nanos().to_string().into());
+    object.insert("level".to_owned(), record.level().as_str().into());
+    object.insert("target".to_owned(), record.target().into());
+    object.insert("message".to_owned(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut JsonVisitor(&mut object));
+    serde_json::to_writer(&mut *buf, &object)?;
+    writeln!(buf)
+}
+
+struct JsonVisitor<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonVisitor<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(value) = value.to_u64() {
+            value.into()
+        } else if let Some(value) = value.to_i64() {
+            value.into()
+        } else if let Some(value) = value.to_bool() {
+            value.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.as_str().to_owned(), value);
+        Ok(())
+    }
+}
+
+/// Number of suppressed errors of the kind since the previous call which returned `Some`,
+/// `None` if error should not be logged because the previous one was logged within interval
+pub fn check_repeated_error(kind: &'static str) -> Option<u64> {
+    let interval = Duration::from_millis(ERRORS_INTERVAL_MS.load(Ordering::Relaxed));
+    check_repeated(&mut ERRORS.lock().unwrap(), kind, interval, Instant::now())
+}
+
+fn check_repeated(
+    errors: &mut HashMap<&'static str, (Instant, u64)>,
+    kind: &'static str,
+    interval: Duration,
+    now: Instant,
+) -> Option<u64> {
+    match errors.get_mut(kind) {
+        Some((logged_at, suppressed)) if now.duration_since(*logged_at) < interval => {
+            *suppressed += 1;
+            None
+        }
+        Some((logged_at, suppressed)) => {
+            *logged_at = now;
+            Some(std::mem::take(suppressed))
+        }
+        None => {
+            errors.insert(kind, (now, 0));
+            Some(0)
+        }
+    }
+}
+
+/// Suffix for error message with number of suppressed errors, empty if nothing was suppressed
+#[derive(Debug, Clone, Copy)]
+pub struct Suppressed(pub u64);
+
+impl fmt::Display for Suppressed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 > 0 {
+            write!(f, " ({} similar errors suppressed)", self.0)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Number of filters by type and commitment level of stream
+#[derive(Debug, Clone, Copy)]
+pub struct FilterSummary<'a>(pub &'a Filter);
+
+impl fmt::Display for FilterSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, count) in self.0.get_metrics() {
+            if count > 0 && name != "all" {
+                write!(f, "{name}={count} ")?;
+            }
+        }
+        write!(f, "commitment={:?}", self.0.get_commitment_level())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{check_repeated, format_json, get_filter, set_filter, setup, validate_filter},
+        crate::config::ConfigLog,
+        env_logger::{Builder, Target},
+        log::{kv::Value, Level, Log, Record},
+        std::{
+            collections::HashMap,
+            io::{self, Write},
+            sync::{Arc, Mutex},
+            time::{Duration, Instant},
+        },
+    };
+
+    #[derive(Debug, Default, Clone)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_check_repeated() {
+        let mut errors = HashMap::new();
+        let interval = Duration::from_secs(10);
+        let start = Instant::now();
+        let mut check = |kind, secs| {
+            check_repeated(
+                &mut errors,
+                kind,
+                interval,
+                start + Duration::from_secs(secs),
+            )
+        };
+
+        assert_eq!(check("a", 0), Some(0));
+        assert_eq!(check("a", 1), None);
+        assert_eq!(check("a", 9), None);
+        // kinds are independent
+        assert_eq!(check("b", 9), Some(0));
+        // suppressed errors are reported once interval passed and counter is reset
+        assert_eq!(check("a", 10), Some(2));
+        assert_eq!(check("a", 19), None);
+        assert_eq!(check("a", 20), Some(1));
+        assert_eq!(check("a", 40), Some(0));
+        assert_eq!(check("b", 10), None);
+    }
+
+    #[test]
+    fn test_format_json() {
+        let buffer = Buffer::default();
+        let logger = Builder::new()
+            .parse_filters("info")
+            .format(format_json)
+            .target(Target::Pipe(Box::new(buffer.clone())))
+            .build();
+
+        let key_values = [
+            ("client_id", Value::from(7u64)),
+            ("offset", Value::from(-3i64)),
+            ("admin", Value::from(true)),
+            ("endpoint", Value::from("127.0.0.1:10000")),
+        ];
+        logger.log(
+            &Record::builder()
+                .args(format_args!("client #7: \"subscribed\""))
+                .level(Level::Warn)
+                .target("geyser")
+                .key_values(&key_values)
+                .build(),
+        );
+        logger.log(
+            &Record::builder()
+                .args(format_args!("no key values"))
+                .level(Level::Info)
+                .target("geyser")
+                .build(),
+        );
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+
+        let object = lines[0].as_object().unwrap();
+        assert!(object["ts"].is_string());
+        assert_eq!(object["level"], "WARN");
+        assert_eq!(object["target"], "geyser");
+        assert_eq!(object["message"], "client #7: \"subscribed\"");
+        assert_eq!(object["client_id"], 7);
+        assert_eq!(object["offset"], -3);
+        assert_eq!(object["admin"], true);
+        assert_eq!(object["endpoint"], "127.0.0.1:10000");
+
+        let object = lines[1].as_object().unwrap();
+        assert_eq!(object["message"], "no key values");
+        assert_eq!(
+            object.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["level", "message", "target", "ts"]
+        );
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        for filter in [
+            "info",
+            "warn,solana=error",
+            "yellowstone_grpc_geyser::grpc=trace, info",
+            "off",
+            "debug/subscribe",
+        ] {
+            assert!(validate_filter(filter).is_ok(), "{filter}");
+        }
+        for filter in [
+            "",
+            " ",
+            "/subscribe",
+            "info,",
+            "=debug",
+            "grpc=verbose",
+            "info,grpc=",
+        ] {
+            assert!(validate_filter(filter).is_err(), "{filter}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_filter_ttl() {
+        setup(&ConfigLog::default());
+        let filter_default = get_filter().unwrap().filter_default;
+
+        // filter is not changed on invalid filter or ttl
+        assert!(set_filter("grpc=verbose", None).is_err());
+        assert!(set_filter("debug", Some(Duration::MAX)).is_err());
+        assert_eq!(get_filter().unwrap().filter, filter_default);
+
+        set_filter("debug", Some(Duration::from_millis(100))).unwrap();
+        let status = get_filter().unwrap();
+        assert_eq!(status.filter, "debug");
+        assert!(status.expires_at.is_some());
+
+        // expired filter is not restored over newer one
+        set_filter("trace", Some(Duration::from_millis(300))).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let status = get_filter().unwrap();
+        assert_eq!(status.filter, "trace");
+        assert!(status.expires_at.is_some());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let status = get_filter().unwrap();
+        assert_eq!(status.filter, filter_default);
+        assert_eq!(status.expires_at, None);
+
+        // filter without ttl becomes default
+        set_filter("warn", None).unwrap();
+        let status = get_filter().unwrap();
+        assert_eq!(status.filter, "warn");
+        assert_eq!(status.filter_default, "warn");
+        assert_eq!(status.expires_at, None);
+    }
+}
@@ -1,30 +1,57 @@
 use {
-    crate::{config::ConfigPrometheus, version::VERSION as VERSION_INFO},
+    crate::{
+        config::{ConfigGrpcClientMetrics, ConfigGrpcClientMetricsLabel, ConfigPrometheus},
+        logger,
+        peer::PeerRejectReason,
+        version::VERSION as VERSION_INFO,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::SlotStatus as GeyserSlosStatus,
-    http_body_util::{combinators::BoxBody, BodyExt, Empty as BodyEmpty, Full as BodyFull},
+    http_body_util::{
+        combinators::BoxBody, BodyExt, Empty as BodyEmpty, Full as BodyFull, Limited as BodyLimited,
+    },
+    humantime_serde::re::humantime,
     hyper::{
         body::{Bytes, Incoming as BodyIncoming},
+        header::CONTENT_TYPE,
         service::service_fn,
-        Request, Response, StatusCode,
+        Method, Request, Response, StatusCode,
     },
     hyper_util::{
         rt::tokio::{TokioExecutor, TokioIo},
         server::conn::auto::Builder as ServerBuilder,
     },
     log::{error, info},
-    prometheus::{IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder},
+    prometheus::{
+        exponential_buckets, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec,
+        IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+    },
+    serde_json::json,
     solana_clock::Slot,
     std::{
         collections::{hash_map::Entry as HashMapEntry, HashMap},
         convert::Infallible,
-        sync::{Arc, Once},
+        sync::{Arc, Mutex, Once},
+        time::{Duration, SystemTime, UNIX_EPOCH},
     },
+    subtle::ConstantTimeEq,
     tokio::{
         net::TcpListener,
         sync::{mpsc, oneshot, Notify},
         task::JoinHandle,
     },
-    yellowstone_grpc_proto::plugin::{filter::Filter, message::SlotStatus},
+    tonic::Status,
+    yellowstone_grpc_proto::{
+        geyser::SubscribeRequest,
+        plugin::{
+            filter::{
+                encoded::FilteredUpdateEncodedStatus,
+                message::{FilteredUpdate, FilteredUpdateOneof},
+                Filter,
+            },
+            message::{CommitmentLevel, SlotStatus},
+        },
+        prost::Message as _,
+    },
 };
 
 lazy_static::lazy_static! {
@@ -58,6 +85,21 @@ lazy_static::lazy_static! {
         "connections_total", "Total number of connections to gRPC service"
     ).unwrap();
 
+    static ref CONNECTIONS_IDENTITY: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("connections_identity", "Number of connections by client identity from TLS certificate"),
+        &["identity"]
+    ).unwrap();
+
+    static ref TLS_RELOAD: IntCounterVec = IntCounterVec::new(
+        Opts::new("tls_reload_total", "Number of TLS certificates reloads by status"),
+        &["status"]
+    ).unwrap();
+
+    static ref PEER_REJECTED: IntCounterVec = IntCounterVec::new(
+        Opts::new("peer_rejected_total", "Number of rejected connections and subscriptions by reason"),
+        &["reason"]
+    ).unwrap();
+
     static ref SUBSCRIPTIONS_TOTAL: IntGaugeVec = IntGaugeVec::new(
         Opts::new("subscriptions_total", "Total number of subscriptions to gRPC service"),
         &["endpoint", "subscription"]
@@ -67,13 +109,108 @@ lazy_static::lazy_static! {
         Opts::new("missed_status_message_total", "Number of missed messages by commitment"),
         &["status"]
     ).unwrap();
+
+    static ref SLOT_STATUS_DURATION: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("slot_status_duration_seconds", "Time between receiving slot statuses")
+            .buckets(exponential_buckets(0.025, 2.0, 12).unwrap()),
+        &["from", "to"]
+    ).unwrap();
+
+    static ref SECONDS_SINCE_LAST_SLOT: GaugeVec = GaugeVec::new(
+        Opts::new("seconds_since_last_slot", "Time since last slot update by status in geyser loop"),
+        &["status"]
+    ).unwrap();
+
+    static ref CLIENT_MESSAGES_SENT: IntCounterVec = IntCounterVec::new(
+        Opts::new("client_messages_sent_total", "Number of messages sent to client by update type"),
+        &["client", "type"]
+    ).unwrap();
+
+    static ref CLIENT_BYTES_SENT: IntCounterVec = IntCounterVec::new(
+        Opts::new("client_bytes_sent_total", "Encoded size of messages sent to client by update type"),
+        &["client", "type"]
+    ).unwrap();
+
+    static ref CLIENT_QUEUE_SIZE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("client_queue_size", "Number of messages in client stream queue"),
+        &["client"]
+    ).unwrap();
+
+    static ref CLIENT_LAG_SLOTS: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("client_lag_slots", "Difference between latest plugin slot and slot of messages sent to client"),
+        &["client"]
+    ).unwrap();
+
+    static ref INGRESS_DROPPED: IntCounterVec = IntCounterVec::new(
+        Opts::new("ingress_dropped_total", "Number of messages dropped on plugin queue overload by type"),
+        &["type"]
+    ).unwrap();
+
+    static ref GEYSER_LOOP_STAGE_DURATION: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("geyser_loop_stage_duration_seconds", "Time spent by messages in geyser loop stages")
+            .buckets(exponential_buckets(0.000_001, 4.0, 12).unwrap()),
+        &["stage"]
+    ).unwrap();
+
+    static ref UPDATES_LATENCY: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("updates_latency_seconds", "Latency of updates: plugin to broadcast (geyser_loop), broadcast to stream send (client_loop)")
+            .buckets(exponential_buckets(0.000_001, 4.0, 12).unwrap()),
+        &["stage"]
+    ).unwrap();
+
+    static ref ENCODED_CACHE: IntCounterVec = IntCounterVec::new(
+        Opts::new("encoded_cache_total", "Number of updates encoded with shared cache by status"),
+        &["status"]
+    ).unwrap();
+
+    static ref CLIENT_LABELS: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
 }
 
+const CLIENT_LABEL_OTHER: &str = "other";
+
+const CLIENT_UPDATE_TYPES: [&str; 11] = [
+    "account",
+    "slot",
+    "transaction",
+    "transaction_status",
+    "block",
+    "ping",
+    "pong",
+    "block_meta",
+    "entry",
+    "notice",
+    "block_failure",
+];
+
+pub type DebugClientTx = mpsc::UnboundedSender<Option<(Option<u64>, Filter)>>;
+
 #[derive(Debug)]
 pub enum DebugClientMessage {
-    UpdateFilter { id: usize, filter: Box<Filter> },
-    UpdateSlot { id: usize, slot: Slot },
-    Removed { id: usize },
+    New {
+        id: usize,
+        endpoint: String,
+        client_tx: DebugClientTx,
+        stream_tx: mpsc::Sender<Result<FilteredUpdate, Status>>,
+    },
+    UpdateFilter {
+        id: usize,
+        filter: Box<Filter>,
+    },
+    UpdateRequest {
+        id: usize,
+        request: Box<SubscribeRequest>,
+    },
+    UpdateSlot {
+        id: usize,
+        slot: Slot,
+    },
+    UpdateIdentity {
+        id: usize,
+        identity: String,
+    },
+    Removed {
+        id: usize,
+    },
 }
 
 impl DebugClientMessage {
@@ -86,13 +223,92 @@ impl DebugClientMessage {
 
 #[derive(Debug)]
 struct DebugClientStatus {
+    endpoint: String,
+    connected_at: SystemTime,
     filter: Box<Filter>,
+    request: Option<Box<SubscribeRequest>>,
     processed_slot: Slot,
+    identity: Option<String>,
+    client_tx: DebugClientTx,
+    stream_tx: mpsc::Sender<Result<FilteredUpdate, Status>>,
+}
+
+impl DebugClientStatus {
+    fn to_info(&self, id: usize) -> DebugClientInfo {
+        let plugin_slot = SLOT_STATUS_PLUGIN
+            .with_label_values(&[SlotStatus::Processed.as_str()])
+            .get() as u64;
+        DebugClientInfo {
+            id,
+            endpoint: self.endpoint.clone(),
+            identity: self.identity.clone(),
+            connected_at: self.connected_at,
+            request: self.request.clone(),
+            commitment: self.filter.get_commitment_level(),
+            processed_slot: self.processed_slot,
+            queue_size: self.stream_tx.max_capacity() - self.stream_tx.capacity(),
+            lag_slots: plugin_slot.saturating_sub(self.processed_slot),
+        }
+    }
+
+    fn to_json(&self, id: usize) -> serde_json::Value {
+        let info = self.to_info(id);
+        json!({
+            "id": info.id,
+            "endpoint": info.endpoint,
+            "identity": info.identity,
+            "connected_at": info
+                .connected_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default(),
+            "filter": info.request,
+            "commitment": match info.commitment {
+                CommitmentLevel::Processed => "processed",
+                CommitmentLevel::Confirmed => "confirmed",
+                CommitmentLevel::Finalized => "finalized",
+            },
+            "processed_slot": info.processed_slot,
+            "queue_size": info.queue_size,
+            "lag_slots": info.lag_slots,
+        })
+    }
+}
+
+/// Status of connected client, returned to admin service
+#[derive(Debug)]
+pub struct DebugClientInfo {
+    pub id: usize,
+    pub endpoint: String,
+    pub identity: Option<String>,
+    pub connected_at: SystemTime,
+    pub request: Option<Box<SubscribeRequest>>,
+    pub commitment: CommitmentLevel,
+    pub processed_slot: Slot,
+    pub queue_size: usize,
+    pub lag_slots: u64,
 }
 
 #[derive(Debug)]
-struct DebugClientStatuses {
-    requests_tx: mpsc::UnboundedSender<oneshot::Sender<String>>,
+enum DebugClientRequest {
+    Statuses {
+        json: bool,
+        tx: oneshot::Sender<String>,
+    },
+    Clients {
+        tx: oneshot::Sender<Vec<DebugClientInfo>>,
+    },
+    Disconnect {
+        id: usize,
+        tx: oneshot::Sender<bool>,
+    },
+}
+
+/// Statuses of connected clients, shared by `/debug_clients` routes and admin service
+#[derive(Debug)]
+pub struct DebugClientStatuses {
+    clients_tx: mpsc::UnboundedSender<DebugClientMessage>,
+    requests_tx: mpsc::UnboundedSender<DebugClientRequest>,
     jh: JoinHandle<()>,
 }
 
@@ -103,31 +319,49 @@ impl Drop for DebugClientStatuses {
 }
 
 impl DebugClientStatuses {
-    fn new(clients_rx: mpsc::UnboundedReceiver<DebugClientMessage>) -> Arc<Self> {
+    pub fn new() -> Arc<Self> {
+        let (clients_tx, clients_rx) = mpsc::unbounded_channel();
         let (requests_tx, requests_rx) = mpsc::unbounded_channel();
         let jh = tokio::spawn(Self::run(clients_rx, requests_rx));
-        Arc::new(Self { requests_tx, jh })
+        Arc::new(Self {
+            clients_tx,
+            requests_tx,
+            jh,
+        })
+    }
+
+    pub fn clients_tx(&self) -> mpsc::UnboundedSender<DebugClientMessage> {
+        self.clients_tx.clone()
     }
 
     async fn run(
         mut clients_rx: mpsc::UnboundedReceiver<DebugClientMessage>,
-        mut requests_rx: mpsc::UnboundedReceiver<oneshot::Sender<String>>,
+        mut requests_rx: mpsc::UnboundedReceiver<DebugClientRequest>,
     ) {
         let mut clients = HashMap::<usize, DebugClientStatus>::new();
         loop {
             tokio::select! {
                 Some(message) = clients_rx.recv() => match message {
+                    DebugClientMessage::New { id, endpoint, client_tx, stream_tx } => {
+                        clients.insert(id, DebugClientStatus {
+                            endpoint,
+                            connected_at: SystemTime::now(),
+                            filter: Box::default(),
+                            request: None,
+                            processed_slot: 0,
+                            identity: None,
+                            client_tx,
+                            stream_tx,
+                        });
+                    }
                     DebugClientMessage::UpdateFilter { id, filter } => {
-                        match clients.entry(id) {
-                            HashMapEntry::Occupied(mut entry) => {
-                                entry.get_mut().filter = filter;
-                            }
-                            HashMapEntry::Vacant(entry) => {
-                                entry.insert(DebugClientStatus {
-                                    filter,
-                                    processed_slot: 0,
-                                });
-                            }
+                        if let Some(status) = clients.get_mut(&id) {
+                            status.filter = filter;
+                        }
+                    }
+                    DebugClientMessage::UpdateRequest { id, request } => {
+                        if let Some(status) = clients.get_mut(&id) {
+                            status.request = Some(request);
                         }
                     }
                     DebugClientMessage::UpdateSlot { id, slot } => {
@@ -135,36 +369,90 @@ impl DebugClientStatuses {
                             status.processed_slot = slot;
                         }
                     }
+                    DebugClientMessage::UpdateIdentity { id, identity } => {
+                        if let Some(status) = clients.get_mut(&id) {
+                            status.identity = Some(identity);
+                        }
+                    }
                     DebugClientMessage::Removed { id } => {
                         clients.remove(&id);
                     }
                 },
-                Some(tx) = requests_rx.recv() => {
-                    let mut statuses: Vec<(usize, String)> = clients.iter().map(|(id, status)| {
-                        (*id, format!("client#{id:06}, {}, {:?}", status.processed_slot, status.filter))
-                    }).collect();
-                    statuses.sort();
-
-                    let mut status = statuses.into_iter().fold(String::new(), |mut acc: String, (_id, status)| {
-                        if !acc.is_empty() {
-                            acc += "\n";
-                        }
-                        acc + &status
-                    });
-                    if !status.is_empty() {
-                        status += "\n";
+                Some(request) = requests_rx.recv() => match request {
+                    DebugClientRequest::Statuses { json: true, tx } => {
+                        let mut statuses = clients.iter().collect::<Vec<_>>();
+                        statuses.sort_by_key(|(id, _status)| **id);
+                        let statuses = statuses
+                            .into_iter()
+                            .map(|(id, status)| status.to_json(*id))
+                            .collect::<Vec<_>>();
+                        let _ = tx.send(serde_json::Value::Array(statuses).to_string());
                     }
+                    DebugClientRequest::Statuses { json: false, tx } => {
+                        let mut statuses: Vec<(usize, String)> = clients.iter().map(|(id, status)| {
+                            let identity = status.identity.as_deref().unwrap_or("-");
+                            (*id, format!("client#{id:06}, {identity}, {}, {:?}", status.processed_slot, status.filter))
+                        }).collect();
+                        statuses.sort();
 
-                    let _ = tx.send(status);
+                        let mut status = statuses.into_iter().fold(String::new(), |mut acc: String, (_id, status)| {
+                            if !acc.is_empty() {
+                                acc += "\n";
+                            }
+                            acc + &status
+                        });
+                        if !status.is_empty() {
+                            status += "\n";
+                        }
+
+                        let _ = tx.send(status);
+                    }
+                    DebugClientRequest::Clients { tx } => {
+                        let mut clients = clients
+                            .iter()
+                            .map(|(id, status)| status.to_info(*id))
+                            .collect::<Vec<_>>();
+                        clients.sort_by_key(|info| info.id);
+                        let _ = tx.send(clients);
+                    }
+                    DebugClientRequest::Disconnect { id, tx } => {
+                        let found = if let Some(status) = clients.get(&id) {
+                            info!("client #{id}: disconnect requested");
+                            let _ = status.stream_tx.try_send(Err(Status::aborted("disconnected by admin")));
+                            let _ = status.client_tx.send(None);
+                            true
+                        } else {
+                            false
+                        };
+                        let _ = tx.send(found);
+                    }
                 },
             }
         }
     }
 
-    async fn get_statuses(&self) -> anyhow::Result<String> {
+    async fn get_statuses(&self, json: bool) -> anyhow::Result<String> {
+        let (tx, rx) = oneshot::channel();
+        self.requests_tx
+            .send(DebugClientRequest::Statuses { json, tx })
+            .map_err(|_error| anyhow::anyhow!("failed to send request"))?;
+        rx.await
+            .map_err(|_error| anyhow::anyhow!("failed to wait response"))
+    }
+
+    pub async fn get_clients(&self) -> anyhow::Result<Vec<DebugClientInfo>> {
+        let (tx, rx) = oneshot::channel();
+        self.requests_tx
+            .send(DebugClientRequest::Clients { tx })
+            .map_err(|_error| anyhow::anyhow!("failed to send request"))?;
+        rx.await
+            .map_err(|_error| anyhow::anyhow!("failed to wait response"))
+    }
+
+    pub async fn disconnect(&self, id: usize) -> anyhow::Result<bool> {
         let (tx, rx) = oneshot::channel();
         self.requests_tx
-            .send(tx)
+            .send(DebugClientRequest::Disconnect { id, tx })
             .map_err(|_error| anyhow::anyhow!("failed to send request"))?;
         rx.await
             .map_err(|_error| anyhow::anyhow!("failed to wait response"))
@@ -180,7 +468,7 @@ pub struct PrometheusService {
 impl PrometheusService {
     pub async fn new(
         config: Option<ConfigPrometheus>,
-        debug_clients_rx: Option<mpsc::UnboundedReceiver<DebugClientMessage>>,
+        debug_clients_statuses: Option<Arc<DebugClientStatuses>>,
     ) -> std::io::Result<Self> {
         static REGISTER: Once = Once::new();
         REGISTER.call_once(|| {
@@ -197,8 +485,21 @@ impl PrometheusService {
             register!(INVALID_FULL_BLOCKS);
             register!(MESSAGE_QUEUE_SIZE);
             register!(CONNECTIONS_TOTAL);
+            register!(CONNECTIONS_IDENTITY);
+            register!(TLS_RELOAD);
+            register!(PEER_REJECTED);
             register!(SUBSCRIPTIONS_TOTAL);
             register!(MISSED_STATUS_MESSAGE);
+            register!(SLOT_STATUS_DURATION);
+            register!(SECONDS_SINCE_LAST_SLOT);
+            register!(CLIENT_MESSAGES_SENT);
+            register!(CLIENT_BYTES_SENT);
+            register!(CLIENT_QUEUE_SIZE);
+            register!(CLIENT_LAG_SLOTS);
+            register!(INGRESS_DROPPED);
+            register!(GEYSER_LOOP_STAGE_DURATION);
+            register!(UPDATES_LATENCY);
+            register!(ENCODED_CACHE);
 
             VERSION
                 .with_label_values(&[
@@ -214,12 +515,13 @@ impl PrometheusService {
         });
 
         let shutdown = Arc::new(Notify::new());
-        let mut debug_clients_statuses = None;
-        if let Some(ConfigPrometheus { address }) = config {
-            if let Some(debug_clients_rx) = debug_clients_rx {
-                debug_clients_statuses = Some(DebugClientStatuses::new(debug_clients_rx));
-            }
+        if let Some(ConfigPrometheus {
+            address,
+            admin_token,
+        }) = config
+        {
             let debug_clients_statuses2 = debug_clients_statuses.clone();
+            let admin_token = admin_token.map(Arc::new);
 
             let shutdown = Arc::clone(&shutdown);
             let listener = TcpListener::bind(&address).await?;
@@ -239,12 +541,14 @@ impl PrometheusService {
                         }
                     };
                     let debug_clients_statuses = debug_clients_statuses2.clone();
+                    let admin_token = admin_token.clone();
                     tokio::spawn(async move {
                         if let Err(error) = ServerBuilder::new(TokioExecutor::new())
                             .serve_connection(
                                 TokioIo::new(stream),
                                 service_fn(move |req: Request<BodyIncoming>| {
                                     let debug_clients_statuses = debug_clients_statuses.clone();
+                                    let admin_token = admin_token.clone();
                                     async move {
                                         match req.uri().path() {
                                             "/metrics" => metrics_handler(),
@@ -252,24 +556,37 @@ impl PrometheusService {
                                                 if let Some(debug_clients_statuses) =
                                                     &debug_clients_statuses
                                                 {
-                                                    let (status, body) =
-                                                        match debug_clients_statuses
-                                                            .get_statuses()
-                                                            .await
-                                                        {
-                                                            Ok(body) => (StatusCode::OK, body),
-                                                            Err(error) => (
-                                                                StatusCode::INTERNAL_SERVER_ERROR,
-                                                                error.to_string(),
-                                                            ),
-                                                        };
-                                                    Response::builder().status(status).body(
-                                                        BodyFull::new(Bytes::from(body)).boxed(),
+                                                    debug_clients_handler(
+                                                        &req,
+                                                        debug_clients_statuses,
+                                                        admin_token.as_deref().map(String::as_str),
                                                     )
+                                                    .await
                                                 } else {
                                                     not_found_handler()
                                                 }
                                             }
+                                            "/debug_clients/disconnect" => {
+                                                if let Some(debug_clients_statuses) =
+                                                    &debug_clients_statuses
+                                                {
+                                                    debug_clients_disconnect_handler(
+                                                        &req,
+                                                        debug_clients_statuses,
+                                                        admin_token.as_deref().map(String::as_str),
+                                                    )
+                                                    .await
+                                                } else {
+                                                    not_found_handler()
+                                                }
+                                            }
+                                            "/admin/log" => {
+                                                admin_log_handler(
+                                                    req,
+                                                    admin_token.as_deref().map(String::as_str),
+                                                )
+                                                .await
+                                            }
                                             _ => not_found_handler(),
                                         }
                                     }
@@ -308,6 +625,143 @@ fn metrics_handler() -> http::Result<Response<BoxBody<Bytes, Infallible>>> {
         .body(BodyFull::new(Bytes::from(metrics)).boxed())
 }
 
+fn get_query_param<'a, B>(req: &'a Request<B>, name: &str) -> Option<&'a str> {
+    req.uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Admin routes are not available without `admin_token`, token is compared in constant time
+fn check_admin_token<B>(req: &Request<B>, admin_token: Option<&str>) -> Result<(), StatusCode> {
+    let Some(admin_token) = admin_token else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let token = req
+        .headers()
+        .get("x-token")
+        .map(|value| value.as_bytes())
+        .unwrap_or_default();
+    if bool::from(token.ct_eq(admin_token.as_bytes())) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+async fn debug_clients_handler<B>(
+    req: &Request<B>,
+    debug_clients_statuses: &DebugClientStatuses,
+    admin_token: Option<&str>,
+) -> http::Result<Response<BoxBody<Bytes, Infallible>>> {
+    let json = get_query_param(req, "format") == Some("json");
+    // JSON status includes filters of clients
+    if json {
+        if let Err(status) = check_admin_token(req, admin_token) {
+            return Response::builder()
+                .status(status)
+                .body(BodyEmpty::new().boxed());
+        }
+    }
+    let (status, body) = match debug_clients_statuses.get_statuses(json).await {
+        Ok(body) => (StatusCode::OK, body),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()),
+    };
+    let mut builder = Response::builder().status(status);
+    if json && status == StatusCode::OK {
+        builder = builder.header(CONTENT_TYPE, "application/json");
+    }
+    builder.body(BodyFull::new(Bytes::from(body)).boxed())
+}
+
+async fn debug_clients_disconnect_handler<B>(
+    req: &Request<B>,
+    debug_clients_statuses: &DebugClientStatuses,
+    admin_token: Option<&str>,
+) -> http::Result<Response<BoxBody<Bytes, Infallible>>> {
+    if let Err(status) = check_admin_token(req, admin_token) {
+        return Response::builder()
+            .status(status)
+            .body(BodyEmpty::new().boxed());
+    }
+    if req.method() != Method::POST {
+        return Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(BodyEmpty::new().boxed());
+    }
+    let Some(id) = get_query_param(req, "id").and_then(|id| id.parse().ok()) else {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(BodyFull::new(Bytes::from("invalid client id\n")).boxed());
+    };
+    let (status, body) = match debug_clients_statuses.disconnect(id).await {
+        Ok(true) => (StatusCode::OK, format!("client #{id} disconnected\n")),
+        Ok(false) => (StatusCode::NOT_FOUND, format!("client #{id} not found\n")),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()),
+    };
+    Response::builder()
+        .status(status)
+        .body(BodyFull::new(Bytes::from(body)).boxed())
+}
+
+async fn admin_log_handler(
+    req: Request<BodyIncoming>,
+    admin_token: Option<&str>,
+) -> http::Result<Response<BoxBody<Bytes, Infallible>>> {
+    if let Err(status) = check_admin_token(&req, admin_token) {
+        return Response::builder()
+            .status(status)
+            .body(BodyEmpty::new().boxed());
+    }
+
+    let error = match *req.method() {
+        Method::GET => None,
+        Method::POST => {
+            let ttl = get_query_param(&req, "ttl")
+                .map(humantime::parse_duration)
+                .transpose();
+            match ttl {
+                Ok(ttl) => match BodyLimited::new(req.into_body(), 4096).collect().await {
+                    Ok(body) => {
+                        let body = body.to_bytes();
+                        let filter = String::from_utf8_lossy(&body);
+                        let filter = filter.trim();
+                        match logger::set_filter(filter, ttl) {
+                            Ok(()) => {
+                                info!("log filter changed: {filter}, ttl: {ttl:?}");
+                                None
+                            }
+                            Err(error) => Some(format!("failed to change filter: {error}\n")),
+                        }
+                    }
+                    Err(error) => Some(format!("failed to read body: {error}\n")),
+                },
+                Err(error) => Some(format!("invalid ttl: {error}\n")),
+            }
+        }
+        Method::DELETE => {
+            logger::reset_filter();
+            None
+        }
+        _ => {
+            return Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(BodyEmpty::new().boxed());
+        }
+    };
+    if let Some(error) = error {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(BodyFull::new(Bytes::from(error)).boxed());
+    }
+
+    let body = serde_json::to_string(&logger::get_filter()).unwrap_or_default();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(BodyFull::new(Bytes::from(body)).boxed())
+}
+
 fn not_found_handler() -> http::Result<Response<BoxBody<Bytes, Infallible>>> {
     Response::builder()
         .status(StatusCode::NOT_FOUND)
@@ -326,6 +780,12 @@ pub fn update_slot_plugin_status(status: SlotStatus, slot: u64) {
         .set(slot as i64);
 }
 
+pub fn set_seconds_since_last_slot(status: SlotStatus, elapsed: Duration) {
+    SECONDS_SINCE_LAST_SLOT
+        .with_label_values(&[status.as_str()])
+        .set(elapsed.as_secs_f64());
+}
+
 pub fn update_invalid_blocks(reason: impl AsRef<str>) {
     INVALID_FULL_BLOCKS
         .with_label_values(&[reason.as_ref()])
@@ -341,6 +801,26 @@ pub fn message_queue_size_dec() {
     MESSAGE_QUEUE_SIZE.dec()
 }
 
+pub fn ingress_dropped_inc(kind: &str) {
+    INGRESS_DROPPED.with_label_values(&[kind]).inc()
+}
+
+pub fn geyser_loop_stage_observe(stage: &str, duration: Duration) {
+    GEYSER_LOOP_STAGE_DURATION
+        .with_label_values(&[stage])
+        .observe(duration.as_secs_f64())
+}
+
+pub fn updates_latency_observe(stage: &str, duration: Duration) {
+    UPDATES_LATENCY
+        .with_label_values(&[stage])
+        .observe(duration.as_secs_f64())
+}
+
+pub fn encoded_cache_inc(status: FilteredUpdateEncodedStatus) {
+    ENCODED_CACHE.with_label_values(&[status.as_str()]).inc()
+}
+
 pub fn connections_total_inc() {
     CONNECTIONS_TOTAL.inc()
 }
@@ -349,6 +829,24 @@ pub fn connections_total_dec() {
     CONNECTIONS_TOTAL.dec()
 }
 
+pub fn connections_identity_inc(identity: &str) {
+    CONNECTIONS_IDENTITY.with_label_values(&[identity]).inc()
+}
+
+pub fn connections_identity_dec(identity: &str) {
+    CONNECTIONS_IDENTITY.with_label_values(&[identity]).dec()
+}
+
+pub fn tls_reload_inc(success: bool) {
+    TLS_RELOAD
+        .with_label_values(&[if success { "success" } else { "failure" }])
+        .inc()
+}
+
+pub fn peer_rejected_inc(reason: PeerRejectReason) {
+    PEER_REJECTED.with_label_values(&[reason.as_str()]).inc()
+}
+
 pub fn update_subscriptions(endpoint: &str, old: Option<&Filter>, new: Option<&Filter>) {
     for (multiplier, filter) in [(-1, old), (1, new)] {
         if let Some(filter) = filter {
@@ -370,3 +868,365 @@ pub fn missed_status_message_inc(status: SlotStatus) {
         .with_label_values(&[status.as_str()])
         .inc()
 }
+
+pub fn slot_status_duration_observe(from: SlotStatus, to: SlotStatus, duration: Duration) {
+    SLOT_STATUS_DURATION
+        .with_label_values(&[from.as_str(), to.as_str()])
+        .observe(duration.as_secs_f64())
+}
+
+/// Interval of queue size and lag updates, so gauges are not stale on idle streams
+pub const CLIENT_METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Metrics of one subscribe stream, label is resolved on connect.
+/// Number of different labels is limited, clients over the limit are counted as `other`.
+/// Label values are removed once the last client with the label disconnected.
+#[derive(Debug, Default)]
+pub struct ClientMetrics {
+    inner: Option<ClientMetricsInner>,
+}
+
+#[derive(Debug)]
+struct ClientMetricsInner {
+    label: String,
+    sent: [Option<(IntCounter, IntCounter)>; CLIENT_UPDATE_TYPES.len()],
+    queue_size: IntGauge,
+    queue_size_value: i64,
+    lag_slots: IntGauge,
+    lag_slot: Option<(CommitmentLevel, Slot)>,
+}
+
+/// Type and encoded size of the update, counted by `ClientMetrics::message_sent`
+/// only once the update is queued to the stream
+#[derive(Debug, Clone, Copy)]
+pub struct ClientMessageSize {
+    index: usize,
+    bytes: u64,
+}
+
+impl ClientMetrics {
+    pub fn new(config: &ConfigGrpcClientMetrics, endpoint: &str, identity: Option<&str>) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+
+        let label = match config.label {
+            ConfigGrpcClientMetricsLabel::Endpoint => endpoint,
+            ConfigGrpcClientMetricsLabel::Identity => identity.unwrap_or(endpoint),
+        };
+        let mut labels = CLIENT_LABELS.lock().expect("poisoned");
+        let labels_count = labels.len() - labels.contains_key(CLIENT_LABEL_OTHER) as usize;
+        let label = if label != CLIENT_LABEL_OTHER
+            && (labels.contains_key(label) || labels_count < config.max_labels)
+        {
+            label
+        } else {
+            CLIENT_LABEL_OTHER
+        };
+        *labels.entry(label.to_owned()).or_default() += 1;
+        drop(labels);
+
+        Self {
+            inner: Some(ClientMetricsInner {
+                label: label.to_owned(),
+                sent: Default::default(),
+                queue_size: CLIENT_QUEUE_SIZE.with_label_values(&[label]),
+                queue_size_value: 0,
+                lag_slots: CLIENT_LAG_SLOTS.with_label_values(&[label]),
+                lag_slot: None,
+            }),
+        }
+    }
+
+    pub fn message_size(&self, message: &FilteredUpdate) -> Option<ClientMessageSize> {
+        self.inner.as_ref()?;
+        let index = match &message.message {
+            FilteredUpdateOneof::Account(_) => 0,
+            FilteredUpdateOneof::Slot(_) => 1,
+            FilteredUpdateOneof::Transaction(_) => 2,
+            FilteredUpdateOneof::TransactionStatus(_) => 3,
+            FilteredUpdateOneof::Block(_) => 4,
+            FilteredUpdateOneof::Ping => 5,
+            FilteredUpdateOneof::Pong(_) => 6,
+            FilteredUpdateOneof::BlockMeta(_) => 7,
+            FilteredUpdateOneof::Entry(_) => 8,
+            FilteredUpdateOneof::Notice(_) => 9,
+            FilteredUpdateOneof::BlockFailure(_) => 10,
+        };
+        Some(ClientMessageSize {
+            index,
+            bytes: message.encoded_len() as u64,
+        })
+    }
+
+    pub fn message_sent(&mut self, size: Option<ClientMessageSize>) {
+        let Some(ClientMetricsInner { label, sent, .. }) = &mut self.inner else {
+            return;
+        };
+        let Some(ClientMessageSize { index, bytes: size }) = size else {
+            return;
+        };
+
+        let (messages, bytes) = sent[index].get_or_insert_with(|| {
+            let labels = [label.as_str(), CLIENT_UPDATE_TYPES[index]];
+            (
+                CLIENT_MESSAGES_SENT.with_label_values(&labels),
+                CLIENT_BYTES_SENT.with_label_values(&labels),
+            )
+        });
+        messages.inc();
+        bytes.inc_by(size);
+    }
+
+    /// Queue size is summed for clients with the same label
+    pub fn update_queue_size(&mut self, stream_tx: &mpsc::Sender<Result<FilteredUpdate, Status>>) {
+        if let Some(inner) = &mut self.inner {
+            let value = (stream_tx.max_capacity() - stream_tx.capacity()) as i64;
+            inner.queue_size.add(value - inner.queue_size_value);
+            inner.queue_size_value = value;
+        }
+    }
+
+    pub fn update_lag(&mut self, commitment: CommitmentLevel, slot: Slot) {
+        if let Some(inner) = &mut self.inner {
+            inner.lag_slot = Some((commitment, slot));
+            let status = match commitment {
+                CommitmentLevel::Processed => SlotStatus::Processed,
+                CommitmentLevel::Confirmed => SlotStatus::Confirmed,
+                CommitmentLevel::Finalized => SlotStatus::Finalized,
+            };
+            let plugin_slot = SLOT_STATUS_PLUGIN
+                .with_label_values(&[status.as_str()])
+                .get();
+            inner.lag_slots.set((plugin_slot - slot as i64).max(0));
+        }
+    }
+
+    /// Periodic update of queue size and of lag since the last sent slot
+    pub fn refresh(&mut self, stream_tx: &mpsc::Sender<Result<FilteredUpdate, Status>>) {
+        self.update_queue_size(stream_tx);
+        if let Some((commitment, slot)) = self.inner.as_ref().and_then(|inner| inner.lag_slot) {
+            self.update_lag(commitment, slot);
+        }
+    }
+}
+
+impl Drop for ClientMetrics {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner.take() else {
+            return;
+        };
+
+        inner.queue_size.sub(inner.queue_size_value);
+
+        let mut labels = CLIENT_LABELS.lock().expect("poisoned");
+        if let HashMapEntry::Occupied(mut entry) = labels.entry(inner.label) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                let (label, _) = entry.remove_entry();
+                for update_type in CLIENT_UPDATE_TYPES {
+                    let _ = CLIENT_MESSAGES_SENT.remove_label_values(&[&label, update_type]);
+                    let _ = CLIENT_BYTES_SENT.remove_label_values(&[&label, update_type]);
+                }
+                let _ = CLIENT_QUEUE_SIZE.remove_label_values(&[&label]);
+                let _ = CLIENT_LAG_SLOTS.remove_label_values(&[&label]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            debug_clients_disconnect_handler, debug_clients_handler, DebugClientMessage,
+            DebugClientStatuses,
+        },
+        http_body_util::BodyExt,
+        hyper::{Method, Request, StatusCode},
+        std::{collections::HashMap, time::Duration},
+        tokio::{sync::mpsc, time::sleep},
+        tonic::Code,
+        yellowstone_grpc_proto::geyser::{
+            subscribe_request_filter_accounts_filter::Filter as AccountsFilterDataOneof,
+            SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+        },
+    };
+
+    fn request(method: Method, uri: &str, token: Option<&str>) -> Request<()> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            builder = builder.header("x-token", token);
+        }
+        builder.body(()).unwrap()
+    }
+
+    async fn status_json(statuses: &DebugClientStatuses) -> serde_json::Value {
+        // client messages are processed in background
+        sleep(Duration::from_millis(10)).await;
+        let req = request(Method::GET, "/debug_clients?format=json", Some("admin"));
+        let response = debug_clients_handler(&req, statuses, Some("admin"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_debug_clients_json_token() {
+        let statuses = DebugClientStatuses::new();
+        for (admin_token, token, status) in [
+            (None, None, StatusCode::NOT_FOUND),
+            (None, Some("admin"), StatusCode::NOT_FOUND),
+            (Some("admin"), None, StatusCode::UNAUTHORIZED),
+            (Some("admin"), Some("admi"), StatusCode::UNAUTHORIZED),
+            (Some("admin"), Some("admin2"), StatusCode::UNAUTHORIZED),
+            (Some("admin"), Some("admin"), StatusCode::OK),
+        ] {
+            let req = request(Method::GET, "/debug_clients?format=json", token);
+            let response = debug_clients_handler(&req, &statuses, admin_token)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{admin_token:?} {token:?}");
+        }
+
+        // text status does not include filters
+        let req = request(Method::GET, "/debug_clients", None);
+        let response = debug_clients_handler(&req, &statuses, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_debug_clients_json() {
+        let statuses = DebugClientStatuses::new();
+        assert_eq!(status_json(&statuses).await, serde_json::json!([]));
+
+        let (client_tx, _client_rx) = mpsc::unbounded_channel();
+        let (stream_tx, _stream_rx) = mpsc::channel(1);
+        let clients_tx = statuses.clients_tx();
+        clients_tx
+            .send(DebugClientMessage::New {
+                id: 42,
+                endpoint: "client".to_owned(),
+                client_tx,
+                stream_tx,
+            })
+            .unwrap();
+        clients_tx
+            .send(DebugClientMessage::UpdateRequest {
+                id: 42,
+                request: Box::new(SubscribeRequest {
+                    accounts: HashMap::from([(
+                        "accounts".to_owned(),
+                        SubscribeRequestFilterAccounts {
+                            owner: vec!["owner".to_owned()],
+                            filters: vec![SubscribeRequestFilterAccountsFilter {
+                                filter: Some(AccountsFilterDataOneof::Datasize(165)),
+                            }],
+                            ..Default::default()
+                        },
+                    )]),
+                    from_slot: Some(10),
+                    ..Default::default()
+                }),
+            })
+            .unwrap();
+
+        let value = status_json(&statuses).await;
+        let client = &value[0];
+        assert_eq!(client["id"], 42);
+        assert_eq!(client["endpoint"], "client");
+        assert_eq!(client["commitment"], "processed");
+        let filter = &client["filter"];
+        assert_eq!(filter["from_slot"], 10);
+        assert_eq!(filter["accounts"]["accounts"]["owner"][0], "owner");
+        assert_eq!(
+            filter["accounts"]["accounts"]["filters"][0]["filter"]["Datasize"],
+            165
+        );
+    }
+
+    #[tokio::test]
+    async fn test_debug_clients_disconnect() {
+        let statuses = DebugClientStatuses::new();
+        let (client_tx, mut client_rx) = mpsc::unbounded_channel();
+        let (stream_tx, mut stream_rx) = mpsc::channel(1);
+        statuses
+            .clients_tx()
+            .send(DebugClientMessage::New {
+                id: 42,
+                endpoint: "client".to_owned(),
+                client_tx,
+                stream_tx,
+            })
+            .unwrap();
+        sleep(Duration::from_millis(10)).await;
+
+        for (method, uri, admin_token, token, status) in [
+            (
+                Method::POST,
+                "/debug_clients/disconnect?id=42",
+                None,
+                Some("admin"),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                Method::POST,
+                "/debug_clients/disconnect?id=42",
+                Some("admin"),
+                None,
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                Method::POST,
+                "/debug_clients/disconnect?id=42",
+                Some("admin"),
+                Some("other"),
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                Method::GET,
+                "/debug_clients/disconnect?id=42",
+                Some("admin"),
+                Some("admin"),
+                StatusCode::METHOD_NOT_ALLOWED,
+            ),
+            (
+                Method::POST,
+                "/debug_clients/disconnect?id=abc",
+                Some("admin"),
+                Some("admin"),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                Method::POST,
+                "/debug_clients/disconnect?id=1",
+                Some("admin"),
+                Some("admin"),
+                StatusCode::NOT_FOUND,
+            ),
+        ] {
+            let req = request(method, uri, token);
+            let response = debug_clients_disconnect_handler(&req, &statuses, admin_token)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{uri} {admin_token:?} {token:?}");
+        }
+        assert!(client_rx.try_recv().is_err());
+        assert!(stream_rx.try_recv().is_err());
+
+        let req = request(
+            Method::POST,
+            "/debug_clients/disconnect?id=42",
+            Some("admin"),
+        );
+        let response = debug_clients_disconnect_handler(&req, &statuses, Some("admin"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(client_rx.recv().await.unwrap().is_none());
+        let status = stream_rx.recv().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+    }
+}
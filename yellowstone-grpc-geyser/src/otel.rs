@@ -0,0 +1,187 @@
+use {
+    crate::config::ConfigTracing,
+    anyhow::Context as _,
+    http::{HeaderMap, Request},
+    log::{error, info},
+    opentelemetry::{global, propagation::Extractor, trace::TracerProvider as _, KeyValue},
+    opentelemetry_otlp::{SpanExporter, WithExportConfig},
+    opentelemetry_sdk::{
+        propagation::TraceContextPropagator,
+        runtime,
+        trace::{Sampler, TracerProvider},
+        Resource,
+    },
+    std::{
+        sync::OnceLock,
+        task::{Context, Poll},
+    },
+    tower::{Layer, Service},
+    tracing::{info_span, instrument::Instrumented, Instrument},
+    tracing_opentelemetry::OpenTelemetrySpanExt,
+    tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter},
+};
+
+static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
+/// Install global `tracing` subscriber with OTLP exporter, should be called within tokio runtime
+pub fn init(config: ConfigTracing) -> anyhow::Result<()> {
+    if PROVIDER.get().is_some() {
+        info!("tracing already initialized, changes in config require restart");
+        return Ok(());
+    }
+
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(config.endpoint)
+        .build()
+        .context("failed to create OTLP exporter")?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio,
+        ))))
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            config.service_name,
+        )]))
+        .build();
+
+    let filter = EnvFilter::try_new(&config.filter).context("invalid tracing filter")?;
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .context("failed to install tracing subscriber")?;
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let _ = PROVIDER.set(provider);
+    Ok(())
+}
+
+/// Export buffered spans
+pub fn flush() {
+    if let Some(provider) = PROVIDER.get() {
+        for result in provider.force_flush() {
+            if let Err(error) = result {
+                error!("failed to flush spans: {error}");
+            }
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Span for every gRPC request with parent from `traceparent` metadata
+#[derive(Debug, Clone, Copy)]
+pub struct TraceLayer;
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = TraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for TraceService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let span = info_span!(
+            "grpc",
+            otel.name = request.uri().path(),
+            otel.kind = "server",
+            rpc.system = "grpc",
+        );
+        if !span.is_disabled() {
+            let parent = global::get_text_map_propagator(|propagator| {
+                propagator.extract(&HeaderExtractor(request.headers()))
+            });
+            span.set_parent(parent);
+        }
+        self.inner.call(request).instrument(span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::TraceLayer,
+        http::Request,
+        opentelemetry::{
+            global,
+            trace::{SpanContext, TraceContextExt, TraceId, TracerProvider as _},
+        },
+        opentelemetry_sdk::{propagation::TraceContextPropagator, trace::TracerProvider},
+        std::convert::Infallible,
+        tower::{service_fn, Layer, Service},
+        tracing::Span,
+        tracing_opentelemetry::OpenTelemetrySpanExt,
+        tracing_subscriber::layer::SubscriberExt,
+    };
+
+    const TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+
+    async fn request_span_context(traceparent: Option<&str>) -> SpanContext {
+        let mut service = TraceLayer.layer(service_fn(|_request: Request<()>| async {
+            Ok::<_, Infallible>(Span::current().context().span().span_context().clone())
+        }));
+        let mut request = Request::builder().uri("/geyser.Geyser/Subscribe");
+        if let Some(traceparent) = traceparent {
+            request = request.header("traceparent", traceparent);
+        }
+        service.call(request.body(()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_traceparent() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // trace id and sampled flag are inherited from client
+        let context =
+            request_span_context(Some(&format!("00-{TRACE_ID}-b7ad6b7169203331-01"))).await;
+        assert_eq!(context.trace_id(), TraceId::from_hex(TRACE_ID).unwrap());
+        assert!(context.is_sampled());
+
+        let context =
+            request_span_context(Some(&format!("00-{TRACE_ID}-b7ad6b7169203331-00"))).await;
+        assert_eq!(context.trace_id(), TraceId::from_hex(TRACE_ID).unwrap());
+        assert!(!context.is_sampled());
+
+        // new trace without or with invalid `traceparent`
+        for traceparent in [None, Some("00-invalid-b7ad6b7169203331-01")] {
+            let context = request_span_context(traceparent).await;
+            assert!(context.is_valid());
+            assert_ne!(context.trace_id(), TraceId::from_hex(TRACE_ID).unwrap());
+        }
+    }
+}
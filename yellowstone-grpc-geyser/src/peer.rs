@@ -0,0 +1,506 @@
+use {
+    crate::{config::ConfigGrpcPeers, metrics},
+    futures::{
+        future::ready,
+        stream::{Stream, StreamExt},
+    },
+    std::{
+        collections::HashMap,
+        fmt,
+        future::Future,
+        io,
+        net::IpAddr,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        task::{Context, Poll},
+        time::Duration,
+    },
+    tokio::{
+        io::{AsyncRead, AsyncWrite, ReadBuf},
+        net::{TcpStream, UnixStream},
+        time::{sleep, Sleep},
+    },
+    tonic::{
+        transport::server::{Connected, TcpConnectInfo, TlsConnectInfo},
+        Request, Status,
+    },
+};
+
+/// Rejected connections are closed after this timeout if client keep them open
+const REJECTED_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Max number of rejected connections kept for responding with error status,
+/// other rejected connections are closed right away
+const REJECTED_CONNECTIONS_MAX: usize = 64;
+
+/// Source of the connection: IP address for TCP and UID for Unix socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Peer {
+    Ip(IpAddr),
+    Uid(u32),
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "{ip}"),
+            Self::Uid(uid) => write!(f, "uid:{uid}"),
+        }
+    }
+}
+
+pub trait PeerSource {
+    fn peer(&self) -> Option<Peer>;
+
+    fn tcp_connect_info(&self) -> Option<TcpConnectInfo> {
+        None
+    }
+}
+
+impl PeerSource for TcpStream {
+    fn peer(&self) -> Option<Peer> {
+        self.peer_addr()
+            .ok()
+            .map(|addr| Peer::Ip(addr.ip().to_canonical()))
+    }
+
+    fn tcp_connect_info(&self) -> Option<TcpConnectInfo> {
+        Some(self.connect_info())
+    }
+}
+
+impl PeerSource for UnixStream {
+    fn peer(&self) -> Option<Peer> {
+        self.peer_cred().ok().map(|cred| Peer::Uid(cred.uid()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerRejectReason {
+    Deny,
+    Connections,
+    Subscriptions,
+}
+
+impl PeerRejectReason {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Deny => "deny",
+            Self::Connections => "connections",
+            Self::Subscriptions => "subscriptions",
+        }
+    }
+
+    fn into_status(self, peer: Peer) -> Status {
+        match self {
+            Self::Deny => Status::permission_denied(format!("source {peer} is not allowed")),
+            Self::Connections => {
+                Status::resource_exhausted(format!("too many connections from {peer}"))
+            }
+            Self::Subscriptions => {
+                Status::resource_exhausted(format!("too many subscriptions from {peer}"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PeerCounts {
+    connections: usize,
+    subscriptions: usize,
+}
+
+/// Allow/deny lists and limits of concurrent connections and subscriptions per peer
+#[derive(Debug)]
+pub struct PeerLimits {
+    config: ConfigGrpcPeers,
+    counts: Mutex<HashMap<Peer, PeerCounts>>,
+    rejected: AtomicUsize,
+}
+
+impl PeerLimits {
+    pub fn new(config: ConfigGrpcPeers) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            counts: Mutex::default(),
+            rejected: AtomicUsize::new(0),
+        })
+    }
+
+    fn is_allowed(&self, peer: Peer) -> bool {
+        let Peer::Ip(ip) = peer else {
+            return true;
+        };
+        (self.config.allow.is_empty() || self.config.allow.iter().any(|net| net.contains(&ip)))
+            && !self.config.deny.iter().any(|net| net.contains(&ip))
+    }
+
+    fn update_counts(&self, peer: Peer, f: impl FnOnce(&mut PeerCounts) -> bool) -> bool {
+        let mut counts = self.counts.lock().expect("poisoned");
+        let entry = counts.entry(peer).or_default();
+        let updated = f(entry);
+        if entry.connections == 0 && entry.subscriptions == 0 {
+            counts.remove(&peer);
+        }
+        updated
+    }
+
+    fn connection_open(self: &Arc<Self>, peer: Peer) -> Result<PeerGuard, PeerRejectReason> {
+        if !self.is_allowed(peer) {
+            return Err(PeerRejectReason::Deny);
+        }
+
+        let max = self.config.max_connections.unwrap_or(usize::MAX);
+        if self.update_counts(peer, |counts| {
+            let accepted = counts.connections < max;
+            if accepted {
+                counts.connections += 1;
+            }
+            accepted
+        }) {
+            Ok(PeerGuard {
+                limits: Arc::clone(self),
+                peer,
+                kind: PeerGuardKind::Connection,
+            })
+        } else {
+            Err(PeerRejectReason::Connections)
+        }
+    }
+
+    /// Reserve subscription for the peer of request, released on guard drop
+    pub fn subscription_open<T>(
+        self: &Arc<Self>,
+        request: &Request<T>,
+    ) -> Result<Option<PeerGuard>, Status> {
+        let Some(peer) = PeerConnectInfo::get(request).and_then(|info| info.peer) else {
+            return Ok(None);
+        };
+
+        let max = self.config.max_subscriptions.unwrap_or(usize::MAX);
+        if self.update_counts(peer, |counts| {
+            let accepted = counts.subscriptions < max;
+            if accepted {
+                counts.subscriptions += 1;
+            }
+            accepted
+        }) {
+            Ok(Some(PeerGuard {
+                limits: Arc::clone(self),
+                peer,
+                kind: PeerGuardKind::Subscription,
+            }))
+        } else {
+            let reason = PeerRejectReason::Subscriptions;
+            metrics::peer_rejected_inc(reason);
+            Err(reason.into_status(peer))
+        }
+    }
+
+    /// Wrap accepted connections, connections from denied or over limit peers are
+    /// kept only for responding with error status on the first request
+    pub fn incoming<IO: PeerSource>(
+        self: &Arc<Self>,
+        incoming: impl Stream<Item = io::Result<IO>>,
+    ) -> impl Stream<Item = io::Result<PeerIo<IO>>> {
+        let limits = Arc::clone(self);
+        incoming.filter_map(move |item| {
+            ready(match item {
+                Ok(io) => limits.accept(io).map(Ok),
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+
+    /// Rejected connection is dropped (closed) if too many rejected connections are kept
+    fn accept<IO: PeerSource>(self: &Arc<Self>, io: IO) -> Option<PeerIo<IO>> {
+        let peer = io.peer();
+        let (guard, rejected) = match peer.map(|peer| self.connection_open(peer)) {
+            Some(Ok(guard)) => (Some(guard), None),
+            Some(Err(reason)) => {
+                metrics::peer_rejected_inc(reason);
+                (Some(self.rejected_open(peer?)?), Some(reason))
+            }
+            None => (None, None),
+        };
+        Some(PeerIo {
+            info: PeerConnectInfo {
+                peer,
+                rejected,
+                tcp: io.tcp_connect_info(),
+            },
+            io,
+            rejected_timeout: rejected.map(|_| Box::pin(sleep(REJECTED_CONNECTION_TIMEOUT))),
+            _guard: guard,
+        })
+    }
+
+    fn rejected_open(self: &Arc<Self>, peer: Peer) -> Option<PeerGuard> {
+        self.rejected
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |rejected| {
+                (rejected < REJECTED_CONNECTIONS_MAX).then_some(rejected + 1)
+            })
+            .ok()
+            .map(|_| PeerGuard {
+                limits: Arc::clone(self),
+                peer,
+                kind: PeerGuardKind::Rejected,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PeerGuardKind {
+    Connection,
+    Subscription,
+    Rejected,
+}
+
+#[derive(Debug)]
+pub struct PeerGuard {
+    limits: Arc<PeerLimits>,
+    peer: Peer,
+    kind: PeerGuardKind,
+}
+
+impl Drop for PeerGuard {
+    fn drop(&mut self) {
+        match self.kind {
+            PeerGuardKind::Connection => self.limits.update_counts(self.peer, |counts| {
+                counts.connections -= 1;
+                true
+            }),
+            PeerGuardKind::Subscription => self.limits.update_counts(self.peer, |counts| {
+                counts.subscriptions -= 1;
+                true
+            }),
+            PeerGuardKind::Rejected => {
+                self.limits.rejected.fetch_sub(1, Ordering::Relaxed);
+                true
+            }
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerConnectInfo {
+    pub peer: Option<Peer>,
+    pub rejected: Option<PeerRejectReason>,
+    /// Addresses of TCP connection
+    pub tcp: Option<TcpConnectInfo>,
+}
+
+impl PeerConnectInfo {
+    pub fn get<T>(request: &Request<T>) -> Option<&Self> {
+        let extensions = request.extensions();
+        extensions.get::<Self>().or_else(|| {
+            extensions
+                .get::<TlsConnectInfo<Self>>()
+                .map(|info| info.get_ref())
+        })
+    }
+
+    /// Respond with error if connection was rejected, otherwise addresses of TCP connection
+    /// are added to request, so `Request::remote_addr` works as without wrapped connection
+    pub fn check_request<T>(request: &mut Request<T>) -> Result<(), Status> {
+        let tcp = match Self::get(request) {
+            Some(Self {
+                peer: Some(peer),
+                rejected: Some(reason),
+                ..
+            }) => return Err(reason.into_status(*peer)),
+            Some(info) => info.tcp.clone(),
+            None => None,
+        };
+        if let Some(tcp) = tcp {
+            request.extensions_mut().insert(tcp);
+        }
+        Ok(())
+    }
+}
+
+pub struct PeerIo<IO> {
+    io: IO,
+    info: PeerConnectInfo,
+    rejected_timeout: Option<Pin<Box<Sleep>>>,
+    _guard: Option<PeerGuard>,
+}
+
+impl<IO> Connected for PeerIo<IO> {
+    type ConnectInfo = PeerConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.info.clone()
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for PeerIo<IO> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if let Some(timeout) = self.rejected_timeout.as_mut() {
+            if timeout.as_mut().poll(cx).is_ready() {
+                // EOF, connection would be closed
+                return Poll::Ready(Ok(()));
+            }
+        }
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for PeerIo<IO> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            Peer, PeerConnectInfo, PeerLimits, PeerRejectReason, PeerSource,
+            REJECTED_CONNECTIONS_MAX,
+        },
+        crate::config::ConfigGrpcPeers,
+        std::net::{IpAddr, Ipv4Addr, SocketAddr},
+        tonic::{transport::server::TcpConnectInfo, Code, Request},
+    };
+
+    struct TestIo(Peer);
+
+    impl PeerSource for TestIo {
+        fn peer(&self) -> Option<Peer> {
+            Some(self.0)
+        }
+    }
+
+    fn ip(ip: [u8; 4]) -> Peer {
+        Peer::Ip(IpAddr::V4(Ipv4Addr::from(ip)))
+    }
+
+    #[test]
+    fn test_allow_deny() {
+        let limits = PeerLimits::new(ConfigGrpcPeers {
+            allow: vec!["10.0.0.0/8".parse().unwrap()],
+            deny: vec!["10.1.0.0/16".parse().unwrap()],
+            ..Default::default()
+        });
+        assert!(limits.connection_open(ip([10, 0, 0, 1])).is_ok());
+        for peer in [ip([10, 1, 0, 1]), ip([192, 168, 0, 1])] {
+            assert_eq!(
+                limits.connection_open(peer).unwrap_err(),
+                PeerRejectReason::Deny
+            );
+        }
+        // Unix socket peers are not filtered by networks
+        assert!(limits.connection_open(Peer::Uid(1000)).is_ok());
+
+        let limits = PeerLimits::new(ConfigGrpcPeers {
+            deny: vec!["10.1.0.0/16".parse().unwrap()],
+            ..Default::default()
+        });
+        assert!(limits.connection_open(ip([192, 168, 0, 1])).is_ok());
+        assert!(limits.connection_open(ip([10, 1, 0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_max_connections() {
+        let limits = PeerLimits::new(ConfigGrpcPeers {
+            max_connections: Some(2),
+            ..Default::default()
+        });
+        let peer = ip([10, 0, 0, 1]);
+        let guard1 = limits.connection_open(peer).unwrap();
+        let _guard2 = limits.connection_open(peer).unwrap();
+        assert_eq!(
+            limits.connection_open(peer).unwrap_err(),
+            PeerRejectReason::Connections
+        );
+        // limit is per peer
+        let guard3 = limits.connection_open(ip([10, 0, 0, 2])).unwrap();
+
+        drop(guard1);
+        let _guard1 = limits.connection_open(peer).unwrap();
+        drop(guard3);
+        assert_eq!(limits.counts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_connections() {
+        let limits = PeerLimits::new(ConfigGrpcPeers {
+            deny: vec!["10.0.0.0/8".parse().unwrap()],
+            ..Default::default()
+        });
+
+        let accepted = limits.accept(TestIo(ip([192, 168, 0, 1]))).unwrap();
+        assert!(accepted.info.rejected.is_none());
+        assert!(accepted.rejected_timeout.is_none());
+
+        // rejected connections are kept up to the limit, others are closed
+        let mut rejected = (0..REJECTED_CONNECTIONS_MAX)
+            .map(|_| limits.accept(TestIo(ip([10, 0, 0, 1]))).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rejected[0].info.rejected, Some(PeerRejectReason::Deny));
+        assert!(limits.accept(TestIo(ip([10, 0, 0, 1]))).is_none());
+        rejected.pop();
+        assert!(limits.accept(TestIo(ip([10, 0, 0, 1]))).is_some());
+    }
+
+    #[test]
+    fn test_check_request() {
+        let remote_addr = SocketAddr::from(([10, 0, 0, 1], 50000));
+        let mut request = Request::new(());
+        request.extensions_mut().insert(PeerConnectInfo {
+            peer: Some(Peer::Ip(remote_addr.ip())),
+            rejected: None,
+            tcp: Some(TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some(remote_addr),
+            }),
+        });
+        assert!(PeerConnectInfo::check_request(&mut request).is_ok());
+        assert_eq!(request.remote_addr(), Some(remote_addr));
+
+        let mut request = Request::new(());
+        request.extensions_mut().insert(PeerConnectInfo {
+            peer: Some(Peer::Ip(remote_addr.ip())),
+            rejected: Some(PeerRejectReason::Connections),
+            tcp: None,
+        });
+        assert_eq!(
+            PeerConnectInfo::check_request(&mut request)
+                .unwrap_err()
+                .code(),
+            Code::ResourceExhausted
+        );
+    }
+}
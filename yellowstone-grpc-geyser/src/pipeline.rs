@@ -0,0 +1,1434 @@
+use {
+    crate::{
+        config::ConfigGrpcPipeline,
+        ingress::IngressReceiver,
+        logger::{self, Suppressed},
+        metrics,
+        watchdog::{SlotWatchdog, SLOT_WATCHDOG_INTERVAL},
+    },
+    log::error,
+    prost_types::Timestamp,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    std::{
+        collections::{BTreeMap, HashMap},
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::SystemTime,
+    },
+    tokio::{
+        sync::{broadcast, mpsc, oneshot},
+        time::{interval, sleep, Duration, Instant},
+    },
+    tracing::debug_span,
+    yellowstone_grpc_proto::{
+        plugin::{
+            filter::{
+                index::{FilterIndex, FilterIndexRoutes},
+                Filter,
+            },
+            message::{
+                CommitmentLevel, Message, MessageBlock, MessageBlockMeta, MessageEntry,
+                MessageSlot, MessageTransactionInfo, SlotStatus,
+            },
+        },
+        prelude::{
+            SubscribeUpdateBlockIncomplete, SubscribeUpdateBlockMeta, SubscribeUpdateSlotTimings,
+        },
+    },
+};
+
+const PROCESSED_MESSAGES_MAX: usize = 31;
+const PROCESSED_MESSAGES_SLEEP: Duration = Duration::from_millis(10);
+
+/// Capacity of channels between router, shards and sequencer
+const STAGE_CHANNEL_CAPACITY: usize = 65_536;
+
+#[derive(Debug, Clone)]
+pub struct BroadcastedMessage {
+    pub commitment: CommitmentLevel,
+    pub messages: Arc<Vec<(u64, Message)>>,
+    /// Routes to streams in the filter index
+    pub routes: Option<Arc<FilterIndexRoutes>>,
+    pub broadcasted_at: SystemTime,
+}
+
+impl BroadcastedMessage {
+    fn new(
+        commitment: CommitmentLevel,
+        messages: Vec<(u64, Message)>,
+        routes: Option<FilterIndexRoutes>,
+    ) -> Self {
+        Self {
+            commitment,
+            messages: Arc::new(messages),
+            routes: routes.map(Arc::new),
+            broadcasted_at: SystemTime::now(),
+        }
+    }
+}
+
+pub enum ReplayedResponse {
+    /// Messages in the order of routing
+    Messages(Vec<(u64, Message)>),
+    Lagged(Slot),
+}
+
+pub type ReplayStoredSlotsRequest = (CommitmentLevel, Slot, oneshot::Sender<ReplayedResponse>);
+
+/// Slots kept by router and messages kept by shards, used for replay and block reconstruction
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplayStats {
+    pub slots: usize,
+    pub first_slot: Option<Slot>,
+    pub last_slot: Option<Slot>,
+    pub messages: usize,
+}
+
+pub type ReplayStatsRequest = oneshot::Sender<ReplayStats>;
+
+/// Message ids are shared by router and shards, unique but not ordered between shards
+#[derive(Debug, Default, Clone)]
+struct MessageId {
+    id: Arc<AtomicU64>,
+}
+
+impl MessageId {
+    fn next(&self) -> u64 {
+        let id = self.id.fetch_add(1, Ordering::Relaxed);
+        id.checked_add(1).expect("message id overflow")
+    }
+}
+
+/// Number of subscriptions with entries transactions signatures, shards collect signatures
+/// only while there is at least one such subscription
+#[derive(Debug, Default, Clone)]
+pub struct EntriesSignatures {
+    subscriptions: Arc<AtomicUsize>,
+}
+
+impl EntriesSignatures {
+    pub fn update(&self, old: Option<&Filter>, new: Option<&Filter>) {
+        let old = old.is_some_and(Filter::has_entries_with_signatures);
+        let new = new.is_some_and(Filter::has_entries_with_signatures);
+        match (old, new) {
+            (false, true) => {
+                self.subscriptions.fetch_add(1, Ordering::Relaxed);
+            }
+            (true, false) => {
+                self.subscriptions.fetch_sub(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.subscriptions.load(Ordering::Relaxed) > 0
+    }
+}
+
+/// Message with sequence number of op, used to restore order of replayed messages
+type SeqMessage = (u64, (u64, Message));
+
+/// Slot statuses info, kept by router
+#[derive(Debug, Default)]
+struct SlotInfo {
+    messages_slots: Vec<SeqMessage>,
+    timings: SubscribeUpdateSlotTimings,
+    parent_slot: Option<Slot>,
+    confirmed: bool,
+    finalized: bool,
+}
+
+impl SlotInfo {
+    fn update_timings(&mut self, msg: &mut MessageSlot) {
+        let timings = &mut self.timings;
+        let (timing, prev) = match msg.status {
+            SlotStatus::FirstShredReceived => (&mut timings.first_shred_received, None),
+            SlotStatus::Completed => (&mut timings.completed, None),
+            SlotStatus::CreatedBank => (&mut timings.created_bank, None),
+            SlotStatus::Processed => (
+                &mut timings.processed,
+                Some((SlotStatus::FirstShredReceived, timings.first_shred_received)),
+            ),
+            SlotStatus::Confirmed => (
+                &mut timings.confirmed,
+                Some((SlotStatus::Processed, timings.processed)),
+            ),
+            SlotStatus::Finalized => (
+                &mut timings.finalized,
+                Some((SlotStatus::Confirmed, timings.confirmed)),
+            ),
+            SlotStatus::Dead => (&mut timings.dead, None),
+        };
+
+        // keep first received status only
+        if timing.is_none() {
+            *timing = Some(msg.created_at);
+            if let Some((prev_status, Some(prev_at))) = prev {
+                if let (Ok(prev_at), Ok(created_at)) = (
+                    SystemTime::try_from(prev_at),
+                    SystemTime::try_from(msg.created_at),
+                ) {
+                    if let Ok(duration) = created_at.duration_since(prev_at) {
+                        metrics::slot_status_duration_observe(prev_status, msg.status, duration);
+                    }
+                }
+            }
+        }
+
+        msg.timings = Some(self.timings);
+    }
+}
+
+/// Block reconstruction info, kept by shard
+#[derive(Debug, Default)]
+struct SlotMessages {
+    messages: Vec<Option<(u64, Message)>>, // Option is used for accounts with low write_version
+    messages_seq: Vec<u64>,                // sequence numbers of ops for `messages`
+    stored: usize,                         // number of not replaced messages
+    block_meta: Option<Arc<MessageBlockMeta>>,
+    transactions: Vec<Arc<MessageTransactionInfo>>,
+    accounts_dedup: HashMap<Pubkey, (u64, usize)>, // (write_version, message_index)
+    entries: Vec<Arc<MessageEntry>>,
+    sign_entries: bool, // decided on first message, so signed entries of slot are complete
+    entries_unsigned: Vec<Arc<MessageEntry>>, // entries waiting for transactions signatures
+    transactions_signatures: HashMap<usize, Signature>,
+    sealed: bool,
+    entries_count: usize,
+    confirmed_at: Option<usize>,
+    finalized_at: Option<usize>,
+}
+
+impl SlotMessages {
+    fn new(entries_signatures: &EntriesSignatures) -> Self {
+        Self {
+            sign_entries: entries_signatures.is_enabled(),
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, seq: u64, message: (u64, Message)) {
+        self.messages.push(Some(message));
+        self.messages_seq.push(seq);
+        self.stored += 1;
+    }
+
+    fn try_seal(&mut self, seq: u64, msgid_gen: &MessageId) -> Option<(u64, Message)> {
+        if !self.sealed {
+            if let Some(block_meta) = &self.block_meta {
+                let executed_transaction_count = block_meta.executed_transaction_count as usize;
+                let entries_count = block_meta.entries_count as usize;
+
+                // Additional check `entries_count == 0` due to bug of zero entries on block produced by validator
+                // See GitHub issue: https://github.com/solana-labs/solana/issues/33823
+                if self.transactions.len() == executed_transaction_count
+                    && (entries_count == 0 || self.entries.len() == entries_count)
+                {
+                    let transactions = std::mem::take(&mut self.transactions);
+                    let mut entries = std::mem::take(&mut self.entries);
+                    if entries_count == 0 {
+                        entries.clear();
+                    }
+
+                    let mut accounts = Vec::with_capacity(self.messages.len());
+                    for item in self.messages.iter().flatten() {
+                        if let (_msgid, Message::Account(account)) = item {
+                            accounts.push(Arc::clone(&account.account));
+                        }
+                    }
+
+                    let message_block = Message::Block(Arc::new(MessageBlock::new(
+                        Arc::clone(block_meta),
+                        transactions,
+                        accounts,
+                        entries,
+                    )));
+                    let message = (msgid_gen.next(), message_block);
+                    self.push(seq, message.clone());
+
+                    self.sealed = true;
+                    self.entries_count = entries_count;
+                    return Some(message);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn try_sign_entries(&mut self, seq: u64, msgid_gen: &MessageId) -> Vec<(u64, Message)> {
+        let mut messages = vec![];
+        let transactions_signatures = &self.transactions_signatures;
+        self.entries_unsigned.retain(|entry| {
+            let start = entry.starting_transaction_index as usize;
+            let end = start + entry.executed_transaction_count as usize;
+            match (start..end)
+                .map(|index| transactions_signatures.get(&index).copied())
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(signatures) => {
+                    let entry = entry.with_transaction_signatures(signatures);
+                    messages.push((msgid_gen.next(), Message::Entry(Arc::new(entry))));
+                    false
+                }
+                None => true,
+            }
+        });
+        for message in messages.iter() {
+            self.push(seq, message.clone());
+        }
+        messages
+    }
+
+    fn messages_replay(&self) -> Vec<SeqMessage> {
+        self.messages_seq
+            .iter()
+            .zip(self.messages.iter())
+            .filter_map(|(seq, message)| message.clone().map(|message| (*seq, message)))
+            .collect()
+    }
+
+    fn messages_from(&self, index: Option<usize>) -> Vec<(u64, Message)> {
+        match index {
+            Some(index) => self.messages.as_slice()[index..]
+                .iter()
+                .filter_map(|x| x.clone())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn to_incomplete_block(&self, slot: Slot, parent_slot: Option<Slot>) -> Message {
+        let incomplete = SubscribeUpdateBlockIncomplete {
+            expected_transaction_count: self
+                .block_meta
+                .as_ref()
+                .map(|block_meta| block_meta.executed_transaction_count),
+            observed_transaction_count: self.transactions.len() as u64,
+            expected_entries_count: self
+                .block_meta
+                .as_ref()
+                .map(|block_meta| block_meta.entries_count),
+            observed_entries_count: self.entries.len() as u64,
+        };
+
+        let block_meta = self.block_meta.clone().unwrap_or_else(|| {
+            Arc::new(MessageBlockMeta {
+                block_meta: SubscribeUpdateBlockMeta {
+                    slot,
+                    parent_slot: parent_slot.unwrap_or_default(),
+                    ..SubscribeUpdateBlockMeta::default()
+                },
+                created_at: Timestamp::from(SystemTime::now()),
+            })
+        });
+
+        let accounts = self
+            .messages
+            .iter()
+            .flatten()
+            .filter_map(|(_msgid, message)| match message {
+                Message::Account(account) => Some(Arc::clone(&account.account)),
+                _ => None,
+            })
+            .collect();
+
+        Message::Block(Arc::new(MessageBlock::new_incomplete(
+            block_meta,
+            self.transactions.clone(),
+            accounts,
+            self.entries.clone(),
+            incomplete,
+        )))
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum ShardOp {
+    /// Slot is outdated
+    Remove {
+        slot: Slot,
+    },
+    /// Slot is finalized, incomplete block is reported if slot is not sealed
+    Finalized {
+        slot: Slot,
+        parent_slot: Option<Slot>,
+    },
+    Message {
+        msgid: u64,
+        message: Message,
+    },
+    /// Notice is sent to clients of all commitments in order with other messages
+    Notice {
+        msgid: u64,
+        message: Message,
+    },
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum ShardRequest {
+    Update {
+        seq: u64,
+        routed_at: Instant,
+        op: ShardOp,
+    },
+    /// Messages of requested slots, slot statuses are kept by router
+    Replay {
+        slots: Vec<Slot>,
+        tx: oneshot::Sender<HashMap<Slot, Vec<SeqMessage>>>,
+    },
+    /// Number of kept messages
+    Stats { tx: oneshot::Sender<usize> },
+}
+
+/// Update prepared by shard, `confirmed` and `finalized` are messages collected for these
+/// commitments, sequencer only does batching of processed messages
+#[derive(Debug)]
+enum ShardUpdate {
+    Slot {
+        message: (u64, Message),
+        confirmed: Vec<(u64, Message)>,
+        finalized: Vec<(u64, Message)>,
+    },
+    Message {
+        message: (u64, Message),
+        confirmed: Vec<(u64, Message)>,
+        finalized: Vec<(u64, Message)>,
+    },
+    Notice((u64, Message)),
+}
+
+#[derive(Debug)]
+struct ShardOutput {
+    seq: u64,
+    updates: Vec<ShardUpdate>,
+    processed_at: Instant,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("block reconstruction shard is stopped")]
+pub struct ShardClosedError;
+
+/// Routes ops to shards by slot, every op gets sequence number used for ordering in sequencer
+#[derive(Debug)]
+struct ShardRouter {
+    shards: Vec<mpsc::Sender<ShardRequest>>,
+    seq: u64,
+}
+
+impl ShardRouter {
+    fn get_shard(&self, slot: Slot) -> &mpsc::Sender<ShardRequest> {
+        &self.shards[(slot % self.shards.len() as u64) as usize]
+    }
+
+    /// Sequencer waits for every sequence number, so pipeline can not continue if op is lost
+    async fn send(
+        &mut self,
+        slot: Slot,
+        routed_at: Instant,
+        op: ShardOp,
+    ) -> Result<u64, ShardClosedError> {
+        let seq = self.seq;
+        self.get_shard(slot)
+            .send(ShardRequest::Update { seq, routed_at, op })
+            .await
+            .map_err(|_error| ShardClosedError)?;
+        self.seq += 1;
+        Ok(seq)
+    }
+}
+
+/// Message pipeline from plugin to clients:
+///   - router: slot statuses, missed statuses and outdated slots, requests to replay
+///   - shards: accounts dedup, blocks sealing and messages for confirmed and finalized,
+///     every shard handles own subset of slots
+///   - sequencer: restores order of shards output and broadcasts processed messages in batches
+///
+/// With sequencer, order of messages to clients is the same as with a single loop.
+#[allow(clippy::too_many_arguments)]
+pub async fn geyser_loop(
+    config: ConfigGrpcPipeline,
+    mut messages_rx: IngressReceiver,
+    blocks_meta_tx: Option<mpsc::UnboundedSender<Message>>,
+    broadcast_tx: broadcast::Sender<BroadcastedMessage>,
+    filter_index: Option<Arc<FilterIndex>>,
+    replay_stored_slots_rx: Option<mpsc::Receiver<ReplayStoredSlotsRequest>>,
+    replay_first_available_slot: Option<Arc<AtomicU64>>,
+    replay_stored_slots: u64,
+    mut replay_stats_rx: mpsc::Receiver<ReplayStatsRequest>,
+    mut slot_watchdog: SlotWatchdog,
+    entries_signatures: EntriesSignatures,
+) -> Result<(), ShardClosedError> {
+    let msgid_gen = MessageId::default();
+    let (output_tx, output_rx) = mpsc::channel(STAGE_CHANNEL_CAPACITY);
+    let mut router = ShardRouter {
+        shards: (0..config.shards.max(1))
+            .map(|_| {
+                let (tx, rx) = mpsc::channel(STAGE_CHANNEL_CAPACITY);
+                tokio::spawn(shard_loop(
+                    rx,
+                    output_tx.clone(),
+                    msgid_gen.clone(),
+                    entries_signatures.clone(),
+                    config.block_reconstruction,
+                ));
+                tx
+            })
+            .collect(),
+        seq: 0,
+    };
+    drop(output_tx);
+    let broadcaster = Broadcaster {
+        tx: broadcast_tx,
+        filter_index,
+    };
+    tokio::spawn(sequencer_loop(output_rx, broadcaster));
+
+    let mut slots: BTreeMap<Slot, SlotInfo> = Default::default();
+    let mut processed_first_slot = None;
+    let (_tx, rx) = mpsc::channel(1);
+    let mut replay_stored_slots_rx = replay_stored_slots_rx.unwrap_or(rx);
+    let mut slot_watchdog_interval = interval(SLOT_WATCHDOG_INTERVAL);
+
+    loop {
+        tokio::select! {
+            Some(mut message) = messages_rx.recv() => {
+                let routed_at = Instant::now();
+                metrics::message_queue_size_dec();
+                let msgid = msgid_gen.next();
+                // span is not entered because of awaits, duration is from creation to drop
+                let _span = debug_span!("geyser_loop.route", msgid, slot = message.get_slot());
+
+                // Update metrics
+                if let Message::Slot(slot_message) = &message {
+                    metrics::update_slot_plugin_status(slot_message.status, slot_message.slot);
+                    slot_watchdog.slot_updated(slot_message);
+                }
+
+                // Update blocks info
+                if let Some(blocks_meta_tx) = &blocks_meta_tx {
+                    if matches!(&message, Message::Slot(_) | Message::BlockMeta(_)) {
+                        let _ = blocks_meta_tx.send(message.clone());
+                    }
+                }
+
+                // Remove outdated block reconstruction info
+                match &message {
+                    // On startup we can receive multiple Confirmed/Finalized slots without BlockMeta message
+                    // With saved first Processed slot we can ignore errors caused by startup process
+                    Message::Slot(msg) if processed_first_slot.is_none() && msg.status == SlotStatus::Processed => {
+                        processed_first_slot = Some(msg.slot);
+                    }
+                    Message::Slot(msg) if msg.status == SlotStatus::Finalized => {
+                        // keep extra 10 slots + slots for replay
+                        if let Some(msg_slot) = msg.slot.checked_sub(10 + replay_stored_slots) {
+                            while let Some(entry) = slots.first_entry() {
+                                if *entry.key() >= msg_slot {
+                                    break;
+                                }
+                                let slot = entry.remove_entry().0;
+                                router.send(slot, routed_at, ShardOp::Remove { slot }).await?;
+                            }
+                            if let Some(stored) = &replay_first_available_slot {
+                                if let Some(slot) = slots.keys().next().copied() {
+                                    stored.store(slot, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+
+                // Update slot statuses info
+                let slot = message.get_slot();
+                let info = slots.entry(slot).or_default();
+                let mut slot_status = None;
+                let mut message_slot = None;
+                if let Message::Slot(msg) = &mut message {
+                    info.update_timings(msg);
+                    match msg.status {
+                        SlotStatus::Processed => {
+                            info.parent_slot = msg.parent;
+                        },
+                        SlotStatus::Confirmed => {
+                            info.confirmed = true;
+                        },
+                        SlotStatus::Finalized => {
+                            info.finalized = true;
+                        },
+                        _ => {}
+                    }
+                    slot_status = Some(msg.status);
+                    message_slot = Some(message.clone());
+                }
+                let parent_slot = info.parent_slot;
+
+                // sometimes we do not receive all statuses
+                let mut missed_statuses = vec![];
+                if let Some(status) = slot_status {
+                    let mut parents = vec![slot];
+                    while let Some((parent, Some(entry))) = parents
+                        .pop()
+                        .and_then(|slot| slots.get(&slot))
+                        .and_then(|entry| entry.parent_slot)
+                        .map(|parent| (parent, slots.get_mut(&parent)))
+                    {
+                        if (status == SlotStatus::Confirmed && !entry.confirmed) ||
+                            (status == SlotStatus::Finalized && !entry.finalized)
+                        {
+                            if status == SlotStatus::Confirmed {
+                                entry.confirmed = true;
+                            } else if status == SlotStatus::Finalized {
+                                entry.finalized = true;
+                            }
+
+                            parents.push(parent);
+                            let mut message_slot = MessageSlot {
+                                slot: parent,
+                                parent: entry.parent_slot,
+                                status,
+                                dead_error: None,
+                                timings: None,
+                                created_at: Timestamp::from(SystemTime::now())
+                            };
+                            entry.update_timings(&mut message_slot);
+                            missed_statuses.push((parent, entry.parent_slot, msgid_gen.next(), Message::Slot(message_slot)));
+                            metrics::missed_status_message_inc(status);
+                        }
+                    }
+                }
+
+                // Statuses of parents are sent first, starting from the oldest one
+                for (parent, parent_parent_slot, msgid, message) in missed_statuses.into_iter().rev() {
+                    if status_finalized(&message, processed_first_slot) {
+                        router.send(parent, routed_at, ShardOp::Finalized { slot: parent, parent_slot: parent_parent_slot }).await?;
+                    }
+                    router.send(parent, routed_at, ShardOp::Message { msgid, message }).await?;
+                }
+                if status_finalized(&message, processed_first_slot) {
+                    router.send(slot, routed_at, ShardOp::Finalized { slot, parent_slot }).await?;
+                }
+                let seq = router.send(slot, routed_at, ShardOp::Message { msgid, message }).await?;
+                if let (Some(message), Some(info)) = (message_slot, slots.get_mut(&slot)) {
+                    info.messages_slots.push((seq, (msgid, message)));
+                }
+                metrics::geyser_loop_stage_observe("route", routed_at.elapsed());
+            }
+            _ = slot_watchdog_interval.tick() => {
+                let routed_at = Instant::now();
+                let notices = slot_watchdog.check().into_iter().chain(messages_rx.take_dropped_notice());
+                for message in notices {
+                    let msgid = msgid_gen.next();
+                    router.send(message.get_slot(), routed_at, ShardOp::Notice { msgid, message }).await?;
+                }
+            }
+            Some((commitment, replay_slot, tx)) = replay_stored_slots_rx.recv() => {
+                if let Some((slot, _)) = slots.first_key_value() {
+                    if replay_slot < *slot {
+                        let _ = tx.send(ReplayedResponse::Lagged(*slot));
+                        continue;
+                    }
+                }
+
+                // messages of slots are collected from shards, requests are handled by shards
+                // after all previously routed ops, so slot statuses and messages are consistent
+                let mut replayed_slots = vec![];
+                let mut shards_slots = vec![vec![]; router.shards.len()];
+                for (slot, info) in slots.range(replay_slot..) {
+                    let with_messages = commitment == CommitmentLevel::Processed
+                        || (commitment == CommitmentLevel::Finalized && info.finalized)
+                        || (commitment == CommitmentLevel::Confirmed && info.confirmed);
+                    if with_messages {
+                        shards_slots[(*slot % router.shards.len() as u64) as usize].push(*slot);
+                    }
+                    replayed_slots.push((*slot, info.messages_slots.clone(), with_messages));
+                }
+                let mut shards_rx = Vec::with_capacity(router.shards.len());
+                for (shard, slots) in router.shards.iter().zip(shards_slots) {
+                    let (tx, rx) = oneshot::channel();
+                    shard.send(ShardRequest::Replay { slots, tx }).await.map_err(|_error| ShardClosedError)?;
+                    shards_rx.push(rx);
+                }
+
+                tokio::spawn(async move {
+                    let mut shards_messages = HashMap::new();
+                    for rx in shards_rx {
+                        match rx.await {
+                            Ok(messages) => shards_messages.extend(messages),
+                            Err(_) => return,
+                        }
+                    }
+
+                    let mut replayed_messages = Vec::with_capacity(32_768);
+                    for (slot, messages_slots, with_messages) in replayed_slots {
+                        replayed_messages.extend(messages_slots);
+                        if with_messages {
+                            if let Some(messages) = shards_messages.remove(&slot) {
+                                replayed_messages.extend(messages);
+                            }
+                        }
+                    }
+                    // message ids are not ordered between shards, messages of one op keep order
+                    replayed_messages.sort_by_key(|(seq, _message)| *seq);
+                    let replayed_messages = replayed_messages.into_iter().map(|(_seq, message)| message).collect();
+                    let _ = tx.send(ReplayedResponse::Messages(replayed_messages));
+                });
+            }
+            Some(tx) = replay_stats_rx.recv() => {
+                let mut stats = ReplayStats {
+                    slots: slots.len(),
+                    first_slot: slots.keys().next().copied(),
+                    last_slot: slots.keys().next_back().copied(),
+                    messages: 0,
+                };
+                let mut shards_rx = Vec::with_capacity(router.shards.len());
+                for shard in router.shards.iter() {
+                    let (tx, rx) = oneshot::channel();
+                    shard.send(ShardRequest::Stats { tx }).await.map_err(|_error| ShardClosedError)?;
+                    shards_rx.push(rx);
+                }
+
+                tokio::spawn(async move {
+                    for rx in shards_rx {
+                        match rx.await {
+                            Ok(messages) => stats.messages += messages,
+                            Err(_) => return,
+                        }
+                    }
+                    let _ = tx.send(stats);
+                });
+            }
+            else => break,
+        }
+    }
+    Ok(())
+}
+
+/// Finalized status of slot after first processed slot, block of such slot should be sealed
+fn status_finalized(message: &Message, processed_first_slot: Option<Slot>) -> bool {
+    matches!(message, Message::Slot(msg) if msg.status == SlotStatus::Finalized
+        && processed_first_slot.is_some_and(|processed_first| msg.slot > processed_first))
+}
+
+async fn shard_loop(
+    mut requests_rx: mpsc::Receiver<ShardRequest>,
+    output_tx: mpsc::Sender<ShardOutput>,
+    msgid_gen: MessageId,
+    entries_signatures: EntriesSignatures,
+    block_reconstruction: bool,
+) {
+    let mut messages: HashMap<Slot, SlotMessages> = HashMap::new();
+    // number of kept messages, updated on every change for stats requests
+    let mut stored = 0;
+    let get_stored = |messages: &HashMap<Slot, SlotMessages>, slot: Slot| {
+        messages
+            .get(&slot)
+            .map_or(0, |slot_messages| slot_messages.stored)
+    };
+
+    while let Some(request) = requests_rx.recv().await {
+        match request {
+            ShardRequest::Update { seq, routed_at, op } => {
+                let started_at = Instant::now();
+                metrics::geyser_loop_stage_observe("shard_queue", started_at - routed_at);
+
+                let span = debug_span!("geyser_loop.shard", seq);
+                let updates = span.in_scope(|| match op {
+                    ShardOp::Remove { slot } => {
+                        if let Some(slot_messages) = messages.remove(&slot) {
+                            stored -= slot_messages.stored;
+                        }
+                        vec![]
+                    }
+                    ShardOp::Finalized { slot, parent_slot } => {
+                        if block_reconstruction {
+                            let stored_before = get_stored(&messages, slot);
+                            let updates = shard_finalized(
+                                &mut messages,
+                                &msgid_gen,
+                                &entries_signatures,
+                                seq,
+                                slot,
+                                parent_slot,
+                            );
+                            stored = stored + get_stored(&messages, slot) - stored_before;
+                            updates
+                        } else {
+                            vec![]
+                        }
+                    }
+                    ShardOp::Message { msgid, message } => {
+                        let slot = message.get_slot();
+                        let stored_before = get_stored(&messages, slot);
+                        let updates = shard_message(
+                            &mut messages,
+                            &msgid_gen,
+                            &entries_signatures,
+                            block_reconstruction,
+                            seq,
+                            msgid,
+                            message,
+                        );
+                        stored = stored + get_stored(&messages, slot) - stored_before;
+                        updates
+                    }
+                    ShardOp::Notice { msgid, message } => {
+                        vec![ShardUpdate::Notice((msgid, message))]
+                    }
+                });
+                metrics::geyser_loop_stage_observe("shard", started_at.elapsed());
+
+                let output = ShardOutput {
+                    seq,
+                    updates,
+                    processed_at: Instant::now(),
+                };
+                if output_tx.send(output).await.is_err() {
+                    break;
+                }
+            }
+            ShardRequest::Replay { slots, tx } => {
+                let replayed = slots
+                    .into_iter()
+                    .filter_map(|slot| {
+                        messages
+                            .get(&slot)
+                            .map(|slot_messages| (slot, slot_messages.messages_replay()))
+                    })
+                    .collect();
+                let _ = tx.send(replayed);
+            }
+            ShardRequest::Stats { tx } => {
+                let _ = tx.send(stored);
+            }
+        }
+    }
+}
+
+fn shard_finalized(
+    messages: &mut HashMap<Slot, SlotMessages>,
+    msgid_gen: &MessageId,
+    entries_signatures: &EntriesSignatures,
+    seq: u64,
+    slot: Slot,
+    parent_slot: Option<Slot>,
+) -> Vec<ShardUpdate> {
+    let slot_messages = messages
+        .entry(slot)
+        .or_insert_with(|| SlotMessages::new(entries_signatures));
+    if slot_messages.sealed {
+        return vec![];
+    }
+    // block is reported once, messages received after are unexpected
+    slot_messages.sealed = true;
+
+    let mut reasons = vec![];
+    if let Some(block_meta) = &slot_messages.block_meta {
+        let block_txn_count = block_meta.executed_transaction_count as usize;
+        let msg_txn_count = slot_messages.transactions.len();
+        if block_txn_count != msg_txn_count {
+            reasons.push("InvalidTxnCount");
+            if let Some(suppressed) = logger::check_repeated_error("InvalidTxnCount") {
+                error!(
+                    slot,
+                    error_kind = "InvalidTxnCount",
+                    suppressed;
+                    "failed to reconstruct #{slot} -- tx count: {block_txn_count} vs {msg_txn_count}{}",
+                    Suppressed(suppressed)
+                );
+            }
+        }
+        let block_entries_count = block_meta.entries_count as usize;
+        let msg_entries_count = slot_messages.entries.len();
+        if block_entries_count != msg_entries_count {
+            reasons.push("InvalidEntriesCount");
+            if let Some(suppressed) = logger::check_repeated_error("InvalidEntriesCount") {
+                error!(
+                    slot,
+                    error_kind = "InvalidEntriesCount",
+                    suppressed;
+                    "failed to reconstruct #{slot} -- entries count: {block_entries_count} vs {msg_entries_count}{}",
+                    Suppressed(suppressed)
+                );
+            }
+        }
+    } else {
+        reasons.push("NoBlockMeta");
+    }
+    let reason = reasons.join(",");
+
+    metrics::update_invalid_blocks(format!("failed reconstruct {reason}"));
+    // incomplete block is kept for replay and sent like sealed block, finalized status follows
+    let message = (
+        msgid_gen.next(),
+        slot_messages.to_incomplete_block(slot, parent_slot),
+    );
+    slot_messages.push(seq, message.clone());
+    vec![ShardUpdate::Message {
+        message,
+        confirmed: slot_messages.messages_from(slot_messages.confirmed_at),
+        finalized: slot_messages.messages_from(slot_messages.finalized_at),
+    }]
+}
+
+fn shard_message(
+    messages: &mut HashMap<Slot, SlotMessages>,
+    msgid_gen: &MessageId,
+    entries_signatures: &EntriesSignatures,
+    block_reconstruction: bool,
+    seq: u64,
+    msgid: u64,
+    message: Message,
+) -> Vec<ShardUpdate> {
+    let slot_messages = messages
+        .entry(message.get_slot())
+        .or_insert_with(|| SlotMessages::new(entries_signatures));
+
+    if let Message::Slot(msg) = &message {
+        let (confirmed, finalized) = match msg.status {
+            SlotStatus::Processed
+            | SlotStatus::FirstShredReceived
+            | SlotStatus::Completed
+            | SlotStatus::CreatedBank
+            | SlotStatus::Dead => (Vec::with_capacity(1), Vec::with_capacity(1)),
+            SlotStatus::Confirmed => {
+                if !slot_messages.sealed {
+                    slot_messages.confirmed_at = Some(slot_messages.messages.len());
+                }
+                let vec = slot_messages.messages_from(Some(0));
+                (vec, Vec::with_capacity(1))
+            }
+            SlotStatus::Finalized => {
+                if !slot_messages.sealed {
+                    slot_messages.finalized_at = Some(slot_messages.messages.len());
+                }
+                let vec = slot_messages.messages_from(Some(0));
+                (Vec::with_capacity(1), vec)
+            }
+        };
+        return vec![ShardUpdate::Slot {
+            message: (msgid, message),
+            confirmed,
+            finalized,
+        }];
+    }
+
+    slot_messages.push(seq, (msgid, message.clone()));
+
+    // If we already build Block message, new message will be a problem
+    if slot_messages.sealed
+        && !(matches!(&message, Message::Entry(_)) && slot_messages.entries_count == 0)
+    {
+        let kind = match &message {
+            Message::Slot(_) => "Slot",
+            Message::Account(_) => "Account",
+            Message::Transaction(_) => "Transaction",
+            Message::Entry(_) => "Entry",
+            Message::BlockMeta(_) => "BlockMeta",
+            Message::Block(_) => "Block",
+            Message::Notice(_) => "Notice",
+        };
+        metrics::update_invalid_blocks(format!("unexpected message {kind}"));
+    }
+
+    let mut sealed_block_msg = None;
+    let mut signed_entries_msgs = vec![];
+    match &message {
+        Message::BlockMeta(msg) => {
+            if slot_messages.block_meta.is_some() {
+                metrics::update_invalid_blocks("unexpected message: BlockMeta (duplicate)");
+            }
+            slot_messages.block_meta = Some(Arc::clone(msg));
+            if block_reconstruction {
+                sealed_block_msg = slot_messages.try_seal(seq, msgid_gen);
+            }
+        }
+        Message::Transaction(msg) => {
+            if block_reconstruction {
+                slot_messages
+                    .transactions
+                    .push(Arc::clone(&msg.transaction));
+                sealed_block_msg = slot_messages.try_seal(seq, msgid_gen);
+            }
+            if slot_messages.sign_entries {
+                slot_messages
+                    .transactions_signatures
+                    .insert(msg.transaction.index, msg.transaction.signature);
+                signed_entries_msgs = slot_messages.try_sign_entries(seq, msgid_gen);
+            }
+        }
+        // Dedup accounts by max write_version
+        Message::Account(msg) => {
+            let write_version = msg.account.write_version;
+            let msg_index = slot_messages.messages.len() - 1;
+            if let Some(entry) = slot_messages.accounts_dedup.get_mut(&msg.account.pubkey) {
+                if entry.0 < write_version {
+                    // We can replace the message, but in this case we will lose the order
+                    slot_messages.messages[entry.1] = None;
+                    slot_messages.stored -= 1;
+                    *entry = (write_version, msg_index);
+                }
+            } else {
+                slot_messages
+                    .accounts_dedup
+                    .insert(msg.account.pubkey, (write_version, msg_index));
+            }
+        }
+        Message::Entry(msg) => {
+            if block_reconstruction {
+                slot_messages.entries.push(Arc::clone(msg));
+                sealed_block_msg = slot_messages.try_seal(seq, msgid_gen);
+            }
+            // entries without transactions are sent to all subscriptions as is
+            if slot_messages.sign_entries && msg.executed_transaction_count > 0 {
+                slot_messages.entries_unsigned.push(Arc::clone(msg));
+                signed_entries_msgs = slot_messages.try_sign_entries(seq, msgid_gen);
+            }
+        }
+        _ => {}
+    }
+
+    // without blocks messages received after commitment status are not delayed
+    let to_update = |message: (u64, Message)| {
+        let (confirmed, finalized) = if block_reconstruction {
+            (vec![], vec![])
+        } else {
+            let confirmed = slot_messages.confirmed_at.map(|_| message.clone());
+            let finalized = slot_messages.finalized_at.map(|_| message.clone());
+            (
+                confirmed.into_iter().collect(),
+                finalized.into_iter().collect(),
+            )
+        };
+        ShardUpdate::Message {
+            message,
+            confirmed,
+            finalized,
+        }
+    };
+    let mut updates = Vec::with_capacity(2 + signed_entries_msgs.len());
+    updates.push(to_update((msgid, message)));
+    // messages received before confirmed and finalized statuses are sent with the block
+    if let Some(sealed_block_msg) = sealed_block_msg {
+        updates.push(ShardUpdate::Message {
+            message: sealed_block_msg,
+            confirmed: slot_messages.messages_from(slot_messages.confirmed_at),
+            finalized: slot_messages.messages_from(slot_messages.finalized_at),
+        });
+    }
+    updates.extend(signed_entries_msgs.into_iter().map(to_update));
+    updates
+}
+
+/// Broadcasts batches of messages with routes for streams in the filter index,
+/// time from plugin to broadcast is observed for processed messages
+struct Broadcaster {
+    tx: broadcast::Sender<BroadcastedMessage>,
+    filter_index: Option<Arc<FilterIndex>>,
+}
+
+impl Broadcaster {
+    fn send(&self, commitment: CommitmentLevel, messages: Vec<(u64, Message)>) {
+        let _span = debug_span!(
+            "geyser_loop.broadcast",
+            commitment = ?commitment,
+            messages = messages.len()
+        )
+        .entered();
+        let routes = self
+            .filter_index
+            .as_ref()
+            .and_then(|filter_index| filter_index.get_routes(&messages));
+        let message = BroadcastedMessage::new(commitment, messages, routes);
+        if commitment == CommitmentLevel::Processed {
+            for (_msgid, msg) in message.messages.iter() {
+                if let Ok(created_at) = SystemTime::try_from(msg.get_created_at()) {
+                    metrics::updates_latency_observe(
+                        "geyser_loop",
+                        message
+                            .broadcasted_at
+                            .duration_since(created_at)
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+        }
+        let _ = self.tx.send(message);
+    }
+}
+
+async fn sequencer_loop(mut output_rx: mpsc::Receiver<ShardOutput>, broadcaster: Broadcaster) {
+    let mut next_seq = 0;
+    let mut pending = BTreeMap::new();
+    let mut processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+    let processed_sleep = sleep(PROCESSED_MESSAGES_SLEEP);
+    tokio::pin!(processed_sleep);
+
+    loop {
+        tokio::select! {
+            output = output_rx.recv() => {
+                // all shards are stopped
+                let Some(output) = output else {
+                    break;
+                };
+                pending.insert(output.seq, output);
+                while let Some(output) = pending.remove(&next_seq) {
+                    next_seq += 1;
+                    metrics::geyser_loop_stage_observe("reorder", output.processed_at.elapsed());
+
+                    let started_at = Instant::now();
+                    for update in output.updates {
+                        let flush = match update {
+                            ShardUpdate::Slot { message, mut confirmed, mut finalized } => {
+                                processed_messages.push(message.clone());
+                                confirmed.push(message.clone());
+                                finalized.push(message);
+                                broadcaster.send(CommitmentLevel::Processed, processed_messages);
+                                broadcaster.send(CommitmentLevel::Confirmed, confirmed);
+                                broadcaster.send(CommitmentLevel::Finalized, finalized);
+                                processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+                                true
+                            }
+                            ShardUpdate::Message { message, confirmed, finalized } => {
+                                processed_messages.push(message);
+                                let flush = processed_messages.len() >= PROCESSED_MESSAGES_MAX
+                                    || !confirmed.is_empty()
+                                    || !finalized.is_empty();
+                                if flush {
+                                    broadcaster.send(CommitmentLevel::Processed, processed_messages);
+                                    processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+                                }
+                                if !confirmed.is_empty() {
+                                    broadcaster.send(CommitmentLevel::Confirmed, confirmed);
+                                }
+                                if !finalized.is_empty() {
+                                    broadcaster.send(CommitmentLevel::Finalized, finalized);
+                                }
+                                flush
+                            }
+                            ShardUpdate::Notice(message) => {
+                                processed_messages.push(message.clone());
+                                broadcaster.send(CommitmentLevel::Processed, processed_messages);
+                                broadcaster.send(CommitmentLevel::Confirmed, vec![message.clone()]);
+                                broadcaster.send(CommitmentLevel::Finalized, vec![message]);
+                                processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+                                true
+                            }
+                        };
+                        if flush {
+                            processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
+                        }
+                    }
+                    metrics::geyser_loop_stage_observe("broadcast", started_at.elapsed());
+                }
+            }
+            () = &mut processed_sleep => {
+                if !processed_messages.is_empty() {
+                    broadcaster.send(CommitmentLevel::Processed, processed_messages);
+                    processed_messages = Vec::with_capacity(PROCESSED_MESSAGES_MAX);
+                }
+                processed_sleep.as_mut().reset(Instant::now() + PROCESSED_MESSAGES_SLEEP);
+            }
+            else => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            geyser_loop, sequencer_loop, shard_loop, Broadcaster, EntriesSignatures, MessageId,
+            ReplayStats, ReplayedResponse, ShardOp, ShardRouter,
+        },
+        crate::{
+            config::{ConfigGrpcIngress, ConfigGrpcPipeline},
+            health::GrpcHealth,
+            ingress,
+            watchdog::SlotWatchdog,
+        },
+        prost_types::Timestamp,
+        solana_pubkey::Pubkey,
+        std::{collections::BTreeMap, sync::Arc, time::Duration},
+        tokio::{
+            sync::{broadcast, mpsc, oneshot},
+            time::{timeout, Instant},
+        },
+        yellowstone_grpc_proto::{
+            plugin::message::{
+                CommitmentLevel, Message, MessageAccount, MessageAccountInfo, MessageBlockMeta,
+                MessageEntry, MessageSlot, MessageTransaction, MessageTransactionInfo, SlotStatus,
+            },
+            prelude::SubscribeUpdateBlockMeta,
+        },
+    };
+
+    fn slot(slot: u64, status: SlotStatus) -> Message {
+        Message::Slot(MessageSlot {
+            slot,
+            parent: Some(slot - 1),
+            status,
+            dead_error: None,
+            timings: None,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    fn block_meta(slot: u64) -> Message {
+        Message::BlockMeta(Arc::new(MessageBlockMeta {
+            block_meta: SubscribeUpdateBlockMeta {
+                slot,
+                parent_slot: slot - 1,
+                executed_transaction_count: 2,
+                entries_count: 1,
+                ..Default::default()
+            },
+            created_at: Timestamp::default(),
+        }))
+    }
+
+    fn transaction(slot: u64, index: usize) -> Message {
+        Message::Transaction(MessageTransaction {
+            transaction: Arc::new(MessageTransactionInfo {
+                signature: Default::default(),
+                is_vote: false,
+                transaction: Default::default(),
+                meta: Default::default(),
+                index,
+                account_keys: Default::default(),
+            }),
+            slot,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    fn entry(slot: u64) -> Message {
+        Message::Entry(Arc::new(MessageEntry {
+            slot,
+            index: 0,
+            num_hashes: 0,
+            hash: Default::default(),
+            executed_transaction_count: 2,
+            starting_transaction_index: 0,
+            transaction_signatures: None,
+            created_at: Timestamp::default(),
+        }))
+    }
+
+    fn account(slot: u64, key: u8, write_version: u64) -> Message {
+        Message::Account(MessageAccount {
+            account: Arc::new(MessageAccountInfo {
+                pubkey: Pubkey::new_from_array([key; 32]),
+                lamports: 0,
+                owner: Default::default(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![],
+                write_version,
+                txn_signature: None,
+            }),
+            slot,
+            is_startup: false,
+            created_at: Timestamp::default(),
+        })
+    }
+
+    /// Message ids and creation time of generated messages depend on the run
+    fn describe(message: &Message) -> String {
+        match message {
+            Message::Slot(msg) => format!("slot {} {:?}", msg.slot, msg.status),
+            Message::Account(msg) => format!(
+                "account {} {} {}",
+                msg.slot, msg.account.pubkey, msg.account.write_version
+            ),
+            Message::Transaction(msg) => {
+                format!("transaction {} {}", msg.slot, msg.transaction.index)
+            }
+            Message::Entry(msg) => format!("entry {} {}", msg.slot, msg.index),
+            Message::BlockMeta(msg) => format!("block_meta {}", msg.slot),
+            Message::Block(msg) => format!(
+                "block {} {} {}",
+                msg.meta.slot,
+                msg.transactions.len(),
+                msg.incomplete.is_some()
+            ),
+            Message::Notice(_) => "notice".to_owned(),
+        }
+    }
+
+    /// Slots from 100 to 103 with data interleaved between slots, only some of statuses are
+    /// received, block of slot 102 is incomplete and account in slot 101 is updated twice
+    fn messages() -> Vec<Message> {
+        let mut messages = vec![];
+        for slot in 100..=103 {
+            messages.push(self::slot(slot, SlotStatus::Processed));
+        }
+        let slots_messages = (100..=103)
+            .map(|slot| {
+                let mut messages = vec![
+                    account(slot, slot as u8, 1),
+                    transaction(slot, 0),
+                    account(slot, slot as u8, 2),
+                    entry(slot),
+                    block_meta(slot),
+                ];
+                if slot != 102 {
+                    messages.push(transaction(slot, 1));
+                }
+                messages
+            })
+            .collect::<Vec<_>>();
+        for index in 0..6 {
+            for slot_messages in slots_messages.iter() {
+                messages.extend(slot_messages.get(index).cloned());
+            }
+        }
+        messages.push(slot(101, SlotStatus::Confirmed));
+        messages.push(account(103, 103, 3));
+        messages.push(slot(103, SlotStatus::Confirmed));
+        messages.push(slot(103, SlotStatus::Finalized));
+        messages
+    }
+
+    async fn run(
+        shards: usize,
+    ) -> (
+        BTreeMap<CommitmentLevel, Vec<String>>,
+        Vec<String>,
+        ReplayStats,
+    ) {
+        let (messages_tx, messages_rx) = ingress::channel(ConfigGrpcIngress::default());
+        let (broadcast_tx, mut broadcast_rx) = broadcast::channel(1_024);
+        let (replay_tx, replay_rx) = mpsc::channel(1);
+        let (replay_stats_tx, replay_stats_rx) = mpsc::channel(1);
+        let (health, _health_server) = GrpcHealth::new(false);
+        tokio::spawn(geyser_loop(
+            ConfigGrpcPipeline {
+                shards,
+                block_reconstruction: true,
+            },
+            messages_rx,
+            None,
+            broadcast_tx,
+            None,
+            Some(replay_rx),
+            None,
+            100,
+            replay_stats_rx,
+            SlotWatchdog::new(Default::default(), health),
+            EntriesSignatures::default(),
+        ));
+
+        for message in messages() {
+            messages_tx.send(message).unwrap();
+        }
+        let mut broadcasted = BTreeMap::<_, Vec<_>>::new();
+        while let Ok(Ok(message)) = timeout(Duration::from_millis(200), broadcast_rx.recv()).await {
+            broadcasted
+                .entry(message.commitment)
+                .or_default()
+                .extend(message.messages.iter().map(|(_msgid, msg)| describe(msg)));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        replay_tx
+            .send((CommitmentLevel::Processed, 100, tx))
+            .await
+            .unwrap();
+        let replayed = match rx.await.unwrap() {
+            ReplayedResponse::Messages(messages) => messages,
+            ReplayedResponse::Lagged(slot) => panic!("lagged: {slot}"),
+        };
+        let replayed = replayed.iter().map(|(_msgid, msg)| describe(msg)).collect();
+
+        let (tx, rx) = oneshot::channel();
+        replay_stats_tx.send(tx).await.unwrap();
+        let stats = rx.await.unwrap();
+
+        (broadcasted, replayed, stats)
+    }
+
+    #[tokio::test]
+    async fn test_shards_same_output() {
+        let (broadcasted, replayed, stats) = run(1).await;
+        assert_eq!(broadcasted.len(), 3);
+        assert!(broadcasted[&CommitmentLevel::Processed].contains(&"block 102 1 true".to_owned()));
+        assert!(broadcasted[&CommitmentLevel::Finalized].contains(&"block 103 2 false".to_owned()));
+        // incomplete block is sent once per commitment and kept for replay
+        for messages in broadcasted.values().chain([&replayed]) {
+            let count = messages
+                .iter()
+                .filter(|message| *message == "block 102 1 true")
+                .count();
+            assert_eq!(count, 1);
+        }
+        // 24 messages, 3 sealed blocks and incomplete block, 5 accounts replaced by newer write version
+        assert_eq!(stats.messages, 23);
+        assert_eq!(stats.slots, 4);
+        for shards in [2, 3, 4] {
+            let (broadcasted_shards, replayed_shards, stats_shards) = run(shards).await;
+            assert_eq!(broadcasted_shards, broadcasted, "shards: {shards}");
+            assert_eq!(replayed_shards, replayed, "shards: {shards}");
+            assert_eq!(stats_shards.messages, stats.messages, "shards: {shards}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shard_stopped() {
+        let msgid_gen = MessageId::default();
+        let (output_tx, output_rx) = mpsc::channel(16);
+        let (shard_tx, shard_rx) = mpsc::channel(16);
+        tokio::spawn(shard_loop(
+            shard_rx,
+            output_tx,
+            msgid_gen.clone(),
+            EntriesSignatures::default(),
+            true,
+        ));
+        let (killed_shard_tx, killed_shard_rx) = mpsc::channel(16);
+        drop(killed_shard_rx);
+        let mut router = ShardRouter {
+            shards: vec![shard_tx, killed_shard_tx],
+            seq: 0,
+        };
+        let (broadcast_tx, mut broadcast_rx) = broadcast::channel(16);
+        let sequencer = tokio::spawn(sequencer_loop(
+            output_rx,
+            Broadcaster {
+                tx: broadcast_tx,
+                filter_index: None,
+            },
+        ));
+
+        // slot 101 is routed to killed shard
+        for (number, is_ok) in [(100, true), (101, false)] {
+            let op = ShardOp::Message {
+                msgid: msgid_gen.next(),
+                message: slot(number, SlotStatus::Processed),
+            };
+            let result = router.send(number, Instant::now(), op).await;
+            assert_eq!(result.is_ok(), is_ok, "slot: {number}");
+        }
+        assert_eq!(router.seq, 1);
+
+        // geyser loop stops on error, sequencer is stopped with the rest of shards
+        drop(router);
+        timeout(Duration::from_secs(1), sequencer)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut broadcasted = vec![];
+        while let Ok(message) = broadcast_rx.recv().await {
+            if message.commitment == CommitmentLevel::Processed {
+                broadcasted.extend(message.messages.iter().map(|(_msgid, msg)| describe(msg)));
+            }
+        }
+        assert_eq!(broadcasted, vec!["slot 100 Processed".to_owned()]);
+    }
+}
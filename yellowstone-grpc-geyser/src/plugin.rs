@@ -1,15 +1,21 @@
 use {
     crate::{
-        config::Config,
+        config::{Config, ConfigIngestion},
         grpc::GrpcService,
-        metrics::{self, PrometheusService},
+        health::GrpcHealth,
+        ingress::IngressSender,
+        logger,
+        metrics::{self, DebugClientStatuses, PrometheusService},
+        otel,
     },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
         GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, ReplicaBlockInfoVersions,
         ReplicaEntryInfoVersions, ReplicaTransactionInfoVersions, Result as PluginResult,
         SlotStatus,
     },
+    solana_pubkey::{pubkey, Pubkey},
     std::{
+        collections::HashSet,
         concat, env,
         sync::{
             atomic::{AtomicBool, Ordering},
@@ -19,28 +25,63 @@ use {
     },
     tokio::{
         runtime::{Builder, Runtime},
-        sync::{mpsc, Notify},
+        sync::Notify,
     },
+    tracing::debug_span,
     yellowstone_grpc_proto::plugin::message::{
         Message, MessageAccount, MessageBlockMeta, MessageEntry, MessageSlot, MessageTransaction,
     },
 };
 
+const VOTE_PROGRAM_ID: Pubkey = pubkey!("Vote111111111111111111111111111111111111111");
+
+/// Ingestion filters, applied before conversion of geyser data into messages
+#[derive(Debug)]
+struct PluginIngestion {
+    skip_vote_transactions: bool,
+    skip_startup_accounts: bool,
+    skip_owners: HashSet<Pubkey>,
+}
+
+impl PluginIngestion {
+    fn new(config: ConfigIngestion) -> Self {
+        let mut skip_owners = config.skip_owners;
+        if config.skip_vote_accounts {
+            skip_owners.insert(VOTE_PROGRAM_ID);
+        }
+        Self {
+            skip_vote_transactions: config.skip_vote_transactions,
+            skip_startup_accounts: config.skip_startup_accounts,
+            skip_owners,
+        }
+    }
+
+    fn is_account_skipped(&self, owner: &[u8], is_startup: bool) -> bool {
+        (is_startup && self.skip_startup_accounts)
+            || (!self.skip_owners.is_empty()
+                && Pubkey::try_from(owner).is_ok_and(|owner| self.skip_owners.contains(&owner)))
+    }
+
+    const fn is_transaction_skipped(&self, is_vote: bool) -> bool {
+        is_vote && self.skip_vote_transactions
+    }
+}
+
 #[derive(Debug)]
 pub struct PluginInner {
     runtime: Runtime,
     snapshot_channel: Mutex<Option<crossbeam_channel::Sender<Box<Message>>>>,
     snapshot_channel_closed: AtomicBool,
-    grpc_channel: mpsc::UnboundedSender<Message>,
+    grpc_channel: IngressSender,
     grpc_shutdown: Arc<Notify>,
+    grpc_health: Arc<GrpcHealth>,
     prometheus: PrometheusService,
+    ingestion: PluginIngestion,
 }
 
 impl PluginInner {
     fn send_message(&self, message: Message) {
-        if self.grpc_channel.send(message).is_ok() {
-            metrics::message_queue_size_inc();
-        }
+        let _ = self.grpc_channel.send(message);
     }
 }
 
@@ -67,11 +108,12 @@ impl GeyserPlugin for Plugin {
     fn on_load(&mut self, config_file: &str, is_reload: bool) -> PluginResult<()> {
         let config = Config::load_from_file(config_file)?;
 
-        // Validate gRPC configuration
-        config.grpc.validate()?;
+        config.validate()?;
 
         // Setup logger
-        solana_logger::setup_with_default(&config.log.level);
+        logger::setup(&config.log);
+
+        let ingestion = PluginIngestion::new(config.ingestion.clone());
 
         // Create inner
         let mut builder = Builder::new_multi_thread();
@@ -94,27 +136,29 @@ impl GeyserPlugin for Plugin {
             .build()
             .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
 
-        let (snapshot_channel, grpc_channel, grpc_shutdown, prometheus) =
+        let (snapshot_channel, grpc_channel, grpc_shutdown, grpc_health, prometheus) =
             runtime.block_on(async move {
-                let (debug_client_tx, debug_client_rx) = mpsc::unbounded_channel();
-                let (snapshot_channel, grpc_channel, grpc_shutdown) = GrpcService::create(
+                if let Some(config) = config.tracing {
+                    otel::init(config)
+                        .map_err(|error| GeyserPluginError::Custom(format!("{error:?}").into()))?;
+                }
+                let debug_clients = config.debug_clients_http.then(DebugClientStatuses::new);
+                let (snapshot_channel, grpc_channel, grpc_shutdown, grpc_health) = GrpcService::create(
                     config.tokio,
                     config.grpc,
-                    config.debug_clients_http.then_some(debug_client_tx),
+                    debug_clients.clone(),
                     is_reload,
                 )
                 .await
                 .map_err(|error| GeyserPluginError::Custom(format!("{error:?}").into()))?;
-                let prometheus = PrometheusService::new(
-                    config.prometheus,
-                    config.debug_clients_http.then_some(debug_client_rx),
-                )
-                .await
-                .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
+                let prometheus = PrometheusService::new(config.prometheus, debug_clients)
+                    .await
+                    .map_err(|error| GeyserPluginError::Custom(Box::new(error)))?;
                 Ok::<_, GeyserPluginError>((
                     snapshot_channel,
                     grpc_channel,
                     grpc_shutdown,
+                    grpc_health,
                     prometheus,
                 ))
             })?;
@@ -125,7 +169,9 @@ impl GeyserPlugin for Plugin {
             snapshot_channel_closed: AtomicBool::new(false),
             grpc_channel,
             grpc_shutdown,
+            grpc_health,
             prometheus,
+            ingestion,
         });
 
         Ok(())
@@ -136,6 +182,7 @@ impl GeyserPlugin for Plugin {
             inner.grpc_shutdown.notify_one();
             drop(inner.grpc_channel);
             inner.prometheus.shutdown();
+            otel::flush();
             inner.runtime.shutdown_timeout(Duration::from_secs(30));
         }
     }
@@ -146,6 +193,7 @@ impl GeyserPlugin for Plugin {
         slot: u64,
         is_startup: bool,
     ) -> PluginResult<()> {
+        let _span = debug_span!("plugin.update_account", slot, is_startup).entered();
         self.with_inner(|inner| {
             let account = match account {
                 ReplicaAccountInfoVersions::V0_0_1(_info) => {
@@ -156,6 +204,9 @@ impl GeyserPlugin for Plugin {
                 }
                 ReplicaAccountInfoVersions::V0_0_3(info) => info,
             };
+            if inner.ingestion.is_account_skipped(account.owner, is_startup) {
+                return Ok(());
+            }
 
             if is_startup {
                 if let Some(channel) = inner.snapshot_channel.lock().unwrap().as_ref() {
@@ -185,6 +236,7 @@ impl GeyserPlugin for Plugin {
     fn notify_end_of_startup(&self) -> PluginResult<()> {
         self.with_inner(|inner| {
             let _snapshot_channel = inner.snapshot_channel.lock().unwrap().take();
+            inner.grpc_health.set_startup_done();
             Ok(())
         })
     }
@@ -195,6 +247,7 @@ impl GeyserPlugin for Plugin {
         parent: Option<u64>,
         status: &SlotStatus,
     ) -> PluginResult<()> {
+        let _span = debug_span!("plugin.update_slot_status", slot, status = ?status).entered();
         self.with_inner(|inner| {
             let message = Message::Slot(MessageSlot::from_geyser(slot, parent, status));
             inner.send_message(message);
@@ -208,6 +261,7 @@ impl GeyserPlugin for Plugin {
         transaction: ReplicaTransactionInfoVersions<'_>,
         slot: u64,
     ) -> PluginResult<()> {
+        let _span = debug_span!("plugin.notify_transaction", slot).entered();
         self.with_inner(|inner| {
             let transaction = match transaction {
                 ReplicaTransactionInfoVersions::V0_0_1(_info) => {
@@ -215,6 +269,9 @@ impl GeyserPlugin for Plugin {
                 }
                 ReplicaTransactionInfoVersions::V0_0_2(info) => info,
             };
+            if inner.ingestion.is_transaction_skipped(transaction.is_vote) {
+                return Ok(());
+            }
 
             let message = Message::Transaction(MessageTransaction::from_geyser(transaction, slot));
             inner.send_message(message);
@@ -224,6 +281,7 @@ impl GeyserPlugin for Plugin {
     }
 
     fn notify_entry(&self, entry: ReplicaEntryInfoVersions) -> PluginResult<()> {
+        let _span = debug_span!("plugin.notify_entry").entered();
         self.with_inner(|inner| {
             #[allow(clippy::infallible_destructuring_match)]
             let entry = match entry {
@@ -241,6 +299,7 @@ impl GeyserPlugin for Plugin {
     }
 
     fn notify_block_metadata(&self, blockinfo: ReplicaBlockInfoVersions<'_>) -> PluginResult<()> {
+        let _span = debug_span!("plugin.notify_block_metadata").entered();
         self.with_inner(|inner| {
             let blockinfo = match blockinfo {
                 ReplicaBlockInfoVersions::V0_0_1(_info) => {
@@ -289,3 +348,55 @@ pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
     let plugin: Box<dyn GeyserPlugin> = Box::new(plugin);
     Box::into_raw(plugin)
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{PluginIngestion, VOTE_PROGRAM_ID},
+        crate::config::ConfigIngestion,
+        solana_pubkey::Pubkey,
+        std::collections::HashSet,
+    };
+
+    #[test]
+    fn test_ingestion_skip_accounts() {
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        let ingestion = PluginIngestion::new(ConfigIngestion::default());
+        assert!(!ingestion.is_account_skipped(VOTE_PROGRAM_ID.as_ref(), false));
+        assert!(!ingestion.is_account_skipped(owner.as_ref(), true));
+
+        let ingestion = PluginIngestion::new(ConfigIngestion {
+            skip_vote_accounts: true,
+            skip_owners: HashSet::from([owner]),
+            ..Default::default()
+        });
+        assert!(ingestion.is_account_skipped(VOTE_PROGRAM_ID.as_ref(), false));
+        assert!(ingestion.is_account_skipped(owner.as_ref(), false));
+        assert!(!ingestion.is_account_skipped(other.as_ref(), false));
+        assert!(!ingestion.is_account_skipped(other.as_ref(), true));
+        assert!(!ingestion.is_account_skipped(&[0; 16], false));
+
+        let ingestion = PluginIngestion::new(ConfigIngestion {
+            skip_startup_accounts: true,
+            ..Default::default()
+        });
+        assert!(ingestion.is_account_skipped(other.as_ref(), true));
+        assert!(!ingestion.is_account_skipped(other.as_ref(), false));
+    }
+
+    #[test]
+    fn test_ingestion_skip_vote_transactions() {
+        let ingestion = PluginIngestion::new(ConfigIngestion::default());
+        assert!(!ingestion.is_transaction_skipped(true));
+        assert!(!ingestion.is_transaction_skipped(false));
+
+        let ingestion = PluginIngestion::new(ConfigIngestion {
+            skip_vote_transactions: true,
+            ..Default::default()
+        });
+        assert!(ingestion.is_transaction_skipped(true));
+        assert!(!ingestion.is_transaction_skipped(false));
+    }
+}
@@ -0,0 +1,392 @@
+use {
+    crate::{config::ConfigGrpcServerTls, metrics, peer::PeerConnectInfo},
+    anyhow::Context,
+    futures::stream::{Stream, StreamExt},
+    log::{debug, error, info},
+    std::{
+        collections::HashMap,
+        fs,
+        io::{self, Cursor},
+        pin::pin,
+        sync::{Arc, RwLock},
+        time::{Duration, SystemTime},
+    },
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        sync::mpsc,
+        time::{sleep, timeout},
+    },
+    tokio_rustls::{
+        rustls::{
+            crypto::ring::default_provider, server::WebPkiClientVerifier, RootCertStore,
+            ServerConfig,
+        },
+        server::TlsStream,
+        TlsAcceptor,
+    },
+    tokio_stream::wrappers::ReceiverStream,
+    tonic::{transport::server::TlsConnectInfo, Request},
+    x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer},
+};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Identity of clients with certificates without names from `client_identities`
+pub const UNKNOWN_CLIENT_IDENTITY: &str = "unknown";
+
+/// rustls server config, re-created on changes of certificate files.
+/// Only new handshakes use reloaded config, established connections are not affected.
+#[derive(Debug, Clone)]
+pub struct TlsServerConfig {
+    server_config: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl TlsServerConfig {
+    pub fn new(config: ConfigGrpcServerTls) -> anyhow::Result<Self> {
+        let server_config = Arc::new(RwLock::new(Arc::new(Self::load(&config)?)));
+
+        // watcher stopped once all servers dropped config
+        let server_config_weak = Arc::downgrade(&server_config);
+        tokio::spawn(async move {
+            let mut modified = Self::get_modified(&config);
+            loop {
+                sleep(config.reload_interval).await;
+                let Some(server_config) = server_config_weak.upgrade() else {
+                    break;
+                };
+
+                let new_modified = Self::get_modified(&config);
+                if new_modified == modified {
+                    continue;
+                }
+                modified = new_modified;
+
+                match Self::load(&config) {
+                    Ok(new_server_config) => {
+                        *server_config.write().expect("poisoned") = Arc::new(new_server_config);
+                        metrics::tls_reload_inc(true);
+                        info!("TLS certificates reloaded");
+                    }
+                    Err(error) => {
+                        metrics::tls_reload_inc(false);
+                        error!("failed to reload TLS certificates: {error:#}");
+                    }
+                }
+            }
+        });
+
+        Ok(Self { server_config })
+    }
+
+    fn get_modified(config: &ConfigGrpcServerTls) -> Vec<Option<SystemTime>> {
+        [
+            Some(&config.cert_path),
+            Some(&config.key_path),
+            config.ca_path.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .collect()
+    }
+
+    fn load(config: &ConfigGrpcServerTls) -> anyhow::Result<ServerConfig> {
+        let cert = fs::read(&config.cert_path).context("failed to load cert file")?;
+        let cert = rustls_pemfile::certs(&mut Cursor::new(cert))
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to parse cert file")?;
+        let key = fs::read(&config.key_path).context("failed to load key file")?;
+        let key = rustls_pemfile::private_key(&mut Cursor::new(key))
+            .context("failed to parse key file")?
+            .context("no private key found in key file")?;
+
+        let provider = Arc::new(default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .context("failed to set TLS protocol versions")?;
+        let builder = if let Some(ca_path) = &config.ca_path {
+            let ca = fs::read(ca_path).context("failed to load ca file")?;
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut Cursor::new(ca)) {
+                roots
+                    .add(cert.context("failed to parse ca file")?)
+                    .context("failed to add ca certificate")?;
+            }
+            let mut verifier =
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            if config.client_auth_optional {
+                verifier = verifier.allow_unauthenticated();
+            }
+            builder.with_client_cert_verifier(
+                verifier
+                    .build()
+                    .context("failed to create client cert verifier")?,
+            )
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        let mut server_config = builder
+            .with_single_cert(cert, key)
+            .context("invalid cert or key")?;
+        server_config.alpn_protocols.push(b"h2".to_vec());
+        Ok(server_config)
+    }
+
+    /// Accept TLS connections on top of `incoming`, handshakes are done concurrently
+    pub fn incoming<IO>(
+        &self,
+        incoming: impl Stream<Item = io::Result<IO>> + Send + 'static,
+    ) -> ReceiverStream<io::Result<TlsStream<IO>>>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(64);
+        let server_config = Arc::clone(&self.server_config);
+        tokio::spawn(async move {
+            let mut incoming = pin!(incoming);
+            loop {
+                let stream = tokio::select! {
+                    () = tx.closed() => break,
+                    item = incoming.next() => match item {
+                        Some(Ok(stream)) => stream,
+                        Some(Err(error)) => {
+                            if tx.send(Err(error)).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        None => break,
+                    },
+                };
+
+                let acceptor =
+                    TlsAcceptor::from(Arc::clone(&server_config.read().expect("poisoned")));
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = tx.send(Ok(stream)).await;
+                        }
+                        Ok(Err(error)) => debug!("TLS handshake failed: {error}"),
+                        Err(_) => debug!("TLS handshake timeout"),
+                    }
+                });
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+/// Names from certificate in order of priority: SAN (DNS, URI) and subject CN
+fn get_certificate_names(cert: &X509Certificate<'_>) -> Vec<String> {
+    let mut names = vec![];
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in san.value.general_names.iter() {
+            match name {
+                GeneralName::DNSName(name) | GeneralName::URI(name) => {
+                    names.push((*name).to_owned())
+                }
+                _ => {}
+            }
+        }
+    }
+    for cn in cert.subject().iter_common_name() {
+        if let Ok(cn) = cn.as_str() {
+            names.push(cn.to_owned());
+        }
+    }
+    names
+}
+
+/// Identity of the client from verified TLS client certificate (mTLS)
+pub fn get_client_identity<T>(
+    request: &Request<T>,
+    identities: &HashMap<String, String>,
+) -> Option<String> {
+    let certs = request
+        .extensions()
+        .get::<TlsConnectInfo<PeerConnectInfo>>()?
+        .peer_certs()?;
+    let (_, cert) = X509Certificate::from_der(certs.first()?.as_ref()).ok()?;
+    Some(map_client_identity(
+        &get_certificate_names(&cert),
+        identities,
+    ))
+}
+
+/// First name found in `identities` is mapped, names are not used as is because
+/// identity is used as metrics label
+fn map_client_identity(names: &[String], identities: &HashMap<String, String>) -> String {
+    names
+        .iter()
+        .find_map(|name| identities.get(name).cloned())
+        .unwrap_or_else(|| UNKNOWN_CLIENT_IDENTITY.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            get_certificate_names, map_client_identity, TlsServerConfig, UNKNOWN_CLIENT_IDENTITY,
+        },
+        crate::config::ConfigGrpcServerTls,
+        futures::stream::{self, StreamExt},
+        std::{
+            collections::HashMap,
+            fs,
+            io::{self, Cursor},
+            path::Path,
+            sync::Arc,
+            time::{Duration, SystemTime},
+        },
+        tokio::{io::duplex, time::sleep},
+        tokio_rustls::{
+            rustls::{
+                crypto::ring::default_provider, pki_types::ServerName, ClientConfig, RootCertStore,
+            },
+            TlsConnector,
+        },
+        x509_parser::pem::parse_x509_pem,
+    };
+
+    const CA_1_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/ca-1.crt");
+    const CA_2_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/ca-2.crt");
+    const SERVER_1_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/server-1.crt");
+    const SERVER_1_KEY: &[u8] = include_bytes!("../tests/fixtures/tls/server-1.key");
+    const SERVER_2_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/server-2.crt");
+    const SERVER_2_KEY: &[u8] = include_bytes!("../tests/fixtures/tls/server-2.key");
+
+    fn write_modified(path: &Path, data: &[u8], modified: SystemTime) {
+        fs::write(path, data).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    /// Handshake over in-memory stream, client trusts only `ca` certificates
+    async fn handshake(tls_config: &TlsServerConfig, ca: &[u8]) -> bool {
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut Cursor::new(ca)) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let client_config = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let (client, server) = duplex(16 * 1024);
+        let mut incoming = tls_config.incoming(stream::iter([io::Result::Ok(server)]));
+        let server = tokio::spawn(async move { incoming.next().await.is_some() });
+        let client = TlsConnector::from(Arc::new(client_config))
+            .connect(ServerName::try_from("localhost").unwrap(), client)
+            .await;
+        client.is_ok() && server.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let dir = std::env::temp_dir().join(format!("yellowstone-grpc-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("server.crt");
+        let key_path = dir.join("server.key");
+        let started_at = SystemTime::now();
+        write_modified(&cert_path, SERVER_1_CERT, started_at);
+        write_modified(&key_path, SERVER_1_KEY, started_at);
+
+        let tls_config = TlsServerConfig::new(ConfigGrpcServerTls {
+            cert_path: cert_path.to_string_lossy().into_owned(),
+            key_path: key_path.to_string_lossy().into_owned(),
+            ca_path: None,
+            client_auth_optional: false,
+            client_identities: HashMap::new(),
+            reload_interval: Duration::from_millis(10),
+        })
+        .unwrap();
+        assert!(handshake(&tls_config, CA_1_CERT).await);
+        assert!(!handshake(&tls_config, CA_2_CERT).await);
+
+        // certificates are reloaded on change
+        let modified = started_at + Duration::from_secs(1);
+        write_modified(&cert_path, SERVER_2_CERT, modified);
+        write_modified(&key_path, SERVER_2_KEY, modified);
+        let mut reloaded = false;
+        for _ in 0..100 {
+            sleep(Duration::from_millis(20)).await;
+            if handshake(&tls_config, CA_2_CERT).await {
+                reloaded = true;
+                break;
+            }
+        }
+        assert!(reloaded);
+        assert!(!handshake(&tls_config, CA_1_CERT).await);
+
+        // invalid certificates are ignored, previous config is used
+        write_modified(&key_path, b"invalid", started_at + Duration::from_secs(2));
+        sleep(Duration::from_millis(100)).await;
+        assert!(handshake(&tls_config, CA_2_CERT).await);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_certificate_names() {
+        let pem = include_bytes!("../tests/fixtures/tls/client.crt");
+        let (_, pem) = parse_x509_pem(pem).unwrap();
+        let cert = pem.parse_x509().unwrap();
+        assert_eq!(
+            get_certificate_names(&cert),
+            vec![
+                "client-1.example.com".to_owned(),
+                "spiffe://example.com/client-1".to_owned(),
+                "client-cn".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_client_identity() {
+        let identities = HashMap::from([
+            ("client-cn".to_owned(), "client-cn-identity".to_owned()),
+            (
+                "spiffe://example.com/client-1".to_owned(),
+                "client-1".to_owned(),
+            ),
+        ]);
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| (*name).to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        // names are checked in order of priority
+        assert_eq!(
+            map_client_identity(
+                &names(&[
+                    "client-1.example.com",
+                    "spiffe://example.com/client-1",
+                    "client-cn"
+                ]),
+                &identities
+            ),
+            "client-1"
+        );
+        assert_eq!(
+            map_client_identity(&names(&["client-cn"]), &identities),
+            "client-cn-identity"
+        );
+        assert_eq!(
+            map_client_identity(&names(&["client-2.example.com"]), &identities),
+            UNKNOWN_CLIENT_IDENTITY
+        );
+        assert_eq!(
+            map_client_identity(&[], &HashMap::new()),
+            UNKNOWN_CLIENT_IDENTITY
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use {
+    crate::config::ConfigGrpcUnixSocket,
+    anyhow::Context,
+    nix::unistd::{Group, User},
+    std::{
+        fs::Permissions,
+        io,
+        os::unix::fs::{chown, FileTypeExt, PermissionsExt},
+        path::{Path, PathBuf},
+        process,
+    },
+    tokio::{
+        fs::{self, DirBuilder},
+        net::{UnixListener, UnixStream},
+    },
+};
+
+/// Bind Unix socket listener, apply file mode and ownership from config.
+/// Existing socket file is removed only if no server is listening on it.
+/// Socket is bound in private directory and moved to `path` once mode and ownership
+/// are applied, so it's never accessible with default permissions.
+pub async fn bind_listener(
+    path: &Path,
+    config: &ConfigGrpcUnixSocket,
+) -> anyhow::Result<UnixListener> {
+    #[cfg(target_os = "linux")]
+    if config.abstract_namespace {
+        return bind_listener_abstract(path);
+    }
+
+    if let Ok(metadata) = fs::symlink_metadata(path).await {
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "Existing file is not a Unix socket: {path:?}"
+        );
+        match UnixStream::connect(path).await {
+            Ok(_stream) => anyhow::bail!("Unix socket {path:?} is used by another running server"),
+            Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+                fs::remove_file(path)
+                    .await
+                    .with_context(|| format!("Failed to remove existing Unix socket: {path:?}"))?;
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to check existing Unix socket: {path:?}"))
+            }
+        }
+    }
+
+    let private_dir = get_private_dir(path)?;
+    DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .await
+        .with_context(|| format!("Failed to create directory for Unix socket: {private_dir:?}"))?;
+    let result = bind_listener_private(path, &private_dir.join("socket"), config).await;
+    let _ = fs::remove_dir_all(&private_dir).await;
+    result
+}
+
+async fn bind_listener_private(
+    path: &Path,
+    private_path: &Path,
+    config: &ConfigGrpcUnixSocket,
+) -> anyhow::Result<UnixListener> {
+    let listener = UnixListener::bind(private_path)
+        .with_context(|| format!("Failed to bind Unix socket: {path:?}"))?;
+
+    if config.owner.is_some() || config.group.is_some() {
+        let uid = config.owner.as_deref().map(get_uid).transpose()?;
+        let gid = config.group.as_deref().map(get_gid).transpose()?;
+        chown(private_path, uid, gid)
+            .with_context(|| format!("Failed to change Unix socket owner: {path:?}"))?;
+    }
+    if let Some(mode) = config.mode {
+        fs::set_permissions(private_path, Permissions::from_mode(mode))
+            .await
+            .with_context(|| format!("Failed to change Unix socket mode: {path:?}"))?;
+    }
+    fs::rename(private_path, path)
+        .await
+        .with_context(|| format!("Failed to move Unix socket: {path:?}"))?;
+
+    Ok(listener)
+}
+
+/// Directory next to the socket path, rename is possible only within one filesystem
+fn get_private_dir(path: &Path) -> anyhow::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Invalid Unix socket path: {path:?}"))?;
+    let mut private_name = name.to_os_string();
+    private_name.push(format!(".{}.tmp", process::id()));
+    Ok(path.with_file_name(private_name))
+}
+
+#[cfg(target_os = "linux")]
+fn bind_listener_abstract(name: &Path) -> anyhow::Result<UnixListener> {
+    use std::os::{
+        linux::net::SocketAddrExt,
+        unix::{
+            ffi::OsStrExt,
+            net::{SocketAddr, UnixListener as StdUnixListener},
+        },
+    };
+
+    let addr = SocketAddr::from_abstract_name(name.as_os_str().as_bytes())
+        .with_context(|| format!("Invalid abstract Unix socket name: {name:?}"))?;
+    let listener = StdUnixListener::bind_addr(&addr)
+        .with_context(|| format!("Failed to bind abstract Unix socket: {name:?}"))?;
+    listener.set_nonblocking(true)?;
+    UnixListener::from_std(listener).context("Failed to create Unix socket listener")
+}
+
+fn get_uid(owner: &str) -> anyhow::Result<u32> {
+    if let Ok(uid) = owner.parse() {
+        return Ok(uid);
+    }
+    User::from_name(owner)
+        .with_context(|| format!("Failed to get user {owner:?}"))?
+        .map(|user| user.uid.as_raw())
+        .with_context(|| format!("User {owner:?} not found"))
+}
+
+fn get_gid(group: &str) -> anyhow::Result<u32> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+    Group::from_name(group)
+        .with_context(|| format!("Failed to get group {group:?}"))?
+        .map(|group| group.gid.as_raw())
+        .with_context(|| format!("Group {group:?} not found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::bind_listener,
+        crate::config::ConfigGrpcUnixSocket,
+        std::{
+            fs,
+            os::unix::fs::{FileTypeExt, PermissionsExt},
+        },
+    };
+
+    #[tokio::test]
+    async fn test_bind_listener() {
+        let dir =
+            std::env::temp_dir().join(format!("yellowstone-grpc-unix-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("grpc.sock");
+        let config = ConfigGrpcUnixSocket {
+            mode: Some(0o600),
+            ..Default::default()
+        };
+
+        let listener = bind_listener(&path, &config).await.unwrap();
+        let metadata = fs::symlink_metadata(&path).unwrap();
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o600);
+        // only the socket is left in the directory
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // socket is used by running server
+        assert!(bind_listener(&path, &config).await.is_err());
+        drop(listener);
+
+        // stale socket is replaced
+        let config = ConfigGrpcUnixSocket {
+            mode: Some(0o660),
+            ..Default::default()
+        };
+        let _listener = bind_listener(&path, &config).await.unwrap();
+        let metadata = fs::symlink_metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o660);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
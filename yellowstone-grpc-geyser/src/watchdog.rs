@@ -0,0 +1,105 @@
+use {
+    crate::{config::ConfigGrpcSlotWatchdog, health::GrpcHealth, metrics},
+    log::{info, warn},
+    solana_clock::Slot,
+    std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    yellowstone_grpc_proto::plugin::message::{Message, MessageNotice, MessageSlot, SlotStatus},
+};
+
+pub const SLOT_WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct SlotWatchdogStatus {
+    status: SlotStatus,
+    threshold: Option<Duration>,
+    last_slot: Option<Slot>,
+    last_update: Instant,
+    stalled: bool,
+}
+
+impl SlotWatchdogStatus {
+    fn new(status: SlotStatus, threshold: Option<Duration>) -> Self {
+        Self {
+            status,
+            threshold,
+            last_slot: None,
+            last_update: Instant::now(),
+            stalled: false,
+        }
+    }
+}
+
+/// Time since the last processed, confirmed and finalized slot in `geyser_loop`,
+/// health status is not serving while any of them stalled.
+#[derive(Debug)]
+pub struct SlotWatchdog {
+    statuses: [SlotWatchdogStatus; 3],
+    health: Arc<GrpcHealth>,
+}
+
+impl SlotWatchdog {
+    pub fn new(config: ConfigGrpcSlotWatchdog, health: Arc<GrpcHealth>) -> Self {
+        Self {
+            statuses: [
+                SlotWatchdogStatus::new(SlotStatus::Processed, config.processed),
+                SlotWatchdogStatus::new(SlotStatus::Confirmed, config.confirmed),
+                SlotWatchdogStatus::new(SlotStatus::Finalized, config.finalized),
+            ],
+            health,
+        }
+    }
+
+    pub fn slot_updated(&mut self, msg: &MessageSlot) {
+        if let Some(status) = self.statuses.iter_mut().find(|st| st.status == msg.status) {
+            status.last_slot = Some(msg.slot);
+            status.last_update = Instant::now();
+        }
+    }
+
+    /// Update metrics and detect stall changes, notices are returned for changed statuses.
+    /// Time is not counted until end of startup, slot updates are not expected before it.
+    pub fn check(&mut self) -> Vec<Message> {
+        let startup_done = self.health.is_startup_done();
+        let mut notices = vec![];
+        for status in self.statuses.iter_mut() {
+            if !startup_done {
+                status.last_update = Instant::now();
+            }
+
+            let elapsed = status.last_update.elapsed();
+            metrics::set_seconds_since_last_slot(status.status, elapsed);
+
+            let stalled = status
+                .threshold
+                .is_some_and(|threshold| elapsed >= threshold);
+            if stalled != status.stalled {
+                status.stalled = stalled;
+                if stalled {
+                    warn!(
+                        "{} slot updates stalled for {elapsed:?}, last slot: {:?}",
+                        status.status.as_str(),
+                        status.last_slot
+                    );
+                } else {
+                    info!(
+                        "{} slot updates resumed, slot: {:?}",
+                        status.status.as_str(),
+                        status.last_slot
+                    );
+                }
+                notices.push(Message::Notice(Arc::new(MessageNotice::slot_stall(
+                    status.status,
+                    stalled,
+                    status.last_slot,
+                    elapsed,
+                ))));
+            }
+        }
+        self.health
+            .set_slot_stalled(self.statuses.iter().any(|status| status.stalled));
+        notices
+    }
+}
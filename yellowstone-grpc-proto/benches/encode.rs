@@ -4,12 +4,15 @@ use {
     prost_types::Timestamp,
     std::time::{Duration, SystemTime},
     yellowstone_grpc_proto::plugin::{
-        filter::message::{
-            tests::{
-                create_accounts, create_message_filters, load_predefined_blocks,
-                load_predefined_transactions,
+        filter::{
+            encoded::FilteredUpdateEncodedCache,
+            message::{
+                tests::{
+                    create_accounts, create_message_filters, load_predefined_blocks,
+                    load_predefined_transactions,
+                },
+                FilteredUpdate, FilteredUpdateOneof,
             },
-            FilteredUpdate, FilteredUpdateOneof,
         },
         message::MessageTransaction,
     },
@@ -43,6 +46,8 @@ fn bench_account(c: &mut Criterion) {
             filters: filters.clone(),
             message: FilteredUpdateOneof::account(&msg, data_slice),
             created_at: Timestamp::from(SystemTime::now()),
+            encoded: None,
+            timings: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "accounts");
@@ -57,6 +62,8 @@ fn bench_account(c: &mut Criterion) {
                 created_at: Timestamp::from(SystemTime::now()),
             }),
             created_at: Timestamp::from(SystemTime::now()),
+            encoded: None,
+            timings: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "transactions");
@@ -67,16 +74,82 @@ fn bench_account(c: &mut Criterion) {
             filters: filters.clone(),
             message: FilteredUpdateOneof::block(Box::new(block)),
             created_at: Timestamp::from(SystemTime::now()),
+            encoded: None,
+            timings: None,
         })
         .collect::<Vec<_>>();
     bench!(&updates, "blocks");
 }
 
+fn bench_encoded_cache(c: &mut Criterion) {
+    const STREAMS: usize = 100;
+
+    let filters = create_message_filters(&["my special filter"]);
+
+    macro_rules! bench {
+        ($updates:expr, $kind:expr) => {
+            c.bench_with_input(BenchmarkId::new($kind, "ref"), $updates, |b, updates| {
+                b.iter(|| {
+                    for update in updates.iter() {
+                        // every stream gets own update from filter
+                        for _ in 0..STREAMS {
+                            let update = update.clone();
+                            update.encode_to_vec().len();
+                        }
+                    }
+                })
+            });
+            c.bench_with_input(BenchmarkId::new($kind, "cache"), $updates, |b, updates| {
+                b.iter(|| {
+                    let cache = FilteredUpdateEncodedCache::new(updates.len(), usize::MAX);
+                    for (msgid, update) in updates.iter().enumerate() {
+                        for _ in 0..STREAMS {
+                            let mut update = update.clone();
+                            cache.encode(msgid as u64, &mut update);
+                            update.encode_to_vec().len();
+                        }
+                    }
+                })
+            });
+        };
+    }
+
+    // popular accounts are small, encoding of big accounts is mostly data copy
+    let updates = create_accounts()
+        .into_iter()
+        .filter(|(msg, _data_slice)| msg.account.data.len() <= 1024)
+        .map(|(msg, data_slice)| {
+            FilteredUpdate::new(
+                filters.clone(),
+                FilteredUpdateOneof::account(&msg, data_slice),
+                Timestamp::from(SystemTime::now()),
+            )
+        })
+        .collect::<Vec<_>>();
+    bench!(&updates, "accounts_100_streams");
+
+    let updates = load_predefined_transactions()
+        .into_iter()
+        .map(|transaction| {
+            FilteredUpdate::new(
+                filters.clone(),
+                FilteredUpdateOneof::transaction(&MessageTransaction {
+                    transaction,
+                    slot: 42,
+                    created_at: Timestamp::from(SystemTime::now()),
+                }),
+                Timestamp::from(SystemTime::now()),
+            )
+        })
+        .collect::<Vec<_>>();
+    bench!(&updates, "transactions_100_streams");
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default()
         .warm_up_time(Duration::from_secs(3)) // default 3
         .measurement_time(Duration::from_secs(5)); // default 5
-    targets = bench_account
+    targets = bench_account, bench_encoded_cache
 );
 criterion_main!(benches);
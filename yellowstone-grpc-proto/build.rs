@@ -3,21 +3,58 @@ use {
     tonic_build::manual::{Builder, Method, Service},
 };
 
+/// Subscribe requests are serializable with `plugin` feature, for JSON status of clients
+fn configure() -> tonic_build::Builder {
+    let mut builder = tonic_build::configure();
+    for path in [
+        ".geyser.SubscribeRequest",
+        ".geyser.SubscribeRequestFilterAccounts",
+        ".geyser.SubscribeRequestFilterAccountsFilter",
+        ".geyser.SubscribeRequestFilterAccountsFilterMemcmp",
+        ".geyser.SubscribeRequestFilterAccountsFilterLamports",
+        ".geyser.SubscribeRequestFilterSlots",
+        ".geyser.SubscribeRequestFilterTransactions",
+        ".geyser.SubscribeRequestFilterBlocks",
+        ".geyser.SubscribeRequestFilterBlocksMeta",
+        ".geyser.SubscribeRequestFilterEntry",
+        ".geyser.SubscribeRequestFilterNotices",
+        ".geyser.SubscribeRequestAccountsDataSlice",
+        ".geyser.SubscribeRequestPing",
+    ] {
+        builder = builder.type_attribute(
+            path,
+            r#"#[cfg_attr(feature = "plugin", derive(serde::Serialize))]"#,
+        );
+    }
+    builder
+}
+
 fn main() -> anyhow::Result<()> {
     std::env::set_var("PROTOC", protobuf_src::protoc());
 
-    // build protos
-    tonic_build::configure().compile_protos(&["proto/geyser.proto"], &["proto"])?;
+    // build protos, with descriptors for gRPC reflection
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not found");
+    configure()
+        .file_descriptor_set_path(Path::new(&out_dir).join("geyser_descriptor.bin"))
+        .compile_protos(&["proto/geyser.proto"], &["proto"])?;
+    // imported geyser.proto is generated again with the same output
+    configure()
+        .file_descriptor_set_path(Path::new(&out_dir).join("admin_descriptor.bin"))
+        .compile_protos(&["proto/admin.proto"], &["proto"])?;
 
     // build protos without tonic (wasm)
-    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not found");
     let out_dir_path = Path::new(&out_dir).join("no-tonic");
     fs::create_dir_all(&out_dir_path).expect("failed to create out no-tonic directory");
-    tonic_build::configure()
+    configure()
         .build_client(false)
         .build_server(false)
-        .out_dir(out_dir_path)
+        .out_dir(&out_dir_path)
         .compile_protos(&["proto/geyser.proto"], &["proto"])?;
+    configure()
+        .build_client(false)
+        .build_server(false)
+        .out_dir(out_dir_path)
+        .compile_protos(&["proto/admin.proto"], &["proto"])?;
 
     // build with accepting our custom struct
     let geyser_service = Service::builder()
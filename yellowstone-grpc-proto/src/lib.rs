@@ -10,6 +10,16 @@ pub mod geyser {
     include!(concat!(env!("OUT_DIR"), "/no-tonic/geyser.rs"));
 }
 
+pub mod geyser_admin {
+    #![allow(clippy::clone_on_ref_ptr)]
+    #![allow(clippy::missing_const_for_fn)]
+
+    #[cfg(feature = "tonic")]
+    include!(concat!(env!("OUT_DIR"), "/geyser_admin.rs"));
+    #[cfg(not(feature = "tonic"))]
+    include!(concat!(env!("OUT_DIR"), "/no-tonic/geyser_admin.rs"));
+}
+
 pub mod solana {
     #![allow(clippy::missing_const_for_fn)]
 
@@ -33,6 +43,14 @@ pub mod prelude {
     pub use super::{geyser::*, solana::storage::confirmed_block::*};
 }
 
+/// Encoded `FileDescriptorSet` of `geyser.proto` and its imports, used for gRPC reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/geyser_descriptor.bin"));
+
+/// Encoded `FileDescriptorSet` of `admin.proto` and its imports, used for gRPC reflection
+pub const ADMIN_FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/admin_descriptor.bin"));
+
 #[cfg(feature = "tonic")]
 pub use tonic;
 pub use {prost, prost_types};
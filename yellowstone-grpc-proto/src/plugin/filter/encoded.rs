@@ -0,0 +1,383 @@
+use {
+    crate::plugin::filter::{
+        message::{FilteredUpdate, FilteredUpdateOneof},
+        FilterAccountsDataSlice,
+    },
+    bytes::Bytes,
+    prost::Message as _,
+    std::{
+        collections::{hash_map::RandomState, HashMap, VecDeque},
+        hash::{BuildHasher, Hash, Hasher},
+        sync::Mutex,
+    },
+};
+
+const CACHE_SHARDS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilteredUpdateEncodedStatus {
+    Hit,
+    Miss,
+    /// Update depends not only on filter names (blocks) or has no message id (ping, pong)
+    Uncacheable,
+}
+
+impl FilteredUpdateEncodedStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Hit => "hit",
+            Self::Miss => "miss",
+            Self::Uncacheable => "uncacheable",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EncodedUpdate {
+    // names are copied, `FilterName` references are used for names cleanup
+    filters: Vec<String>,
+    data_slice: Option<FilterAccountsDataSlice>,
+    encoded: Bytes,
+}
+
+impl EncodedUpdate {
+    fn is_match(
+        &self,
+        update: &FilteredUpdate,
+        data_slice: Option<&FilterAccountsDataSlice>,
+    ) -> bool {
+        self.data_slice.as_ref() == data_slice
+            && self
+                .filters
+                .iter()
+                .map(String::as_str)
+                .eq(update.filters.iter().map(|name| name.as_ref()))
+    }
+}
+
+/// Message id, update type and hash of filter names with accounts data slice
+type EncodedKey = (u64, &'static str, u64);
+
+#[derive(Debug, Default)]
+struct EncodedCacheShard {
+    updates: HashMap<EncodedKey, EncodedUpdate>,
+    order: VecDeque<EncodedKey>,
+    bytes: usize,
+}
+
+impl EncodedCacheShard {
+    fn get(
+        &self,
+        key: &EncodedKey,
+        update: &FilteredUpdate,
+        data_slice: Option<&FilterAccountsDataSlice>,
+    ) -> Option<&EncodedUpdate> {
+        self.updates
+            .get(key)
+            .filter(|encoded| encoded.is_match(update, data_slice))
+    }
+
+    fn insert(
+        &mut self,
+        key: EncodedKey,
+        update: EncodedUpdate,
+        capacity: usize,
+        max_bytes: usize,
+    ) {
+        while self.order.len() >= capacity || self.bytes + update.encoded.len() > max_bytes {
+            let Some(key) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(update) = self.updates.remove(&key) {
+                self.bytes -= update.encoded.len();
+            }
+        }
+        self.bytes += update.encoded.len();
+        self.order.push_back(key);
+        self.updates.insert(key, update);
+    }
+}
+
+/// Encoded updates shared between streams, an update is encoded once for the same message id,
+/// update type, filter names and accounts data slice. Old updates are removed in insertion order.
+#[derive(Debug)]
+pub struct FilteredUpdateEncodedCache {
+    shards: Box<[Mutex<EncodedCacheShard>]>,
+    shard_capacity: usize,
+    shard_max_bytes: usize,
+    hasher: RandomState,
+}
+
+impl FilteredUpdateEncodedCache {
+    /// `capacity` is the max number of encoded updates and `max_bytes` is the max total size
+    /// of encoded updates in the cache
+    pub fn new(capacity: usize, max_bytes: usize) -> Self {
+        Self {
+            shards: (0..CACHE_SHARDS)
+                .map(|_| Mutex::new(EncodedCacheShard::default()))
+                .collect(),
+            shard_capacity: capacity.div_ceil(CACHE_SHARDS).max(1),
+            shard_max_bytes: max_bytes.div_ceil(CACHE_SHARDS),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Set `FilteredUpdate::encoded` from cache or encode update and save it to cache
+    pub fn encode(&self, msgid: u64, update: &mut FilteredUpdate) -> FilteredUpdateEncodedStatus {
+        // timings are stream specific and encoded after shared bytes
+        let timings = update.timings.take();
+        let status = self.encode_shared(msgid, update);
+        update.timings = timings;
+        status
+    }
+
+    fn encode_shared(
+        &self,
+        msgid: u64,
+        update: &mut FilteredUpdate,
+    ) -> FilteredUpdateEncodedStatus {
+        let (kind, data_slice) = match &update.message {
+            FilteredUpdateOneof::Account(msg) => ("account", Some(&msg.data_slice)),
+            // slots filters can include or exclude timings
+            FilteredUpdateOneof::Slot(msg) if msg.timings.is_some() => ("slot_timings", None),
+            FilteredUpdateOneof::Slot(_) => ("slot", None),
+            FilteredUpdateOneof::Transaction(_) => ("transaction", None),
+            FilteredUpdateOneof::TransactionStatus(_) => ("transaction_status", None),
+            FilteredUpdateOneof::BlockMeta(_) => ("block_meta", None),
+            FilteredUpdateOneof::Entry(_) => ("entry", None),
+            FilteredUpdateOneof::Notice(_) => ("notice", None),
+            FilteredUpdateOneof::BlockFailure(_) => ("block_failure", None),
+            FilteredUpdateOneof::Block(_)
+            | FilteredUpdateOneof::Ping
+            | FilteredUpdateOneof::Pong(_) => return FilteredUpdateEncodedStatus::Uncacheable,
+        };
+        let key = (msgid, kind, self.hash(update, data_slice));
+        let shard = &self.shards[(msgid % CACHE_SHARDS as u64) as usize];
+
+        let cached = shard
+            .lock()
+            .expect("poisoned")
+            .get(&key, update, data_slice)
+            .map(|encoded| encoded.encoded.clone());
+        if let Some(encoded) = cached {
+            update.encoded = Some(encoded);
+            return FilteredUpdateEncodedStatus::Hit;
+        }
+
+        // encode without lock, same update can be encoded by few streams at once
+        let encoded = Bytes::from(update.encode_to_vec());
+        update.encoded = Some(encoded.clone());
+        if encoded.len() > self.shard_max_bytes {
+            return FilteredUpdateEncodedStatus::Miss;
+        }
+
+        let mut shard = shard.lock().expect("poisoned");
+        // keep existing update on hash collision
+        if !shard.updates.contains_key(&key) {
+            let update = EncodedUpdate {
+                filters: update.filters.iter().map(|name| name.to_string()).collect(),
+                data_slice: data_slice.cloned(),
+                encoded,
+            };
+            shard.insert(key, update, self.shard_capacity, self.shard_max_bytes);
+        }
+        FilteredUpdateEncodedStatus::Miss
+    }
+
+    fn hash(&self, update: &FilteredUpdate, data_slice: Option<&FilterAccountsDataSlice>) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        for name in update.filters.iter() {
+            name.as_ref().hash(&mut hasher);
+        }
+        data_slice
+            .map(|data_slice| data_slice.as_ref())
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FilteredUpdateEncodedCache, FilteredUpdateEncodedStatus, CACHE_SHARDS},
+        crate::{
+            geyser::{SubscribeUpdateSlotTimings, SubscribeUpdateTimings},
+            plugin::{
+                filter::{
+                    message::{
+                        tests::{create_accounts, create_message_filters},
+                        FilteredUpdate, FilteredUpdateOneof,
+                    },
+                    FilterAccountsDataSlice,
+                },
+                message::{MessageAccount, MessageSlot, SlotStatus},
+            },
+        },
+        prost::Message as _,
+        prost_types::Timestamp,
+        std::time::SystemTime,
+    };
+
+    fn create_update(
+        filters: &[&str],
+        message: &MessageAccount,
+        data_slice: FilterAccountsDataSlice,
+        created_at: Timestamp,
+    ) -> FilteredUpdate {
+        FilteredUpdate::new(
+            create_message_filters(filters),
+            FilteredUpdateOneof::account(message, data_slice),
+            created_at,
+        )
+    }
+
+    #[test]
+    fn test_encoded_cache_account() {
+        let cache = FilteredUpdateEncodedCache::new(1_000, usize::MAX);
+        let created_at = Timestamp::from(SystemTime::now());
+        for (msgid, (message, data_slice)) in create_accounts().into_iter().enumerate() {
+            let msgid = msgid as u64;
+            let expected = create_update(&["f1"], &message, data_slice.clone(), created_at);
+
+            let mut update = expected.clone();
+            assert_eq!(
+                cache.encode(msgid, &mut update),
+                FilteredUpdateEncodedStatus::Miss
+            );
+            assert_eq!(update.encode_to_vec(), expected.encode_to_vec());
+            assert_eq!(update.encoded_len(), expected.encoded_len());
+
+            let mut update = expected.clone();
+            assert_eq!(
+                cache.encode(msgid, &mut update),
+                FilteredUpdateEncodedStatus::Hit
+            );
+            assert_eq!(update.encode_to_vec(), expected.encode_to_vec());
+
+            // timings are not shared
+            let timings = Some(SubscribeUpdateTimings {
+                broadcasted: Some(created_at),
+                sent: Some(Timestamp::from(SystemTime::now())),
+            });
+            let mut expected = expected.clone();
+            expected.timings = timings;
+            let mut update = expected.clone();
+            assert_eq!(
+                cache.encode(msgid, &mut update),
+                FilteredUpdateEncodedStatus::Hit
+            );
+            assert_eq!(update.encode_to_vec(), expected.encode_to_vec());
+            assert_eq!(update.encoded_len(), expected.encoded_len());
+
+            // other filter names or data slice are encoded separately
+            let mut update = create_update(&["f2"], &message, data_slice.clone(), created_at);
+            assert_eq!(
+                cache.encode(msgid, &mut update),
+                FilteredUpdateEncodedStatus::Miss
+            );
+            let status = if data_slice == FilterAccountsDataSlice::default() {
+                FilteredUpdateEncodedStatus::Hit
+            } else {
+                FilteredUpdateEncodedStatus::Miss
+            };
+            let mut update = create_update(&["f1"], &message, Default::default(), created_at);
+            assert_eq!(cache.encode(msgid, &mut update), status);
+        }
+    }
+
+    #[test]
+    fn test_encoded_cache_slot_timings() {
+        let cache = FilteredUpdateEncodedCache::new(1_000, usize::MAX);
+        let created_at = Timestamp::from(SystemTime::now());
+        let message = MessageSlot {
+            slot: 42,
+            parent: Some(41),
+            status: SlotStatus::Processed,
+            dead_error: None,
+            timings: Some(SubscribeUpdateSlotTimings {
+                processed: Some(created_at),
+                ..Default::default()
+            }),
+            created_at,
+        };
+        for (message, status) in [
+            (message.clone(), FilteredUpdateEncodedStatus::Miss),
+            (
+                MessageSlot {
+                    timings: None,
+                    ..message.clone()
+                },
+                FilteredUpdateEncodedStatus::Miss,
+            ),
+            (message, FilteredUpdateEncodedStatus::Hit),
+        ] {
+            let expected = FilteredUpdate::new(
+                create_message_filters(&["f1"]),
+                FilteredUpdateOneof::slot(message),
+                created_at,
+            );
+            let mut update = expected.clone();
+            assert_eq!(cache.encode(0, &mut update), status);
+            assert_eq!(update.encode_to_vec(), expected.encode_to_vec());
+        }
+    }
+
+    #[test]
+    fn test_encoded_cache_capacity() {
+        let cache = FilteredUpdateEncodedCache::new(16, usize::MAX);
+        let created_at = Timestamp::from(SystemTime::now());
+        let (message, data_slice) = create_accounts().remove(0);
+        for msgid in 0..32 {
+            let mut update = create_update(&["f1"], &message, data_slice.clone(), created_at);
+            assert_eq!(
+                cache.encode(msgid, &mut update),
+                FilteredUpdateEncodedStatus::Miss
+            );
+        }
+        let mut update = create_update(&["f1"], &message, data_slice.clone(), created_at);
+        assert_eq!(
+            cache.encode(31, &mut update),
+            FilteredUpdateEncodedStatus::Hit
+        );
+        let mut update = create_update(&["f1"], &message, data_slice, created_at);
+        assert_eq!(
+            cache.encode(0, &mut update),
+            FilteredUpdateEncodedStatus::Miss
+        );
+    }
+
+    #[test]
+    fn test_encoded_cache_max_bytes() {
+        let created_at = Timestamp::from(SystemTime::now());
+        let (message, data_slice) = create_accounts().remove(0);
+        let update = create_update(&["f1"], &message, data_slice, created_at);
+        let len = update.encoded_len();
+
+        // two updates per shard, msgids 0, 16 and 32 share the same shard
+        let cache = FilteredUpdateEncodedCache::new(1_000, len * 2 * CACHE_SHARDS);
+        for msgid in [0, 16, 32] {
+            assert_eq!(
+                cache.encode(msgid, &mut update.clone()),
+                FilteredUpdateEncodedStatus::Miss
+            );
+        }
+        for (msgid, status) in [
+            (32, FilteredUpdateEncodedStatus::Hit),
+            (16, FilteredUpdateEncodedStatus::Hit),
+            (0, FilteredUpdateEncodedStatus::Miss),
+        ] {
+            assert_eq!(cache.encode(msgid, &mut update.clone()), status);
+        }
+
+        // updates bigger than shard limit are not cached
+        let cache = FilteredUpdateEncodedCache::new(1_000, CACHE_SHARDS);
+        for _ in 0..2 {
+            let mut encoded = update.clone();
+            assert_eq!(
+                cache.encode(0, &mut encoded),
+                FilteredUpdateEncodedStatus::Miss
+            );
+            assert_eq!(encoded.encode_to_vec(), update.encode_to_vec());
+        }
+    }
+}
@@ -8,15 +8,16 @@ use {
             SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
             SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterLamports,
             SubscribeRequestFilterBlocks, SubscribeRequestFilterBlocksMeta,
-            SubscribeRequestFilterEntry, SubscribeRequestFilterSlots,
-            SubscribeRequestFilterTransactions,
+            SubscribeRequestFilterEntry, SubscribeRequestFilterNotices,
+            SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+            SubscribeUpdateBlockFailure,
         },
         plugin::{
             filter::{
                 limits::{
                     FilterLimits, FilterLimitsAccounts, FilterLimitsBlocks, FilterLimitsBlocksMeta,
-                    FilterLimitsCheckError, FilterLimitsEntries, FilterLimitsSlots,
-                    FilterLimitsTransactions,
+                    FilterLimitsCheckError, FilterLimitsEntries, FilterLimitsKind,
+                    FilterLimitsNotices, FilterLimitsSlots, FilterLimitsTransactions,
                 },
                 message::{
                     FilteredUpdate, FilteredUpdateBlock, FilteredUpdateFilters,
@@ -25,14 +26,16 @@ use {
                 name::{FilterName, FilterNameError, FilterNames},
             },
             message::{
-                CommitmentLevel, Message, MessageAccount, MessageBlock, MessageBlockMeta,
-                MessageEntry, MessageSlot, MessageTransaction, SlotStatus,
+                CommitmentLevel, Message, MessageAccount, MessageAccountInfo, MessageBlock,
+                MessageBlockMeta, MessageEntry, MessageNotice, MessageSlot, MessageTransaction,
+                SlotStatus,
             },
         },
     },
     base64::{engine::general_purpose::STANDARD as base64_engine, Engine},
     bytes::buf::BufMut,
     prost::encoding::{encode_key, encode_varint, WireType},
+    smallvec::{smallvec, SmallVec},
     solana_pubkey::{ParsePubkeyError, Pubkey},
     solana_signature::{ParseSignatureError, Signature},
     spl_token_2022::{generic_token_account::GenericTokenAccount, state::Account as TokenAccount},
@@ -105,9 +108,11 @@ pub struct Filter {
     entries: FilterEntries,
     blocks: FilterBlocks,
     blocks_meta: FilterBlocksMeta,
+    notices: FilterNotices,
     commitment: CommitmentLevel,
     accounts_data_slice: FilterAccountsDataSlice,
     ping: Option<i32>,
+    timings: bool,
 }
 
 impl Default for Filter {
@@ -126,9 +131,11 @@ impl Default for Filter {
             entries: FilterEntries::default(),
             blocks: FilterBlocks::default(),
             blocks_meta: FilterBlocksMeta::default(),
+            notices: FilterNotices::default(),
             commitment: CommitmentLevel::Processed,
             accounts_data_slice: FilterAccountsDataSlice::default(),
             ping: None,
+            timings: false,
         }
     }
 }
@@ -139,6 +146,25 @@ impl Filter {
         limits: &FilterLimits,
         names: &mut FilterNames,
     ) -> FilterResult<Self> {
+        for (kind, is_empty) in [
+            (FilterLimitsKind::Accounts, config.accounts.is_empty()),
+            (FilterLimitsKind::Slots, config.slots.is_empty()),
+            (
+                FilterLimitsKind::Transactions,
+                config.transactions.is_empty(),
+            ),
+            (
+                FilterLimitsKind::TransactionsStatus,
+                config.transactions_status.is_empty(),
+            ),
+            (FilterLimitsKind::Blocks, config.blocks.is_empty()),
+            (FilterLimitsKind::BlocksMeta, config.blocks_meta.is_empty()),
+            (FilterLimitsKind::Entries, config.entry.is_empty()),
+            (FilterLimitsKind::Notices, config.notices.is_empty()),
+        ] {
+            limits.check_kind(kind, is_empty)?;
+        }
+
         Ok(Self {
             accounts: FilterAccounts::new(&config.accounts, &limits.accounts, names)?,
             slots: FilterSlots::new(&config.slots, &limits.slots, names)?,
@@ -157,12 +183,14 @@ impl Filter {
             entries: FilterEntries::new(&config.entry, &limits.entries, names)?,
             blocks: FilterBlocks::new(&config.blocks, &limits.blocks, names)?,
             blocks_meta: FilterBlocksMeta::new(&config.blocks_meta, &limits.blocks_meta, names)?,
+            notices: FilterNotices::new(&config.notices, &limits.notices, names)?,
             commitment: Self::decode_commitment(config.commitment)?,
             accounts_data_slice: FilterAccountsDataSlice::new(
                 &config.accounts_data_slice,
                 limits.accounts.data_slice_max,
             )?,
             ping: config.ping.as_ref().map(|msg| msg.id),
+            timings: config.timings.unwrap_or_default(),
         })
     }
 
@@ -201,7 +229,12 @@ impl Filter {
         Self::decode_pubkeys(pubkeys, limit).collect::<FilterResult<_>>()
     }
 
-    pub fn get_metrics(&self) -> [(&'static str, usize); 8] {
+    /// Entries are sent with transactions signatures only if requested
+    pub fn has_entries_with_signatures(&self) -> bool {
+        !self.entries.filters_with_signatures.is_empty()
+    }
+
+    pub fn get_metrics(&self) -> [(&'static str, usize); 9] {
         [
             ("accounts", self.accounts.filters.len()),
             ("slots", self.slots.filters.len()),
@@ -210,18 +243,20 @@ impl Filter {
                 "transactions_status",
                 self.transactions_status.filters.len(),
             ),
-            ("entries", self.entries.filters.len()),
+            ("entries", self.entries.len()),
             ("blocks", self.blocks.filters.len()),
             ("blocks_meta", self.blocks_meta.filters.len()),
+            ("notices", self.notices.filters.len()),
             (
                 "all",
                 self.accounts.filters.len()
                     + self.slots.filters.len()
                     + self.transactions.filters.len()
                     + self.transactions_status.filters.len()
-                    + self.entries.filters.len()
+                    + self.entries.len()
                     + self.blocks.filters.len()
-                    + self.blocks_meta.filters.len(),
+                    + self.blocks_meta.filters.len()
+                    + self.notices.filters.len(),
             ),
         ]
     }
@@ -230,6 +265,25 @@ impl Filter {
         self.commitment
     }
 
+    pub const fn get_timings(&self) -> bool {
+        self.timings
+    }
+
+    /// Keys which should be present in account or transaction to match any filter
+    pub(crate) fn get_index_keys(&self) -> FilterIndexKeys {
+        FilterIndexKeys {
+            accounts: self.accounts.get_index_keys(),
+            transactions: self
+                .transactions
+                .get_index_keys()
+                .zip(self.transactions_status.get_index_keys())
+                .map(|(mut keys, keys_status)| {
+                    keys.extend(keys_status);
+                    keys
+                }),
+        }
+    }
+
     pub fn get_updates(
         &self,
         message: &Message,
@@ -248,6 +302,7 @@ impl Filter {
             Message::Entry(message) => self.entries.get_updates(message),
             Message::Block(message) => self.blocks.get_updates(message, &self.accounts_data_slice),
             Message::BlockMeta(message) => self.blocks_meta.get_updates(message),
+            Message::Notice(message) => self.notices.get_updates(message),
         }
     }
 
@@ -257,6 +312,15 @@ impl Filter {
     }
 }
 
+/// Indexed keys of the filter, `None` if some filter can match a message without them
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct FilterIndexKeys {
+    /// Account pubkeys and owners
+    pub accounts: Option<(Vec<Pubkey>, Vec<Pubkey>)>,
+    /// Transaction account keys
+    pub transactions: Option<Vec<Pubkey>>,
+}
+
 #[derive(Debug, Default, Clone)]
 struct FilterAccounts {
     nonempty_txn_signature: Vec<(FilterName, Option<bool>)>,
@@ -266,6 +330,7 @@ struct FilterAccounts {
     owner: HashMap<Pubkey, HashSet<FilterName>>,
     owner_required: HashSet<FilterName>,
     filters: Vec<(FilterName, FilterAccountsState)>,
+    data_slice: HashMap<FilterName, FilterAccountsDataSlice>,
 }
 
 impl FilterAccounts {
@@ -310,6 +375,13 @@ impl FilterAccounts {
 
             this.filters
                 .push((names.get(name)?, FilterAccountsState::new(&filter.filters)?));
+
+            if !filter.data_slice.is_empty() {
+                this.data_slice.insert(
+                    names.get(name)?,
+                    FilterAccountsDataSlice::new(&filter.data_slice, limits.data_slice_max)?,
+                );
+            }
         }
         Ok(this)
     }
@@ -334,6 +406,20 @@ impl FilterAccounts {
         Ok(required)
     }
 
+    fn get_index_keys(&self) -> Option<(Vec<Pubkey>, Vec<Pubkey>)> {
+        self.filters
+            .iter()
+            .all(|(name, _)| {
+                self.account_required.contains(name) || self.owner_required.contains(name)
+            })
+            .then(|| {
+                (
+                    self.account.keys().copied().collect(),
+                    self.owner.keys().copied().collect(),
+                )
+            })
+    }
+
     fn get_updates(
         &self,
         message: &MessageAccount,
@@ -345,11 +431,34 @@ impl FilterAccounts {
         filter.match_owner(&message.account.owner);
         filter.match_data_lamports(&message.account.data, message.account.lamports);
         let filters = filter.get_filters();
-        filtered_updates_once_owned!(
-            filters,
-            FilteredUpdateOneof::account(message, accounts_data_slice.clone()),
-            message.created_at
-        )
+        if self.data_slice.is_empty() {
+            return filtered_updates_once_owned!(
+                filters,
+                FilteredUpdateOneof::account(message, accounts_data_slice.clone()),
+                message.created_at
+            );
+        }
+
+        // group matched filters by data slice, one update per distinct slice
+        let mut groups: SmallVec<[(&FilterAccountsDataSlice, FilteredUpdateFilters); 2]> =
+            SmallVec::new();
+        for name in filters {
+            let data_slice = self.data_slice.get(&name).unwrap_or(accounts_data_slice);
+            match groups.iter_mut().find(|(slice, _)| *slice == data_slice) {
+                Some((_, names)) => names.push(name),
+                None => groups.push((data_slice, smallvec![name])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(data_slice, filters)| {
+                FilteredUpdate::new(
+                    filters,
+                    FilteredUpdateOneof::account(message, data_slice.clone()),
+                    message.created_at,
+                )
+            })
+            .collect()
     }
 }
 
@@ -590,6 +699,7 @@ impl<'a> FilterAccountsMatch<'a> {
 struct FilterSlotsInner {
     filter_by_commitment: bool,
     interslot_updates: bool,
+    include_timings: bool,
 }
 
 impl FilterSlotsInner {
@@ -597,6 +707,7 @@ impl FilterSlotsInner {
         Self {
             filter_by_commitment: filter.filter_by_commitment.unwrap_or_default(),
             interslot_updates: filter.interslot_updates.unwrap_or_default(),
+            include_timings: filter.include_timings.unwrap_or_default(),
         }
     }
 }
@@ -631,31 +742,46 @@ impl FilterSlots {
         message: &MessageSlot,
         commitment: Option<CommitmentLevel>,
     ) -> FilteredUpdates {
-        let filters = self
-            .filters
-            .iter()
-            .filter_map(|(name, inner)| {
-                if (!inner.filter_by_commitment
-                    || commitment
-                        .map(|commitment| commitment == message.status)
-                        .unwrap_or(false))
-                    && (inner.interslot_updates
-                        || matches!(
-                            message.status,
-                            SlotStatus::Processed | SlotStatus::Confirmed | SlotStatus::Finalized
-                        ))
-                {
-                    Some(name.clone())
+        let mut filters = FilteredUpdateFilters::new();
+        let mut filters_timings = FilteredUpdateFilters::new();
+        for (name, inner) in self.filters.iter() {
+            if (!inner.filter_by_commitment
+                || commitment
+                    .map(|commitment| commitment == message.status)
+                    .unwrap_or(false))
+                && (inner.interslot_updates
+                    || matches!(
+                        message.status,
+                        SlotStatus::Processed | SlotStatus::Confirmed | SlotStatus::Finalized
+                    ))
+            {
+                if inner.include_timings {
+                    filters_timings.push(name.clone());
                 } else {
-                    None
+                    filters.push(name.clone());
                 }
-            })
-            .collect::<FilteredUpdateFilters>();
-        filtered_updates_once_owned!(
-            filters,
-            FilteredUpdateOneof::slot(message.clone()),
-            message.created_at
-        )
+            }
+        }
+
+        let mut updates = FilteredUpdates::new();
+        if !filters.is_empty() {
+            updates.push(FilteredUpdate::new(
+                filters,
+                FilteredUpdateOneof::slot(MessageSlot {
+                    timings: None,
+                    ..message.clone()
+                }),
+                message.created_at,
+            ));
+        }
+        if !filters_timings.is_empty() {
+            updates.push(FilteredUpdate::new(
+                filters_timings,
+                FilteredUpdateOneof::slot(message.clone()),
+                message.created_at,
+            ));
+        }
+        updates
     }
 }
 
@@ -746,6 +872,21 @@ impl FilterTransactions {
         })
     }
 
+    fn get_index_keys(&self) -> Option<Vec<Pubkey>> {
+        let mut keys = Vec::new();
+        for inner in self.filters.values() {
+            if !inner.account_include.is_empty() {
+                keys.extend(inner.account_include.iter().copied());
+            } else if let Some(key) = inner.account_required.iter().next() {
+                // every required key should be present, one is enough
+                keys.push(*key);
+            } else {
+                return None;
+            }
+        }
+        Some(keys)
+    }
+
     pub fn get_updates(&self, message: &MessageTransaction) -> FilteredUpdates {
         let filters = self
             .filters
@@ -818,6 +959,7 @@ impl FilterTransactions {
 #[derive(Debug, Default, Clone)]
 struct FilterEntries {
     filters: Vec<FilterName>,
+    filters_with_signatures: Vec<FilterName>,
 }
 
 impl FilterEntries {
@@ -828,16 +970,43 @@ impl FilterEntries {
     ) -> FilterResult<Self> {
         FilterLimits::check_max(configs.len(), limits.max)?;
 
-        Ok(Self {
-            filters: configs
-                .iter()
-                .map(|(name, _filter)| names.get(name))
-                .collect::<Result<_, _>>()?,
-        })
+        let mut this = Self::default();
+        for (name, filter) in configs {
+            if filter.include_transaction_signatures == Some(true) {
+                this.filters_with_signatures.push(names.get(name)?);
+            } else {
+                this.filters.push(names.get(name)?);
+            }
+        }
+        Ok(this)
+    }
+
+    fn len(&self) -> usize {
+        self.filters.len() + self.filters_with_signatures.len()
     }
 
     fn get_updates(&self, message: &Arc<MessageEntry>) -> FilteredUpdates {
-        let filters = self.filters.as_slice();
+        // entries with signatures are sent separately, once all transactions are received,
+        // entries without transactions are sent only once
+        let filters = if message.transaction_signatures.is_some() {
+            self.filters_with_signatures.as_slice()
+        } else if message.executed_transaction_count == 0
+            && !self.filters_with_signatures.is_empty()
+        {
+            let filters = self
+                .filters
+                .iter()
+                .chain(self.filters_with_signatures.iter())
+                .cloned()
+                .collect::<FilteredUpdateFilters>();
+            return filtered_updates_once_owned!(
+                filters,
+                FilteredUpdateOneof::entry(Arc::clone(message)),
+                message.created_at
+            );
+        } else {
+            self.filters.as_slice()
+        };
         filtered_updates_once_ref!(
             filters,
             FilteredUpdateOneof::entry(Arc::clone(message)),
@@ -852,6 +1021,25 @@ struct FilterBlocksInner {
     include_transactions: Option<bool>,
     include_accounts: Option<bool>,
     include_entries: Option<bool>,
+    include_incomplete: bool,
+    account_owner: HashSet<Pubkey>,
+    account_filters: FilterAccountsState,
+    accounts_data_slice: Option<FilterAccountsDataSlice>,
+}
+
+impl FilterBlocksInner {
+    fn is_account_match(&self, account: &MessageAccountInfo) -> bool {
+        if !self.account_include.is_empty() && !self.account_include.contains(&account.pubkey) {
+            return false;
+        }
+        if !self.account_owner.is_empty() && !self.account_owner.contains(&account.owner) {
+            return false;
+        }
+        self.account_filters.is_empty()
+            || self
+                .account_filters
+                .is_match(&account.data, account.lamports)
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -886,6 +1074,15 @@ impl FilterBlocks {
             if !(matches!(filter.include_entries, None | Some(false)) || limits.include_accounts) {
                 return Err(FilterError::CreateBlocksNotAllowed("entries"));
             }
+            FilterLimits::check_pubkey_max(filter.account_owner.len(), limits.account_owner_max)?;
+            let accounts_data_slice = if filter.accounts_data_slice.is_empty() {
+                None
+            } else {
+                Some(FilterAccountsDataSlice::new(
+                    &filter.accounts_data_slice,
+                    limits.accounts_data_slice_max,
+                )?)
+            };
 
             this.filters.insert(
                 names.get(name)?,
@@ -897,6 +1094,13 @@ impl FilterBlocks {
                     include_transactions: filter.include_transactions,
                     include_accounts: filter.include_accounts,
                     include_entries: filter.include_entries,
+                    include_incomplete: filter.include_incomplete.unwrap_or_default(),
+                    account_owner: Filter::decode_pubkeys_into_set(
+                        &filter.account_owner,
+                        &limits.account_owner_reject,
+                    )?,
+                    account_filters: FilterAccountsState::new(&filter.account_filters)?,
+                    accounts_data_slice,
                 },
             );
         }
@@ -909,9 +1113,20 @@ impl FilterBlocks {
         accounts_data_slice: &FilterAccountsDataSlice,
     ) -> FilteredUpdates {
         let mut updates = FilteredUpdates::new();
+        // for incomplete blocks without `include_incomplete` only failure is sent
+        let mut failure_filters = FilteredUpdateFilters::new();
         for (filter, inner) in self.filters.iter() {
+            if message.incomplete.is_some() && !inner.include_incomplete {
+                failure_filters.push(filter.clone());
+                continue;
+            }
+
+            let include_transactions = matches!(inner.include_transactions, None | Some(true));
+            let include_accounts = inner.include_accounts == Some(true);
+            let include_entries = inner.include_entries == Some(true);
+
             #[allow(clippy::unnecessary_filter_map)]
-            let transactions = if matches!(inner.include_transactions, None | Some(true)) {
+            let transactions = if include_transactions {
                 message
                     .transactions
                     .iter()
@@ -933,26 +1148,18 @@ impl FilterBlocks {
                 vec![]
             };
 
-            #[allow(clippy::unnecessary_filter_map)]
-            let accounts = if inner.include_accounts == Some(true) {
+            let accounts = if include_accounts {
                 message
                     .accounts
                     .iter()
-                    .filter_map(|account| {
-                        if !inner.account_include.is_empty()
-                            && !inner.account_include.contains(&account.pubkey)
-                        {
-                            None
-                        } else {
-                            Some(Arc::clone(account))
-                        }
-                    })
+                    .filter(|account| inner.is_account_match(account))
+                    .map(Arc::clone)
                     .collect::<Vec<_>>()
             } else {
                 vec![]
             };
 
-            let entries = if inner.include_entries == Some(true) {
+            let entries = if include_entries {
                 message.entries.to_vec()
             } else {
                 vec![]
@@ -966,13 +1173,28 @@ impl FilterBlocks {
                     meta: Arc::clone(&message.meta),
                     transactions,
                     updated_account_count: message.updated_account_count,
-                    accounts_data_slice: accounts_data_slice.clone(),
+                    accounts_data_slice: inner
+                        .accounts_data_slice
+                        .as_ref()
+                        .unwrap_or(accounts_data_slice)
+                        .clone(),
                     accounts,
                     entries,
+                    incomplete: message.incomplete,
                 })),
                 message.created_at,
             ));
         }
+        if !failure_filters.is_empty() {
+            updates.push(FilteredUpdate::new(
+                failure_filters,
+                FilteredUpdateOneof::block_failure(SubscribeUpdateBlockFailure {
+                    slot: message.meta.slot,
+                    incomplete: message.incomplete,
+                }),
+                message.created_at,
+            ));
+        }
         updates
     }
 }
@@ -1008,6 +1230,37 @@ impl FilterBlocksMeta {
     }
 }
 
+#[derive(Debug, Default, Clone)]
+struct FilterNotices {
+    filters: Vec<FilterName>,
+}
+
+impl FilterNotices {
+    fn new(
+        configs: &HashMap<String, SubscribeRequestFilterNotices>,
+        limits: &FilterLimitsNotices,
+        names: &mut FilterNames,
+    ) -> FilterResult<Self> {
+        FilterLimits::check_max(configs.len(), limits.max)?;
+
+        Ok(Self {
+            filters: configs
+                .iter()
+                .map(|(name, _filter)| names.get(name))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn get_updates(&self, message: &Arc<MessageNotice>) -> FilteredUpdates {
+        let filters = self.filters.as_slice();
+        filtered_updates_once_ref!(
+            filters,
+            FilteredUpdateOneof::notice(Arc::clone(message)),
+            message.created_at
+        )
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct FilterAccountsDataSlice(Arc<Vec<Range<usize>>>);
 
@@ -1107,16 +1360,24 @@ mod tests {
         crate::{
             convert_to,
             geyser::{
-                SubscribeRequest, SubscribeRequestFilterAccounts,
-                SubscribeRequestFilterTransactions,
+                subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestAccountsDataSlice,
+                SubscribeRequestFilterAccounts, SubscribeRequestFilterBlocks,
+                SubscribeRequestFilterEntry, SubscribeRequestFilterNotices,
+                SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+                SubscribeUpdateBlockIncomplete, SubscribeUpdateBlockMeta,
+                SubscribeUpdateSlotTimings,
             },
             plugin::{
                 filter::{
-                    limits::FilterLimits,
+                    limits::{FilterLimits, FilterLimitsKind},
                     message::{FilteredUpdateFilters, FilteredUpdateOneof},
                     name::{FilterName, FilterNames},
                 },
-                message::{Message, MessageTransaction, MessageTransactionInfo},
+                message::{
+                    Message, MessageAccount, MessageAccountInfo, MessageBlock, MessageBlockMeta,
+                    MessageEntry, MessageNotice, MessageSlot, MessageTransaction,
+                    MessageTransactionInfo, SlotStatus,
+                },
             },
         },
         prost_types::Timestamp,
@@ -1124,11 +1385,12 @@ mod tests {
         solana_keypair::Keypair,
         solana_message::{v0::LoadedAddresses, Message as SolMessage, MessageHeader},
         solana_pubkey::Pubkey,
+        solana_signature::Signature,
         solana_signer::Signer,
         solana_transaction::{sanitized::SanitizedTransaction, Transaction},
         solana_transaction_status::TransactionStatusMeta,
         std::{
-            collections::HashMap,
+            collections::{HashMap, HashSet},
             sync::Arc,
             time::{Duration, SystemTime},
         },
@@ -1204,12 +1466,31 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names());
         assert!(filter.is_ok());
     }
 
+    #[test]
+    fn test_filters_kinds_not_allowed() {
+        let mut config = SubscribeRequest {
+            slots: HashMap::from([("slots".to_owned(), Default::default())]),
+            ..Default::default()
+        };
+        let limit = FilterLimits {
+            kinds: Some(HashSet::from([FilterLimitsKind::Slots])),
+            ..Default::default()
+        };
+        assert!(Filter::new(&config, &limit, &mut create_filter_names()).is_ok());
+
+        config.accounts = HashMap::from([("accounts".to_owned(), Default::default())]);
+        let error = Filter::new(&config, &limit, &mut create_filter_names()).unwrap_err();
+        assert_eq!(error.to_string(), "Subscribe on `accounts` is not allowed");
+    }
+
     #[test]
     fn test_filters_account_empty() {
         let mut accounts = HashMap::new();
@@ -1221,6 +1502,7 @@ mod tests {
                 account: vec![],
                 owner: vec![],
                 filters: vec![],
+                data_slice: vec![],
             },
         );
 
@@ -1236,6 +1518,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let mut limit = FilterLimits::default();
         limit.accounts.any = false;
@@ -1272,6 +1556,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let mut limit = FilterLimits::default();
         limit.transactions.any = false;
@@ -1307,6 +1593,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let mut limit = FilterLimits::default();
         limit.transactions.any = false;
@@ -1315,6 +1603,118 @@ mod tests {
         assert!(filter_res.is_ok());
     }
 
+    #[test]
+    fn test_filters_notices() {
+        let mut config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let message = Message::Notice(Arc::new(MessageNotice::slot_stall(
+            SlotStatus::Processed,
+            true,
+            Some(42),
+            Duration::from_secs(30),
+        )));
+
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        assert!(filter.get_updates(&message, None).is_empty());
+
+        config
+            .notices
+            .insert("notices".to_owned(), SubscribeRequestFilterNotices {});
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+        let updates = filter.get_updates(&message, None);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(
+            updates[0].filters,
+            FilteredUpdateFilters::from_vec(vec![FilterName::new("notices")])
+        );
+        assert!(matches!(updates[0].message, FilteredUpdateOneof::Notice(_)));
+
+        let mut limit = FilterLimits::default();
+        limit.notices.max = 0;
+        assert!(Filter::new(&config, &limit, &mut create_filter_names()).is_err());
+    }
+
+    #[test]
+    fn test_slots_include_timings() {
+        let mut slots = HashMap::new();
+        for (name, include_timings) in [("a", None), ("b", Some(false)), ("c", Some(true))] {
+            slots.insert(
+                name.to_owned(),
+                SubscribeRequestFilterSlots {
+                    filter_by_commitment: None,
+                    interslot_updates: None,
+                    include_timings,
+                },
+            );
+        }
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots,
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+
+        let created_at = Timestamp::from(SystemTime::now());
+        let timings = SubscribeUpdateSlotTimings {
+            processed: Some(created_at),
+            ..Default::default()
+        };
+        let message = Message::Slot(MessageSlot {
+            slot: 42,
+            parent: Some(41),
+            status: SlotStatus::Processed,
+            dead_error: None,
+            timings: Some(timings),
+            created_at,
+        });
+
+        let mut updates = filter
+            .get_updates(&message, None)
+            .into_iter()
+            .map(|update| {
+                let mut update = update.as_subscribe_update();
+                let Some(UpdateOneof::Slot(slot)) = update.update_oneof else {
+                    panic!("expected slot update");
+                };
+                update.filters.sort();
+                (update.filters, slot.timings)
+            })
+            .collect::<Vec<_>>();
+        updates.sort_by_key(|(filters, _timings)| filters.clone());
+        assert_eq!(
+            updates,
+            vec![
+                (vec!["a".to_owned(), "b".to_owned()], None),
+                (vec!["c".to_owned()], Some(timings)),
+            ]
+        );
+    }
+
     #[test]
     fn test_transaction_include_a() {
         let mut transactions = HashMap::new();
@@ -1348,6 +1748,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
@@ -1413,6 +1815,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
@@ -1478,6 +1882,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
@@ -1529,6 +1935,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
@@ -1602,6 +2010,8 @@ mod tests {
             accounts_data_slice: Vec::new(),
             ping: None,
             from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
         };
         let limit = FilterLimits::default();
         let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
@@ -1613,4 +2023,361 @@ mod tests {
             assert!(message.filters.is_empty());
         }
     }
+
+    #[test]
+    fn test_block_incomplete() {
+        let keypair = Keypair::new();
+        let message_transaction = create_message_transaction(&keypair, vec![keypair.pubkey()]);
+        let block = MessageBlock::new_incomplete(
+            Arc::new(MessageBlockMeta {
+                block_meta: SubscribeUpdateBlockMeta {
+                    slot: 100,
+                    executed_transaction_count: 2,
+                    ..SubscribeUpdateBlockMeta::default()
+                },
+                created_at: Timestamp::from(SystemTime::now()),
+            }),
+            vec![message_transaction.transaction],
+            vec![],
+            vec![],
+            SubscribeUpdateBlockIncomplete {
+                expected_transaction_count: Some(2),
+                observed_transaction_count: 1,
+                expected_entries_count: Some(0),
+                observed_entries_count: 0,
+            },
+        );
+        let message_incomplete = block.incomplete;
+        let message = Message::Block(Arc::new(block));
+
+        let mut blocks = HashMap::new();
+        for (name, include_incomplete) in [
+            ("failure", None),
+            ("failure_explicit", Some(false)),
+            ("incomplete", Some(true)),
+        ] {
+            blocks.insert(
+                name.to_owned(),
+                SubscribeRequestFilterBlocks {
+                    account_include: vec![],
+                    include_transactions: Some(true),
+                    include_accounts: None,
+                    include_entries: None,
+                    include_incomplete,
+                    account_owner: vec![],
+                    account_filters: vec![],
+                    accounts_data_slice: vec![],
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks,
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+
+        let updates = filter.get_updates(&message, None);
+        assert_eq!(updates.len(), 2);
+        for update in updates {
+            let mut filters = update
+                .filters
+                .iter()
+                .map(|name| name.as_ref().to_owned())
+                .collect::<Vec<_>>();
+            filters.sort();
+            match update.message {
+                FilteredUpdateOneof::Block(block) => {
+                    assert_eq!(filters, vec!["incomplete".to_owned()]);
+                    assert!(block.incomplete.is_some());
+                    assert_eq!(block.transactions.len(), 1);
+                }
+                FilteredUpdateOneof::BlockFailure(failure) => {
+                    assert_eq!(
+                        filters,
+                        vec!["failure".to_owned(), "failure_explicit".to_owned()]
+                    );
+                    assert_eq!(failure.slot, 100);
+                    assert_eq!(failure.incomplete, message_incomplete);
+                }
+                message => panic!("unexpected update: {message:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_accounts_owner_data_slice() {
+        let owner = Pubkey::new_unique();
+        let accounts = [owner, Pubkey::new_unique()]
+            .into_iter()
+            .map(|owner| {
+                Arc::new(MessageAccountInfo {
+                    pubkey: Pubkey::new_unique(),
+                    lamports: 42,
+                    owner,
+                    executable: false,
+                    rent_epoch: 0,
+                    data: vec![1, 2, 3, 4],
+                    write_version: 1,
+                    txn_signature: None,
+                })
+            })
+            .collect::<Vec<_>>();
+        let block = MessageBlock::new(
+            Arc::new(MessageBlockMeta {
+                block_meta: SubscribeUpdateBlockMeta {
+                    slot: 100,
+                    ..SubscribeUpdateBlockMeta::default()
+                },
+                created_at: Timestamp::from(SystemTime::now()),
+            }),
+            vec![],
+            accounts,
+            vec![],
+        );
+        let message = Message::Block(Arc::new(block));
+
+        let mut blocks = HashMap::new();
+        blocks.insert(
+            "blocks".to_owned(),
+            SubscribeRequestFilterBlocks {
+                account_include: vec![],
+                include_transactions: None,
+                include_accounts: Some(true),
+                include_entries: None,
+                include_incomplete: None,
+                account_owner: vec![owner.to_string()],
+                account_filters: vec![],
+                accounts_data_slice: vec![SubscribeRequestAccountsDataSlice {
+                    offset: 1,
+                    length: 2,
+                }],
+            },
+        );
+
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks,
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+
+        let updates = filter.get_updates(&message, None);
+        assert_eq!(updates.len(), 1);
+        let FilteredUpdateOneof::Block(block) = &updates[0].message else {
+            panic!("expected block update");
+        };
+        assert_eq!(block.updated_account_count, 2);
+        assert_eq!(block.accounts.len(), 1);
+        assert_eq!(block.accounts[0].owner, owner);
+        assert_eq!(
+            block.accounts_data_slice.get_slice(&block.accounts[0].data),
+            vec![2, 3]
+        );
+
+        let mut limit = FilterLimits::default();
+        limit.blocks.account_owner_max = 0;
+        assert!(Filter::new(&config, &limit, &mut create_filter_names()).is_err());
+    }
+
+    #[test]
+    fn test_accounts_data_slice_per_filter() {
+        let owner = Pubkey::new_unique();
+        let message = Message::Account(MessageAccount {
+            account: Arc::new(MessageAccountInfo {
+                pubkey: Pubkey::new_unique(),
+                lamports: 42,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+                data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+                write_version: 1,
+                txn_signature: None,
+            }),
+            slot: 100,
+            is_startup: false,
+            created_at: Timestamp::from(SystemTime::now()),
+        });
+
+        let mut accounts = HashMap::new();
+        for (name, data_slice) in [
+            (
+                "a",
+                vec![SubscribeRequestAccountsDataSlice {
+                    offset: 0,
+                    length: 2,
+                }],
+            ),
+            ("b", vec![]),
+            (
+                "c",
+                vec![SubscribeRequestAccountsDataSlice {
+                    offset: 1,
+                    length: 2,
+                }],
+            ),
+            (
+                "d",
+                vec![SubscribeRequestAccountsDataSlice {
+                    offset: 0,
+                    length: 2,
+                }],
+            ),
+        ] {
+            accounts.insert(
+                name.to_owned(),
+                SubscribeRequestFilterAccounts {
+                    nonempty_txn_signature: None,
+                    account: vec![],
+                    owner: vec![owner.to_string()],
+                    filters: vec![],
+                    data_slice,
+                },
+            );
+        }
+
+        let config = SubscribeRequest {
+            accounts,
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry: HashMap::new(),
+            commitment: None,
+            accounts_data_slice: vec![SubscribeRequestAccountsDataSlice {
+                offset: 5,
+                length: 2,
+            }],
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+
+        let mut updates = filter
+            .get_updates(&message, None)
+            .into_iter()
+            .map(|update| {
+                let FilteredUpdateOneof::Account(account) = &update.message else {
+                    panic!("expected account update");
+                };
+                let mut filters = update
+                    .filters
+                    .iter()
+                    .map(|name| name.as_ref().to_owned())
+                    .collect::<Vec<_>>();
+                filters.sort();
+                (filters, account.data_slice.get_slice(&account.account.data))
+            })
+            .collect::<Vec<_>>();
+        updates.sort();
+        assert_eq!(
+            updates,
+            vec![
+                (vec!["a".to_owned(), "d".to_owned()], vec![1, 2]),
+                (vec!["b".to_owned()], vec![6, 7]),
+                (vec!["c".to_owned()], vec![2, 3]),
+            ]
+        );
+
+        // per-filter slices are checked without request-level slices
+        let config = SubscribeRequest {
+            accounts_data_slice: vec![],
+            ..config
+        };
+        let mut limit = FilterLimits::default();
+        limit.accounts.data_slice_max = 1;
+        assert!(Filter::new(&config, &limit, &mut create_filter_names()).is_ok());
+        limit.accounts.data_slice_max = 0;
+        assert!(Filter::new(&config, &limit, &mut create_filter_names()).is_err());
+    }
+
+    #[test]
+    fn test_entries_transaction_signatures() {
+        let mut entry = HashMap::new();
+        entry.insert("plain".to_owned(), SubscribeRequestFilterEntry::default());
+        entry.insert(
+            "signatures".to_owned(),
+            SubscribeRequestFilterEntry {
+                include_transaction_signatures: Some(true),
+            },
+        );
+
+        let config = SubscribeRequest {
+            accounts: HashMap::new(),
+            slots: HashMap::new(),
+            transactions: HashMap::new(),
+            transactions_status: HashMap::new(),
+            blocks: HashMap::new(),
+            blocks_meta: HashMap::new(),
+            entry,
+            commitment: None,
+            accounts_data_slice: Vec::new(),
+            ping: None,
+            from_slot: None,
+            notices: HashMap::new(),
+            timings: None,
+        };
+        let limit = FilterLimits::default();
+        let filter = Filter::new(&config, &limit, &mut create_filter_names()).unwrap();
+
+        let entry = MessageEntry {
+            slot: 100,
+            index: 0,
+            num_hashes: 1,
+            hash: Hash::default(),
+            executed_transaction_count: 1,
+            starting_transaction_index: 0,
+            transaction_signatures: None,
+            created_at: Timestamp::from(SystemTime::now()),
+        };
+        let signed_entry = entry.with_transaction_signatures(vec![Signature::default()]);
+        let empty_entry = MessageEntry {
+            executed_transaction_count: 0,
+            ..entry.clone()
+        };
+        assert!(filter.has_entries_with_signatures());
+
+        for (entry, names) in [
+            (entry, vec!["plain"]),
+            (signed_entry, vec!["signatures"]),
+            (empty_entry, vec!["plain", "signatures"]),
+        ] {
+            let message = Message::Entry(Arc::new(entry));
+            let updates = filter.get_updates(&message, None);
+            assert_eq!(updates.len(), 1);
+            let mut filters = updates[0].filters.to_vec();
+            filters.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+            assert_eq!(
+                filters,
+                names.into_iter().map(FilterName::new).collect::<Vec<_>>()
+            );
+        }
+    }
 }
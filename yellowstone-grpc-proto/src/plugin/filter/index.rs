@@ -0,0 +1,371 @@
+use {
+    crate::plugin::{
+        filter::{filter::FilterIndexKeys, Filter},
+        message::Message,
+    },
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::RwLock,
+    },
+};
+
+/// Stream with indexed filter, routes are valid only if created after the last filter update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterIndexClient {
+    id: usize,
+    generation: u64,
+    accounts: bool,
+    transactions: bool,
+}
+
+#[derive(Debug, Default)]
+struct FilterIndexInner {
+    generation: u64,
+    clients: HashMap<usize, FilterIndexKeys>,
+    accounts: HashMap<Pubkey, HashSet<usize>>,
+    owners: HashMap<Pubkey, HashSet<usize>>,
+    transactions: HashMap<Pubkey, HashSet<usize>>,
+}
+
+impl FilterIndexInner {
+    fn insert(map: &mut HashMap<Pubkey, HashSet<usize>>, keys: &[Pubkey], id: usize) {
+        for key in keys {
+            map.entry(*key).or_default().insert(id);
+        }
+    }
+
+    fn remove(map: &mut HashMap<Pubkey, HashSet<usize>>, keys: &[Pubkey], id: usize) {
+        for key in keys {
+            if let Some(ids) = map.get_mut(key) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    map.remove(key);
+                }
+            }
+        }
+    }
+
+    fn remove_client(&mut self, id: usize) {
+        if let Some(keys) = self.clients.remove(&id) {
+            if let Some((accounts, owners)) = &keys.accounts {
+                Self::remove(&mut self.accounts, accounts, id);
+                Self::remove(&mut self.owners, owners, id);
+            }
+            if let Some(transactions) = &keys.transactions {
+                Self::remove(&mut self.transactions, transactions, id);
+            }
+        }
+    }
+}
+
+/// Inverted index from account pubkeys, owners and transaction account keys to streams.
+/// Accounts and transactions are routed only to matched streams, filters which can match
+/// a message without indexed keys are evaluated for every message.
+#[derive(Debug, Default)]
+pub struct FilterIndex {
+    inner: RwLock<FilterIndexInner>,
+}
+
+impl FilterIndex {
+    /// Replace stream keys in the index, `None` if nothing can be indexed
+    pub fn update(&self, id: usize, filter: &Filter) -> Option<FilterIndexClient> {
+        let keys = filter.get_index_keys();
+
+        let mut inner = self.inner.write().expect("poisoned");
+        inner.remove_client(id);
+        inner.generation += 1;
+        if keys.accounts.is_none() && keys.transactions.is_none() {
+            return None;
+        }
+
+        if let Some((accounts, owners)) = &keys.accounts {
+            FilterIndexInner::insert(&mut inner.accounts, accounts, id);
+            FilterIndexInner::insert(&mut inner.owners, owners, id);
+        }
+        if let Some(transactions) = &keys.transactions {
+            FilterIndexInner::insert(&mut inner.transactions, transactions, id);
+        }
+        let client = FilterIndexClient {
+            id,
+            generation: inner.generation,
+            accounts: keys.accounts.is_some(),
+            transactions: keys.transactions.is_some(),
+        };
+        inner.clients.insert(id, keys);
+        Some(client)
+    }
+
+    pub fn remove(&self, id: usize) {
+        let mut inner = self.inner.write().expect("poisoned");
+        inner.remove_client(id);
+        inner.generation += 1;
+    }
+
+    /// Routes for broadcasted messages, `None` if there are no indexed streams
+    pub fn get_routes(&self, messages: &[(u64, Message)]) -> Option<FilterIndexRoutes> {
+        let inner = self.inner.read().expect("poisoned");
+        if inner.clients.is_empty() {
+            return None;
+        }
+
+        let mut routes = FilterIndexRoutes {
+            generation: inner.generation,
+            ..Default::default()
+        };
+        for (position, (_msgid, message)) in messages.iter().enumerate() {
+            routes.slot_max = routes.slot_max.max(Some(message.get_slot()));
+            match message {
+                Message::Account(msg) => {
+                    routes.accounts.push(position);
+                    let ids = inner
+                        .accounts
+                        .get(&msg.account.pubkey)
+                        .into_iter()
+                        .chain(inner.owners.get(&msg.account.owner))
+                        .flatten();
+                    FilterIndexRoutes::push(&mut routes.accounts_matched, ids, position);
+                }
+                Message::Transaction(msg) => {
+                    routes.transactions.push(position);
+                    let ids = msg
+                        .transaction
+                        .account_keys
+                        .iter()
+                        .filter_map(|key| inner.transactions.get(key))
+                        .flatten();
+                    FilterIndexRoutes::push(&mut routes.transactions_matched, ids, position);
+                }
+                _ => routes.others.push(position),
+            }
+        }
+        Some(routes)
+    }
+}
+
+/// Positions of broadcasted messages by kind and matched positions of indexed streams
+#[derive(Debug, Default)]
+pub struct FilterIndexRoutes {
+    generation: u64,
+    slot_max: Option<Slot>,
+    others: Vec<usize>,
+    accounts: Vec<usize>,
+    accounts_matched: HashMap<usize, Vec<usize>>,
+    transactions: Vec<usize>,
+    transactions_matched: HashMap<usize, Vec<usize>>,
+}
+
+impl FilterIndexRoutes {
+    fn push<'a>(
+        matched: &mut HashMap<usize, Vec<usize>>,
+        ids: impl Iterator<Item = &'a usize>,
+        position: usize,
+    ) {
+        for id in ids {
+            let positions = matched.entry(*id).or_default();
+            if positions.last() != Some(&position) {
+                positions.push(position);
+            }
+        }
+    }
+
+    pub const fn get_slot_max(&self) -> Option<Slot> {
+        self.slot_max
+    }
+
+    /// Sorted positions of messages to evaluate by stream filter,
+    /// `None` if routes were created before the last stream filter update
+    pub fn get_positions(&self, client: &FilterIndexClient) -> Option<Vec<usize>> {
+        if self.generation < client.generation {
+            return None;
+        }
+
+        let accounts = if client.accounts {
+            self.accounts_matched.get(&client.id)
+        } else {
+            Some(&self.accounts)
+        };
+        let transactions = if client.transactions {
+            self.transactions_matched.get(&client.id)
+        } else {
+            Some(&self.transactions)
+        };
+        let mut positions = self.others.clone();
+        positions.extend(accounts.into_iter().chain(transactions).flatten());
+        positions.sort_unstable();
+        Some(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::FilterIndex,
+        crate::{
+            geyser::{
+                SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots,
+                SubscribeRequestFilterTransactions,
+            },
+            plugin::{
+                filter::{
+                    limits::FilterLimits,
+                    message::tests::{create_accounts_raw, load_predefined_transactions},
+                    name::FilterNames,
+                    Filter,
+                },
+                message::{Message, MessageAccount, MessageSlot, MessageTransaction, SlotStatus},
+            },
+        },
+        prost_types::Timestamp,
+        std::{collections::HashMap, sync::Arc, time::SystemTime},
+    };
+
+    fn create_filter(
+        accounts: Vec<SubscribeRequestFilterAccounts>,
+        transactions: Vec<SubscribeRequestFilterTransactions>,
+    ) -> Filter {
+        let config = SubscribeRequest {
+            accounts: accounts
+                .into_iter()
+                .enumerate()
+                .map(|(index, filter)| (format!("accounts{index}"), filter))
+                .collect(),
+            slots: HashMap::from([("slots".to_owned(), SubscribeRequestFilterSlots::default())]),
+            transactions: transactions
+                .into_iter()
+                .enumerate()
+                .map(|(index, filter)| (format!("transactions{index}"), filter))
+                .collect(),
+            ..Default::default()
+        };
+        let mut names = FilterNames::new(64, 1024, std::time::Duration::from_secs(1));
+        Filter::new(&config, &FilterLimits::default(), &mut names).unwrap()
+    }
+
+    fn create_messages() -> Vec<(u64, Message)> {
+        let created_at = Timestamp::from(SystemTime::now());
+        let mut messages = vec![Message::Slot(MessageSlot {
+            slot: 100,
+            parent: None,
+            status: SlotStatus::Processed,
+            dead_error: None,
+            timings: None,
+            created_at,
+        })];
+        messages.extend(create_accounts_raw().into_iter().map(|account| {
+            Message::Account(MessageAccount {
+                account,
+                slot: 100,
+                is_startup: false,
+                created_at,
+            })
+        }));
+        messages.extend(
+            load_predefined_transactions()
+                .into_iter()
+                .take(4)
+                .map(|transaction| {
+                    let mut transaction = Arc::unwrap_or_clone(transaction);
+                    transaction.fill_account_keys().expect("valid account keys");
+                    Message::Transaction(MessageTransaction {
+                        transaction: Arc::new(transaction),
+                        slot: 101,
+                        created_at,
+                    })
+                }),
+        );
+        messages
+            .into_iter()
+            .enumerate()
+            .map(|(msgid, message)| (msgid as u64, message))
+            .collect()
+    }
+
+    fn find(messages: &[(u64, Message)], f: impl Fn(&Message) -> bool) -> Vec<usize> {
+        messages
+            .iter()
+            .enumerate()
+            .filter_map(|(position, (_msgid, message))| f(message).then_some(position))
+            .collect()
+    }
+
+    #[test]
+    fn test_index_accounts() {
+        let messages = create_messages();
+        let Message::Account(account) = &messages[1].1 else {
+            panic!("expected account");
+        };
+        let pubkey = account.account.pubkey;
+        let owner = account.account.owner;
+
+        let index = FilterIndex::default();
+        let filter = create_filter(
+            vec![
+                SubscribeRequestFilterAccounts {
+                    account: vec![pubkey.to_string()],
+                    ..Default::default()
+                },
+                SubscribeRequestFilterAccounts {
+                    owner: vec![owner.to_string()],
+                    ..Default::default()
+                },
+            ],
+            vec![],
+        );
+        let client = index.update(1, &filter).expect("indexed");
+        let routes = index.get_routes(&messages).expect("routes");
+        let expected = find(&messages, |message| match message {
+            Message::Account(msg) => msg.account.pubkey == pubkey || msg.account.owner == owner,
+            Message::Transaction(_) => false,
+            _ => true,
+        });
+        assert_eq!(routes.get_positions(&client), Some(expected));
+        assert_eq!(routes.get_slot_max(), Some(101));
+
+        // routes created before filter update
+        let client = index.update(1, &filter).expect("indexed");
+        assert_eq!(routes.get_positions(&client), None);
+
+        index.remove(1);
+        assert!(index.get_routes(&messages).is_none());
+    }
+
+    #[test]
+    fn test_index_transactions() {
+        let messages = create_messages();
+        let Message::Transaction(transaction) = &messages[messages.len() - 1].1 else {
+            panic!("expected transaction");
+        };
+        let key = *transaction
+            .transaction
+            .account_keys
+            .iter()
+            .next()
+            .expect("account key");
+
+        let index = FilterIndex::default();
+        let filter = create_filter(
+            vec![SubscribeRequestFilterAccounts::default()],
+            vec![SubscribeRequestFilterTransactions {
+                account_required: vec![key.to_string()],
+                ..Default::default()
+            }],
+        );
+        let client = index.update(2, &filter).expect("indexed");
+        let routes = index.get_routes(&messages).expect("routes");
+        let expected = find(&messages, |message| match message {
+            Message::Transaction(msg) => msg.transaction.account_keys.contains(&key),
+            _ => true,
+        });
+        assert_eq!(routes.get_positions(&client), Some(expected));
+
+        // accounts and transactions without keys are not indexed
+        let filter = create_filter(
+            vec![SubscribeRequestFilterAccounts::default()],
+            vec![SubscribeRequestFilterTransactions::default()],
+        );
+        assert_eq!(index.update(2, &filter), None);
+        assert!(index.get_routes(&messages).is_none());
+    }
+}
@@ -14,6 +14,8 @@ pub enum FilterLimitsCheckError {
     MaxPubkey { max: usize },
     #[error("Pubkey {pubkey} in filters is not allowed")]
     PubkeyReject { pubkey: Pubkey },
+    #[error("Subscribe on `{}` is not allowed", kind.as_str())]
+    KindNotAllowed { kind: FilterLimitsKind },
 }
 
 pub type FilterLimitsCheckResult = Result<(), FilterLimitsCheckError>;
@@ -28,9 +30,52 @@ pub struct FilterLimits {
     pub blocks: FilterLimitsBlocks,
     pub blocks_meta: FilterLimitsBlocksMeta,
     pub entries: FilterLimitsEntries,
+    pub notices: FilterLimitsNotices,
+    /// Allowed subscribe kinds, all kinds allowed if not set
+    pub kinds: Option<HashSet<FilterLimitsKind>>,
 }
 
 impl FilterLimits {
+    /// Limits allowed by both `self` and `other`
+    pub fn restrict(&self, other: &Self) -> Self {
+        Self {
+            accounts: self.accounts.restrict(&other.accounts),
+            slots: FilterLimitsSlots {
+                max: self.slots.max.min(other.slots.max),
+            },
+            transactions: self.transactions.restrict(&other.transactions),
+            transactions_status: self
+                .transactions_status
+                .restrict(&other.transactions_status),
+            blocks: self.blocks.restrict(&other.blocks),
+            blocks_meta: FilterLimitsBlocksMeta {
+                max: self.blocks_meta.max.min(other.blocks_meta.max),
+            },
+            entries: FilterLimitsEntries {
+                max: self.entries.max.min(other.entries.max),
+            },
+            notices: FilterLimitsNotices {
+                max: self.notices.max.min(other.notices.max),
+            },
+            kinds: match (&self.kinds, &other.kinds) {
+                (Some(kinds), Some(other_kinds)) => {
+                    Some(kinds.intersection(other_kinds).copied().collect())
+                }
+                (Some(kinds), None) | (None, Some(kinds)) => Some(kinds.clone()),
+                (None, None) => None,
+            },
+        }
+    }
+
+    pub fn check_kind(&self, kind: FilterLimitsKind, is_empty: bool) -> FilterLimitsCheckResult {
+        match &self.kinds {
+            Some(kinds) if !is_empty && !kinds.contains(&kind) => {
+                Err(FilterLimitsCheckError::KindNotAllowed { kind })
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub const fn check_max(len: usize, max: usize) -> FilterLimitsCheckResult {
         if len <= max {
             Ok(())
@@ -64,6 +109,34 @@ impl FilterLimits {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterLimitsKind {
+    Accounts,
+    Slots,
+    Transactions,
+    TransactionsStatus,
+    Blocks,
+    BlocksMeta,
+    Entries,
+    Notices,
+}
+
+impl FilterLimitsKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Accounts => "accounts",
+            Self::Slots => "slots",
+            Self::Transactions => "transactions",
+            Self::TransactionsStatus => "transactions_status",
+            Self::Blocks => "blocks",
+            Self::BlocksMeta => "blocks_meta",
+            Self::Entries => "entries",
+            Self::Notices => "notices",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct FilterLimitsAccounts {
@@ -78,6 +151,20 @@ pub struct FilterLimitsAccounts {
     pub data_slice_max: usize,
 }
 
+impl FilterLimitsAccounts {
+    fn restrict(&self, other: &Self) -> Self {
+        Self {
+            max: self.max.min(other.max),
+            any: self.any && other.any,
+            account_max: self.account_max.min(other.account_max),
+            account_reject: &self.account_reject | &other.account_reject,
+            owner_max: self.owner_max.min(other.owner_max),
+            owner_reject: &self.owner_reject | &other.owner_reject,
+            data_slice_max: self.data_slice_max.min(other.data_slice_max),
+        }
+    }
+}
+
 impl Default for FilterLimitsAccounts {
     fn default() -> Self {
         Self {
@@ -121,6 +208,19 @@ pub struct FilterLimitsTransactions {
     pub account_required_max: usize,
 }
 
+impl FilterLimitsTransactions {
+    fn restrict(&self, other: &Self) -> Self {
+        Self {
+            max: self.max.min(other.max),
+            any: self.any && other.any,
+            account_include_max: self.account_include_max.min(other.account_include_max),
+            account_include_reject: &self.account_include_reject | &other.account_include_reject,
+            account_exclude_max: self.account_exclude_max.min(other.account_exclude_max),
+            account_required_max: self.account_required_max.min(other.account_required_max),
+        }
+    }
+}
+
 impl Default for FilterLimitsTransactions {
     fn default() -> Self {
         Self {
@@ -144,11 +244,36 @@ pub struct FilterLimitsBlocks {
     pub account_include_any: bool,
     #[serde(deserialize_with = "deserialize_pubkey_set")]
     pub account_include_reject: HashSet<Pubkey>,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub account_owner_max: usize,
+    #[serde(deserialize_with = "deserialize_pubkey_set")]
+    pub account_owner_reject: HashSet<Pubkey>,
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub accounts_data_slice_max: usize,
     pub include_transactions: bool,
     pub include_accounts: bool,
     pub include_entries: bool,
 }
 
+impl FilterLimitsBlocks {
+    fn restrict(&self, other: &Self) -> Self {
+        Self {
+            max: self.max.min(other.max),
+            account_include_max: self.account_include_max.min(other.account_include_max),
+            account_include_any: self.account_include_any && other.account_include_any,
+            account_include_reject: &self.account_include_reject | &other.account_include_reject,
+            account_owner_max: self.account_owner_max.min(other.account_owner_max),
+            account_owner_reject: &self.account_owner_reject | &other.account_owner_reject,
+            accounts_data_slice_max: self
+                .accounts_data_slice_max
+                .min(other.accounts_data_slice_max),
+            include_transactions: self.include_transactions && other.include_transactions,
+            include_accounts: self.include_accounts && other.include_accounts,
+            include_entries: self.include_entries && other.include_entries,
+        }
+    }
+}
+
 impl Default for FilterLimitsBlocks {
     fn default() -> Self {
         Self {
@@ -156,6 +281,9 @@ impl Default for FilterLimitsBlocks {
             account_include_max: usize::MAX,
             account_include_any: true,
             account_include_reject: HashSet::new(),
+            account_owner_max: usize::MAX,
+            account_owner_reject: HashSet::new(),
+            accounts_data_slice_max: usize::MAX,
             include_transactions: true,
             include_accounts: true,
             include_entries: true,
@@ -189,6 +317,19 @@ impl Default for FilterLimitsEntries {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterLimitsNotices {
+    #[serde(deserialize_with = "deserialize_usize_str")]
+    pub max: usize,
+}
+
+impl Default for FilterLimitsNotices {
+    fn default() -> Self {
+        Self { max: usize::MAX }
+    }
+}
+
 fn deserialize_usize_str<'de, D>(deserializer: D) -> Result<usize, D::Error>
 where
     D: Deserializer<'de>,
@@ -209,7 +350,7 @@ where
     }
 }
 
-fn deserialize_pubkey_set<'de, D>(deserializer: D) -> Result<HashSet<Pubkey>, D::Error>
+pub fn deserialize_pubkey_set<'de, D>(deserializer: D) -> Result<HashSet<Pubkey>, D::Error>
 where
     D: Deserializer<'de>,
 {
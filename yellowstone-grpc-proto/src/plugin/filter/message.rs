@@ -3,7 +3,8 @@ use {
         geyser::{
             subscribe_update::UpdateOneof, SlotStatus as SlotStatusProto, SubscribeUpdate,
             SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
-            SubscribeUpdateEntry, SubscribeUpdatePing, SubscribeUpdatePong, SubscribeUpdateSlot,
+            SubscribeUpdateBlockFailure, SubscribeUpdateBlockIncomplete, SubscribeUpdateEntry,
+            SubscribeUpdatePing, SubscribeUpdatePong, SubscribeUpdateSlot, SubscribeUpdateTimings,
             SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
             SubscribeUpdateTransactionStatus,
         },
@@ -11,12 +12,15 @@ use {
             filter::{name::FilterName, FilterAccountsDataSlice},
             message::{
                 MessageAccount, MessageAccountInfo, MessageBlock, MessageBlockMeta, MessageEntry,
-                MessageSlot, MessageTransaction, MessageTransactionInfo,
+                MessageNotice, MessageSlot, MessageTransaction, MessageTransactionInfo,
             },
         },
         solana::storage::confirmed_block,
     },
-    bytes::buf::{Buf, BufMut},
+    bytes::{
+        buf::{Buf, BufMut},
+        Bytes,
+    },
     prost::{
         encoding::{
             encode_key, encode_varint, encoded_len_varint, key_len, message, DecodeContext,
@@ -70,23 +74,42 @@ pub struct FilteredUpdate {
     pub filters: FilteredUpdateFilters,
     pub message: FilteredUpdateOneof,
     pub created_at: Timestamp,
+    /// Encoded update shared between streams, written instead of encoding fields
+    pub encoded: Option<Bytes>,
+    /// Stream specific, encoded after shared fields
+    pub timings: Option<SubscribeUpdateTimings>,
 }
 
 impl prost::Message for FilteredUpdate {
     fn encode_raw(&self, buf: &mut impl BufMut) {
-        for name in self.filters.iter().map(|filter| filter.as_ref()) {
-            encode_key(1u32, WireType::LengthDelimited, buf);
-            encode_varint(name.len() as u64, buf);
-            buf.put_slice(name.as_bytes());
+        if let Some(encoded) = &self.encoded {
+            buf.put_slice(encoded);
+        } else {
+            for name in self.filters.iter().map(|filter| filter.as_ref()) {
+                encode_key(1u32, WireType::LengthDelimited, buf);
+                encode_varint(name.len() as u64, buf);
+                buf.put_slice(name.as_bytes());
+            }
+            self.message.encode_raw(buf);
+            message::encode(11u32, &self.created_at, buf);
+        }
+        if let Some(timings) = &self.timings {
+            message::encode(13u32, timings, buf);
         }
-        self.message.encode_raw(buf);
-        message::encode(11u32, &self.created_at, buf);
     }
 
     fn encoded_len(&self) -> usize {
-        prost_repeated_encoded_len_map!(1u32, self.filters, |filter| filter.as_ref().len())
-            + self.message.encoded_len()
-            + message::encoded_len(11u32, &self.created_at)
+        let len = if let Some(encoded) = &self.encoded {
+            encoded.len()
+        } else {
+            prost_repeated_encoded_len_map!(1u32, self.filters, |filter| filter.as_ref().len())
+                + self.message.encoded_len()
+                + message::encoded_len(11u32, &self.created_at)
+        };
+        len + self
+            .timings
+            .as_ref()
+            .map_or(0, |timings| message::encoded_len(13u32, timings))
     }
 
     fn merge_field(
@@ -114,6 +137,8 @@ impl FilteredUpdate {
             filters,
             message,
             created_at,
+            encoded: None,
+            timings: None,
         }
     }
 
@@ -161,6 +186,12 @@ impl FilteredUpdate {
             hash: message.hash.to_bytes().to_vec(),
             executed_transaction_count: message.executed_transaction_count,
             starting_transaction_index: message.starting_transaction_index,
+            transaction_signatures: message
+                .transaction_signatures
+                .iter()
+                .flatten()
+                .map(|signature| signature.as_ref().to_vec())
+                .collect(),
         }
     }
 
@@ -179,6 +210,7 @@ impl FilteredUpdate {
                 parent: msg.parent,
                 status: msg.status as i32,
                 dead_error: msg.dead_error.clone(),
+                timings: msg.timings,
             }),
             FilteredUpdateOneof::Transaction(msg) => {
                 UpdateOneof::Transaction(SubscribeUpdateTransaction {
@@ -225,6 +257,7 @@ impl FilteredUpdate {
                     .iter()
                     .map(|entry| Self::as_subscribe_update_entry(entry.as_ref()))
                     .collect(),
+                incomplete: msg.incomplete,
             }),
             FilteredUpdateOneof::Ping => UpdateOneof::Ping(SubscribeUpdatePing {}),
             FilteredUpdateOneof::Pong(msg) => UpdateOneof::Pong(*msg),
@@ -232,6 +265,8 @@ impl FilteredUpdate {
             FilteredUpdateOneof::Entry(msg) => {
                 UpdateOneof::Entry(Self::as_subscribe_update_entry(&msg.0))
             }
+            FilteredUpdateOneof::Notice(msg) => UpdateOneof::Notice(msg.notice),
+            FilteredUpdateOneof::BlockFailure(msg) => UpdateOneof::BlockFailure(*msg),
         };
 
         SubscribeUpdate {
@@ -242,6 +277,7 @@ impl FilteredUpdate {
                 .collect(),
             update_oneof: Some(message),
             created_at: Some(self.created_at),
+            timings: self.timings,
         }
     }
 
@@ -295,6 +331,7 @@ impl FilteredUpdate {
                     accounts: block.accounts,
                     accounts_data_slice: FilterAccountsDataSlice::default(),
                     entries: block.entries,
+                    incomplete: block.incomplete,
                 }))
             }
             UpdateOneof::Ping(_) => FilteredUpdateOneof::Ping,
@@ -307,12 +344,19 @@ impl FilteredUpdate {
                 let entry = MessageEntry::from_update_oneof(&msg, created_at)?;
                 FilteredUpdateOneof::Entry(FilteredUpdateEntry(Arc::new(entry)))
             }
+            UpdateOneof::Notice(msg) => {
+                let notice = MessageNotice::from_update_oneof(msg, created_at);
+                FilteredUpdateOneof::Notice(Arc::new(notice))
+            }
+            UpdateOneof::BlockFailure(msg) => FilteredUpdateOneof::BlockFailure(msg),
         };
 
         Ok(Self {
             filters: update.filters.into_iter().map(FilterName::new).collect(),
             message,
             created_at,
+            encoded: None,
+            timings: update.timings,
         })
     }
 }
@@ -330,6 +374,8 @@ pub enum FilteredUpdateOneof {
     Pong(SubscribeUpdatePong),                          // 9
     BlockMeta(Arc<MessageBlockMeta>),                   // 7
     Entry(FilteredUpdateEntry),                         // 8
+    Notice(Arc<MessageNotice>),                         // 12
+    BlockFailure(SubscribeUpdateBlockFailure),          // 14
 }
 
 impl FilteredUpdateOneof {
@@ -379,6 +425,14 @@ impl FilteredUpdateOneof {
     pub const fn entry(message: Arc<MessageEntry>) -> Self {
         Self::Entry(FilteredUpdateEntry(message))
     }
+
+    pub const fn notice(message: Arc<MessageNotice>) -> Self {
+        Self::Notice(message)
+    }
+
+    pub const fn block_failure(message: SubscribeUpdateBlockFailure) -> Self {
+        Self::BlockFailure(message)
+    }
 }
 
 impl prost::Message for FilteredUpdateOneof {
@@ -396,6 +450,8 @@ impl prost::Message for FilteredUpdateOneof {
             Self::Pong(msg) => message::encode(9u32, msg, buf),
             Self::BlockMeta(msg) => message::encode(7u32, &msg.block_meta, buf),
             Self::Entry(msg) => message::encode(8u32, msg, buf),
+            Self::Notice(msg) => message::encode(12u32, &msg.notice, buf),
+            Self::BlockFailure(msg) => message::encode(14u32, msg, buf),
         }
     }
 
@@ -410,6 +466,8 @@ impl prost::Message for FilteredUpdateOneof {
             Self::Pong(msg) => message::encoded_len(9u32, msg),
             Self::BlockMeta(msg) => message::encoded_len(7u32, &msg.block_meta),
             Self::Entry(msg) => message::encoded_len(8u32, msg),
+            Self::Notice(msg) => message::encoded_len(12u32, &msg.notice),
+            Self::BlockFailure(msg) => message::encoded_len(14u32, msg),
         }
     }
 
@@ -578,6 +636,9 @@ impl prost::Message for FilteredUpdateSlot {
         if let Some(error) = &self.dead_error {
             ::prost::encoding::string::encode(4u32, error, buf);
         }
+        if let Some(timings) = &self.timings {
+            message::encode(5u32, timings, buf);
+        }
     }
 
     fn encoded_len(&self) -> usize {
@@ -597,7 +658,10 @@ impl prost::Message for FilteredUpdateSlot {
             ::prost::encoding::string::encoded_len(4u32, error)
         } else {
             0
-        }
+        } + self
+            .timings
+            .as_ref()
+            .map_or(0, |timings| message::encoded_len(5u32, timings))
     }
 
     fn merge_field(
@@ -763,6 +827,7 @@ pub struct FilteredUpdateBlock {
     pub accounts: Vec<Arc<MessageAccountInfo>>,
     pub accounts_data_slice: FilterAccountsDataSlice,
     pub entries: Vec<Arc<MessageEntry>>,
+    pub incomplete: Option<SubscribeUpdateBlockIncomplete>,
 }
 
 impl prost::Message for FilteredUpdateBlock {
@@ -816,6 +881,9 @@ impl prost::Message for FilteredUpdateBlock {
             );
             FilteredUpdateEntry::entry_encode_raw(entry, buf);
         }
+        if let Some(msg) = &self.incomplete {
+            message::encode(14u32, msg, buf);
+        }
     }
 
     fn encoded_len(&self) -> usize {
@@ -879,6 +947,10 @@ impl prost::Message for FilteredUpdateBlock {
             + prost_repeated_encoded_len_map!(13u32, self.entries, |entry| {
                 FilteredUpdateEntry::entry_encoded_len(entry)
             })
+            + self
+                .incomplete
+                .as_ref()
+                .map_or(0, |msg| message::encoded_len(14u32, msg))
     }
 
     fn merge_field(
@@ -943,6 +1015,9 @@ impl FilteredUpdateEntry {
         if entry.starting_transaction_index != 0u64 {
             ::prost::encoding::uint64::encode(6u32, &entry.starting_transaction_index, buf);
         }
+        for signature in entry.transaction_signatures.iter().flatten() {
+            prost_bytes_encode_raw(7u32, signature.as_ref(), buf);
+        }
     }
 
     fn entry_encoded_len(entry: &MessageEntry) -> usize {
@@ -971,6 +1046,12 @@ impl FilteredUpdateEntry {
             } else {
                 0
             }
+            + entry
+                .transaction_signatures
+                .iter()
+                .flatten()
+                .map(|signature| prost_bytes_encoded_len(7u32, signature.as_ref()))
+                .sum::<usize>()
     }
 }
 
@@ -982,12 +1063,16 @@ pub mod tests {
         super::{FilteredUpdate, FilteredUpdateBlock, FilteredUpdateFilters, FilteredUpdateOneof},
         crate::{
             convert_to,
-            geyser::{SubscribeUpdate, SubscribeUpdateBlockMeta},
+            geyser::{
+                SubscribeUpdate, SubscribeUpdateBlockFailure, SubscribeUpdateBlockIncomplete,
+                SubscribeUpdateBlockMeta, SubscribeUpdateSlotTimings, SubscribeUpdateTimings,
+            },
             plugin::{
                 filter::{name::FilterName, FilterAccountsDataSlice},
                 message::{
                     MessageAccount, MessageAccountInfo, MessageBlockMeta, MessageEntry,
-                    MessageSlot, MessageTransaction, MessageTransactionInfo, SlotStatus,
+                    MessageNotice, MessageSlot, MessageTransaction, MessageTransactionInfo,
+                    SlotStatus,
                 },
             },
         },
@@ -1007,7 +1092,7 @@ pub mod tests {
             ops::Range,
             str::FromStr,
             sync::Arc,
-            time::SystemTime,
+            time::{Duration, SystemTime},
         },
     };
 
@@ -1097,6 +1182,7 @@ pub mod tests {
                 hash: Hash::new_from_array([98; 32]),
                 executed_transaction_count: 32,
                 starting_transaction_index: 1000,
+                transaction_signatures: None,
                 created_at: Timestamp::from(SystemTime::now()),
             },
             MessageEntry {
@@ -1106,6 +1192,7 @@ pub mod tests {
                 hash: Hash::new_from_array([42; 32]),
                 executed_transaction_count: 32,
                 starting_transaction_index: 1000,
+                transaction_signatures: None,
                 created_at: Timestamp::from(SystemTime::now()),
             },
         ]
@@ -1217,6 +1304,7 @@ pub mod tests {
                                 accounts: accounts.clone(),
                                 accounts_data_slice: data_slice.clone(),
                                 entries: entries.clone(),
+                                incomplete: None,
                             },
                             FilteredUpdateBlock {
                                 meta: Arc::clone(&block_meta2),
@@ -1225,6 +1313,7 @@ pub mod tests {
                                 accounts: accounts.clone(),
                                 accounts_data_slice: data_slice,
                                 entries: entries.clone(),
+                                incomplete: None,
                             },
                         ]
                     })
@@ -1233,10 +1322,16 @@ pub mod tests {
     }
 
     fn encode_decode_cmp(filters: &[&str], message: FilteredUpdateOneof) {
+        let created_at = Timestamp::from(SystemTime::now());
         let msg = FilteredUpdate {
             filters: create_message_filters(filters),
             message,
-            created_at: Timestamp::from(SystemTime::now()),
+            created_at,
+            encoded: None,
+            timings: Some(SubscribeUpdateTimings {
+                broadcasted: Some(created_at),
+                sent: Some(created_at),
+            }),
         };
         let update = msg.as_subscribe_update();
         assert_eq!(msg.encoded_len(), update.encoded_len());
@@ -1278,6 +1373,7 @@ pub mod tests {
                             parent,
                             status,
                             dead_error: None,
+                            timings: None,
                             created_at: Timestamp::from(SystemTime::now()),
                         }),
                     )
@@ -1289,6 +1385,23 @@ pub mod tests {
                         parent,
                         status: SlotStatus::Dead,
                         dead_error: Some("123".to_owned()),
+                        timings: None,
+                        created_at: Timestamp::from(SystemTime::now()),
+                    }),
+                );
+                encode_decode_cmp(
+                    &["123"],
+                    FilteredUpdateOneof::slot(MessageSlot {
+                        slot,
+                        parent,
+                        status: SlotStatus::Confirmed,
+                        dead_error: None,
+                        timings: Some(SubscribeUpdateSlotTimings {
+                            first_shred_received: Some(Timestamp::from(SystemTime::now())),
+                            processed: Some(Timestamp::from(SystemTime::now())),
+                            confirmed: Some(Timestamp::from(SystemTime::now())),
+                            ..SubscribeUpdateSlotTimings::default()
+                        }),
                         created_at: Timestamp::from(SystemTime::now()),
                     }),
                 )
@@ -1316,6 +1429,49 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_message_block_incomplete() {
+        for block in load_predefined_blocks() {
+            for incomplete in [
+                SubscribeUpdateBlockIncomplete::default(),
+                SubscribeUpdateBlockIncomplete {
+                    expected_transaction_count: Some(block.transactions.len() as u64 + 1),
+                    observed_transaction_count: block.transactions.len() as u64,
+                    expected_entries_count: Some(0),
+                    observed_entries_count: block.entries.len() as u64,
+                },
+            ] {
+                let block = FilteredUpdateBlock {
+                    transactions: vec![],
+                    accounts: vec![],
+                    incomplete: Some(incomplete),
+                    ..block.clone()
+                };
+                encode_decode_cmp(&["123"], FilteredUpdateOneof::block(Box::new(block)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_block_failure() {
+        for incomplete in [
+            None,
+            Some(SubscribeUpdateBlockIncomplete::default()),
+            Some(SubscribeUpdateBlockIncomplete {
+                expected_transaction_count: None,
+                observed_transaction_count: 3,
+                expected_entries_count: None,
+                observed_entries_count: 1,
+            }),
+        ] {
+            let failure = SubscribeUpdateBlockFailure {
+                slot: 42,
+                incomplete,
+            };
+            encode_decode_cmp(&["123"], FilteredUpdateOneof::block_failure(failure));
+        }
+    }
+
     #[test]
     fn test_message_ping() {
         encode_decode_cmp(&["123"], FilteredUpdateOneof::Ping)
@@ -1327,6 +1483,19 @@ pub mod tests {
         encode_decode_cmp(&["123"], FilteredUpdateOneof::pong(42));
     }
 
+    #[test]
+    fn test_message_notice() {
+        for (status, stalled, last_slot) in [
+            (SlotStatus::Processed, true, Some(42)),
+            (SlotStatus::Finalized, false, Some(42)),
+            (SlotStatus::Confirmed, true, None),
+        ] {
+            let notice =
+                MessageNotice::slot_stall(status, stalled, last_slot, Duration::from_millis(1500));
+            encode_decode_cmp(&["123"], FilteredUpdateOneof::notice(Arc::new(notice)));
+        }
+    }
+
     #[test]
     fn test_message_blockmeta() {
         for block_meta in load_predefined_blockmeta() {
@@ -1340,4 +1509,15 @@ pub mod tests {
             encode_decode_cmp(&["123"], FilteredUpdateOneof::entry(entry));
         }
     }
+
+    #[test]
+    fn test_message_entry_signatures() {
+        for entry in create_entries() {
+            let signatures = (0..entry.executed_transaction_count)
+                .map(|i| Signature::from([i as u8; 64]))
+                .collect();
+            let entry = Arc::new(entry.with_transaction_signatures(signatures));
+            encode_decode_cmp(&["123"], FilteredUpdateOneof::entry(entry));
+        }
+    }
 }
@@ -1,5 +1,7 @@
 #[allow(clippy::module_inception)]
 mod filter;
+pub mod encoded;
+pub mod index;
 pub mod limits;
 pub mod message;
 pub mod name;
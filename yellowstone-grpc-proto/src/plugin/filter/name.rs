@@ -77,11 +77,27 @@ impl FilterNames {
         if self.names.len() > self.names_size_limit
             && self.cleanup_ts.elapsed() > self.cleanup_interval
         {
-            self.names.retain(|name| !name.is_uniq());
-            self.cleanup_ts = Instant::now();
+            self.clean();
         }
     }
 
+    /// Remove names not used by any filter regardless of limit and interval,
+    /// returns number of removed names
+    pub fn clean(&mut self) -> usize {
+        let len = self.names.len();
+        self.names.retain(|name| !name.is_uniq());
+        self.cleanup_ts = Instant::now();
+        len - self.names.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
     pub fn get(&mut self, name: &str) -> FilterNameResult<FilterName> {
         match self.names.get(name) {
             Some(name) => Ok(name.clone()),
@@ -2,10 +2,13 @@ use {
     crate::{
         convert_to,
         geyser::{
-            subscribe_update::UpdateOneof, CommitmentLevel as CommitmentLevelProto,
-            SlotStatus as SlotStatusProto, SubscribeUpdateAccount, SubscribeUpdateAccountInfo,
-            SubscribeUpdateBlock, SubscribeUpdateBlockMeta, SubscribeUpdateEntry,
-            SubscribeUpdateSlot, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+            subscribe_update::UpdateOneof, subscribe_update_notice::Notice as NoticeOneof,
+            CommitmentLevel as CommitmentLevelProto, SlotStatus as SlotStatusProto,
+            SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateBlock,
+            SubscribeUpdateBlockIncomplete, SubscribeUpdateBlockMeta, SubscribeUpdateEntry,
+            SubscribeUpdateNotice, SubscribeUpdateNoticeMessagesDropped,
+            SubscribeUpdateNoticeSlotStall, SubscribeUpdateSlot, SubscribeUpdateSlotTimings,
+            SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
         },
         solana::storage::confirmed_block,
     },
@@ -22,7 +25,7 @@ use {
         collections::HashSet,
         ops::{Deref, DerefMut},
         sync::Arc,
-        time::SystemTime,
+        time::{Duration, SystemTime},
     },
 };
 
@@ -149,6 +152,7 @@ pub struct MessageSlot {
     pub parent: Option<Slot>,
     pub status: SlotStatus,
     pub dead_error: Option<String>,
+    pub timings: Option<SubscribeUpdateSlotTimings>,
     pub created_at: Timestamp,
 }
 
@@ -163,6 +167,7 @@ impl MessageSlot {
             } else {
                 None
             },
+            timings: None,
             created_at: Timestamp::from(SystemTime::now()),
         }
     }
@@ -178,6 +183,7 @@ impl MessageSlot {
                 .map_err(|_| "failed to parse slot status")?
                 .into(),
             dead_error: msg.dead_error.clone(),
+            timings: msg.timings,
             created_at,
         })
     }
@@ -368,7 +374,7 @@ impl MessageTransaction {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MessageEntry {
     pub slot: u64,
     pub index: usize,
@@ -376,6 +382,7 @@ pub struct MessageEntry {
     pub hash: Hash,
     pub executed_transaction_count: u64,
     pub starting_transaction_index: u64,
+    pub transaction_signatures: Option<Vec<Signature>>, // set once all transactions of entry are known
     pub created_at: Timestamp,
 }
 
@@ -391,10 +398,19 @@ impl MessageEntry {
                 .starting_transaction_index
                 .try_into()
                 .expect("failed convert usize to u64"),
+            transaction_signatures: None,
             created_at: Timestamp::from(SystemTime::now()),
         }
     }
 
+    pub fn with_transaction_signatures(&self, transaction_signatures: Vec<Signature>) -> Self {
+        Self {
+            transaction_signatures: Some(transaction_signatures),
+            created_at: Timestamp::from(SystemTime::now()),
+            ..self.clone()
+        }
+    }
+
     pub fn from_update_oneof(
         msg: &SubscribeUpdateEntry,
         created_at: Timestamp,
@@ -409,6 +425,17 @@ impl MessageEntry {
             ),
             executed_transaction_count: msg.executed_transaction_count,
             starting_transaction_index: msg.starting_transaction_index,
+            transaction_signatures: if msg.transaction_signatures.is_empty() {
+                None
+            } else {
+                Some(
+                    msg.transaction_signatures
+                        .iter()
+                        .map(|sig| Signature::try_from(sig.as_slice()))
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| "invalid signature length")?,
+                )
+            },
             created_at,
         })
     }
@@ -473,6 +500,7 @@ pub struct MessageBlock {
     pub updated_account_count: u64,
     pub accounts: Vec<Arc<MessageAccountInfo>>,
     pub entries: Vec<Arc<MessageEntry>>,
+    pub incomplete: Option<SubscribeUpdateBlockIncomplete>,
     pub created_at: Timestamp,
 }
 
@@ -489,10 +517,24 @@ impl MessageBlock {
             updated_account_count: accounts.len() as u64,
             accounts,
             entries,
+            incomplete: None,
             created_at: Timestamp::from(SystemTime::now()),
         }
     }
 
+    pub fn new_incomplete(
+        meta: Arc<MessageBlockMeta>,
+        transactions: Vec<Arc<MessageTransactionInfo>>,
+        accounts: Vec<Arc<MessageAccountInfo>>,
+        entries: Vec<Arc<MessageEntry>>,
+        incomplete: SubscribeUpdateBlockIncomplete,
+    ) -> Self {
+        Self {
+            incomplete: Some(incomplete),
+            ..Self::new(meta, transactions, accounts, entries)
+        }
+    }
+
     pub fn from_update_oneof(
         msg: SubscribeUpdateBlock,
         created_at: Timestamp,
@@ -528,11 +570,68 @@ impl MessageBlock {
                 .iter()
                 .map(|entry| MessageEntry::from_update_oneof(entry, created_at).map(Arc::new))
                 .collect::<Result<Vec<_>, _>>()?,
+            incomplete: msg.incomplete,
             created_at,
         })
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageNotice {
+    pub notice: SubscribeUpdateNotice,
+    pub created_at: Timestamp,
+}
+
+impl MessageNotice {
+    pub fn slot_stall(
+        status: SlotStatus,
+        stalled: bool,
+        last_slot: Option<Slot>,
+        since_last_slot: Duration,
+    ) -> Self {
+        Self {
+            notice: SubscribeUpdateNotice {
+                notice: Some(NoticeOneof::SlotStall(SubscribeUpdateNoticeSlotStall {
+                    status: SlotStatusProto::from(status) as i32,
+                    stalled,
+                    last_slot,
+                    since_last_slot_ms: since_last_slot.as_millis() as u64,
+                })),
+            },
+            created_at: Timestamp::from(SystemTime::now()),
+        }
+    }
+
+    pub fn messages_dropped(accounts: u64, transactions: u64, entries: u64) -> Self {
+        Self {
+            notice: SubscribeUpdateNotice {
+                notice: Some(NoticeOneof::MessagesDropped(
+                    SubscribeUpdateNoticeMessagesDropped {
+                        accounts,
+                        transactions,
+                        entries,
+                    },
+                )),
+            },
+            created_at: Timestamp::from(SystemTime::now()),
+        }
+    }
+
+    pub const fn from_update_oneof(msg: SubscribeUpdateNotice, created_at: Timestamp) -> Self {
+        Self {
+            notice: msg,
+            created_at,
+        }
+    }
+
+    pub fn get_slot(&self) -> Slot {
+        match &self.notice.notice {
+            Some(NoticeOneof::SlotStall(msg)) => msg.last_slot.unwrap_or_default(),
+            Some(NoticeOneof::MessagesDropped(_)) | None => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     Slot(MessageSlot),
@@ -541,6 +640,7 @@ pub enum Message {
     Entry(Arc<MessageEntry>),
     BlockMeta(Arc<MessageBlockMeta>),
     Block(Arc<MessageBlock>),
+    Notice(Arc<MessageNotice>),
 }
 
 impl Message {
@@ -552,6 +652,19 @@ impl Message {
             Self::Entry(msg) => msg.slot,
             Self::BlockMeta(msg) => msg.slot,
             Self::Block(msg) => msg.meta.slot,
+            Self::Notice(msg) => msg.get_slot(),
+        }
+    }
+
+    pub fn get_created_at(&self) -> Timestamp {
+        match self {
+            Self::Slot(msg) => msg.created_at,
+            Self::Account(msg) => msg.created_at,
+            Self::Transaction(msg) => msg.created_at,
+            Self::Entry(msg) => msg.created_at,
+            Self::BlockMeta(msg) => msg.created_at,
+            Self::Block(msg) => msg.created_at,
+            Self::Notice(msg) => msg.created_at,
         }
     }
 
@@ -581,6 +694,10 @@ impl Message {
             UpdateOneof::Entry(msg) => {
                 Self::Entry(Arc::new(MessageEntry::from_update_oneof(&msg, created_at)?))
             }
+            UpdateOneof::Notice(msg) => {
+                Self::Notice(Arc::new(MessageNotice::from_update_oneof(msg, created_at)))
+            }
+            UpdateOneof::BlockFailure(_) => return Err("BlockFailure message is not supported"),
         })
     }
 }